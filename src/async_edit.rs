@@ -0,0 +1,805 @@
+//! Async versions of the editing functions, which wait for the editor and read and write the
+//! temporary file without blocking the async runtime.
+//!
+//! The functions themselves don't depend on a particular runtime; the `rt` module provides the
+//! few operations that do, using [tokio](https://tokio.rs) if the `tokio` feature is enabled and
+//! the runtime-agnostic [async-process](https://docs.rs/async-process) otherwise (which works
+//! with async-std, smol, and any other executor).
+
+use crate::{
+    lock::{self, Lock},
+    recover,
+    session::{check_status, FileState, POLL_INTERVAL},
+    Cancelled, Editor, LockPolicy, TempOptions, WaitFor,
+};
+use futures_core::Stream;
+use std::{
+    fmt, fs,
+    future::{self, Future},
+    io::{Error, ErrorKind, Result},
+    path::Path,
+    pin::{pin, Pin},
+    process::ExitStatus,
+    task::{Context, Poll},
+    time::{Duration, Instant, SystemTime},
+};
+use tempfile::TempPath;
+
+/// Open the contents of a string or buffer in the [default editor] without blocking the async
+/// runtime.
+///
+/// This is the same as [`edit`], except that the editor is waited for and the temporary file is
+/// written and read asynchronously, using either tokio or async-std/smol depending on which
+/// feature is enabled (see the [crate documentation](index.html#features)).
+///
+/// ```rust,ignore
+/// let edited = edit::edit_async("Hello, _____!").await?;
+/// ```
+///
+/// [default editor]: fn.get_editor.html
+/// [`edit`]: fn.edit.html
+pub async fn edit_async<S: AsRef<[u8]>>(text: S) -> Result<String> {
+    Editor::new().edit_async(text).await
+}
+
+/// Open the contents of a string or buffer in the [default editor] without blocking the async
+/// runtime and return them as raw bytes.
+///
+/// See [`edit_async`], the version of this function that takes and returns [`String`].
+///
+/// [default editor]: fn.get_editor.html
+/// [`edit_async`]: fn.edit_async.html
+/// [`String`]: https://doc.rust-lang.org/std/string/struct.String.html
+pub async fn edit_bytes_async<B: AsRef<[u8]>>(buf: B) -> Result<Vec<u8>> {
+    Editor::new().edit_bytes_async(buf).await
+}
+
+/// Open an existing file (or create a new one, depending on the editor's behavior) in the
+/// [default editor] and wait for the editor to exit without blocking the async runtime.
+///
+/// This is the async version of [`edit_file`].
+///
+/// [default editor]: fn.get_editor.html
+/// [`edit_file`]: fn.edit_file.html
+pub async fn edit_file_async<P: AsRef<Path>>(file: P) -> Result<()> {
+    Editor::new().edit_file_async(file).await
+}
+
+/// Open the contents of a string or buffer in the [default editor] and get the contents of the
+/// temporary file every time it is saved.
+///
+/// This is useful for e.g. showing a live preview of a document while it is being edited. The
+/// returned [`SaveStream`] yields the contents of the file after each save, and ends once the
+/// editor exits (yielding an error instead if the editor fails). The temporary file is deleted
+/// when the stream is dropped.
+///
+/// ```rust,ignore
+/// use futures_lite::StreamExt;
+///
+/// let mut saves = edit::edit_watch_async("# Title\n").await?;
+/// while let Some(contents) = saves.next().await {
+///     render_preview(&contents?);
+/// }
+/// ```
+///
+/// [default editor]: fn.get_editor.html
+/// [`SaveStream`]: struct.SaveStream.html
+pub async fn edit_watch_async<B: AsRef<[u8]>>(buf: B) -> Result<SaveStream> {
+    Editor::new().edit_watch_async(buf).await
+}
+
+/// Open the contents of `reader` in the [default editor] without reading it all into memory.
+///
+/// This is like [`edit_bytes_async`], but the contents are copied from `reader` into the
+/// temporary file, and the edited file is returned as an [`EditedReader`] to be read back
+/// incrementally. This is useful for very large buffers that shouldn't be held in memory all at
+/// once.
+///
+/// `reader` is a [`tokio::io::AsyncRead`] with the `tokio` feature and a
+/// [`futures_io::AsyncRead`] otherwise.
+///
+/// ```rust,ignore
+/// let input = tokio::fs::File::open("huge.log").await?;
+/// let mut edited = edit::edit_stream_async(input).await?;
+/// tokio::io::copy(&mut edited, &mut tokio::io::stdout()).await?;
+/// ```
+///
+/// [default editor]: fn.get_editor.html
+/// [`edit_bytes_async`]: fn.edit_bytes_async.html
+/// [`EditedReader`]: struct.EditedReader.html
+/// [`tokio::io::AsyncRead`]: https://docs.rs/tokio/1/tokio/io/trait.AsyncRead.html
+/// [`futures_io::AsyncRead`]: https://docs.rs/futures-io/0.3/futures_io/trait.AsyncRead.html
+pub async fn edit_stream_async<R: rt::AsyncRead + Unpin>(reader: R) -> Result<EditedReader> {
+    Editor::new().edit_stream_async(reader).await
+}
+
+impl Editor {
+    /// Open the contents of a string or buffer in the editor without blocking the async runtime.
+    /// See [`edit_async`].
+    ///
+    /// [`edit_async`]: fn.edit_async.html
+    pub async fn edit_async<S: AsRef<[u8]>>(&self, text: S) -> Result<String> {
+        let text = self.template(text.as_ref())?;
+        self.edit_temp_async(&text, &TempOptions::new(), |edited| {
+            self.finish_text(edited)
+        })
+        .await
+    }
+
+    /// Open the contents of a string or buffer in the editor without blocking the async runtime
+    /// and return them as raw bytes. See [`edit_bytes_async`].
+    ///
+    /// [`edit_bytes_async`]: fn.edit_bytes_async.html
+    pub async fn edit_bytes_async<B: AsRef<[u8]>>(&self, buf: B) -> Result<Vec<u8>> {
+        self.edit_bytes_with_builder_async(buf, &TempOptions::new())
+            .await
+    }
+
+    /// Open the contents of a string or buffer in the editor without blocking the async
+    /// runtime, using a temporary file created with the options in `builder`, and return them as
+    /// raw bytes.
+    ///
+    /// Options that aren't set in `builder` are taken from this `Editor`.
+    pub async fn edit_bytes_with_builder_async<B: AsRef<[u8]>>(
+        &self,
+        buf: B,
+        builder: &TempOptions,
+    ) -> Result<Vec<u8>> {
+        self.edit_temp_async(buf.as_ref(), builder, Ok).await
+    }
+
+    /// Edit `buf` in a temporary file created with `options`, and turn the edited contents into
+    /// the result with `finish`.
+    async fn edit_temp_async<T, F: FnOnce(Vec<u8>) -> Result<T>>(
+        &self,
+        buf: &[u8],
+        options: &TempOptions,
+        finish: F,
+    ) -> Result<T> {
+        if let Some(file) = self.memory_file(buf)? {
+            self.open_and_wait_async(file.path()).await?;
+            return finish(rt::read(file.path()).await?);
+        }
+
+        // creating the (empty) file is quick, so it isn't worth moving to a blocking thread
+        let path = self.temp_file(options, Some(buf))?.into_temp_path();
+        rt::write(&path, buf).await?;
+
+        let edited = match self.open_and_wait_async(&path).await {
+            Ok(()) => rt::read(&path).await.and_then(finish),
+            Err(e) => Err(e),
+        };
+        match edited {
+            Ok(edited) => {
+                self.remove_temp_async(path).await?;
+                Ok(edited)
+            }
+            Err(e) if self.keep_on_error && !self.secure => Err(recover::keep(path, buf, e)),
+            Err(e) => {
+                let _ = self.remove_temp_async(path).await;
+                Err(e)
+            }
+        }
+    }
+
+    /// Delete a temporary file that was edited, overwriting it first in secure mode.
+    async fn remove_temp_async(&self, path: TempPath) -> Result<()> {
+        if self.secure {
+            rt::shred(&path).await?;
+        }
+        path.close()
+    }
+
+    /// Open the contents of `reader` in the editor without reading it all into memory. See
+    /// [`edit_stream_async`].
+    ///
+    /// [`Editor::secure`] isn't supported, since the temporary file is deleted when the returned
+    /// reader is dropped, where it can't be overwritten without blocking, and makes this return
+    /// [`ErrorKind::Unsupported`].
+    ///
+    /// [`edit_stream_async`]: fn.edit_stream_async.html
+    /// [`Editor::secure`]: struct.Editor.html#method.secure
+    /// [`ErrorKind::Unsupported`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.Unsupported
+    pub async fn edit_stream_async<R: rt::AsyncRead + Unpin>(
+        &self,
+        reader: R,
+    ) -> Result<EditedReader> {
+        if self.secure {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "secure mode isn't supported when streaming the edited file",
+            ));
+        }
+
+        let path = self.temp_file(&TempOptions::new(), None)?.into_temp_path();
+        rt::write_from(&path, reader).await?;
+        // make sure a save right away is noticed even on filesystems with coarse timestamps
+        let _ = fs::OpenOptions::new()
+            .write(true)
+            .open(&path)
+            .and_then(|f| f.set_modified(SystemTime::now() - Duration::from_secs(60)));
+        let written = FileState::of(&path);
+
+        let file = match self.open_and_wait_async(&path).await {
+            Ok(()) => rt::open(&path).await,
+            Err(e) => Err(e),
+        };
+        match file {
+            Ok(file) => Ok(EditedReader { file, _path: path }),
+            // the original contents aren't kept in memory, so keep the file if it was saved at all
+            Err(e) if self.keep_on_error => {
+                let changed = FileState::of(&path) != written;
+                Err(recover::keep_if_changed(path, changed, e))
+            }
+            Err(e) => {
+                let _ = self.remove_temp_async(path).await;
+                Err(e)
+            }
+        }
+    }
+
+    /// Open the contents of a string or buffer in the editor and get the contents of the
+    /// temporary file every time it is saved. See [`edit_watch_async`].
+    ///
+    /// [`Editor::timeout`] and [`Editor::cancel_handle`] aren't supported yet, and neither is
+    /// [`Editor::secure`] (since the temporary file is deleted when the stream is dropped, where it
+    /// can't be overwritten without blocking); they make this return [`ErrorKind::Unsupported`].
+    /// [`Editor::wait_for`] is ignored. (Drop the stream to
+    /// stop watching; the editor is left running.) With [`Editor::noninteractive_content`] (or
+    /// its environment variable) set, no editor is opened, and the stream yields the content once
+    /// before ending.
+    ///
+    /// [`edit_watch_async`]: fn.edit_watch_async.html
+    /// [`Editor::timeout`]: struct.Editor.html#method.timeout
+    /// [`Editor::cancel_handle`]: struct.Editor.html#method.cancel_handle
+    /// [`Editor::secure`]: struct.Editor.html#method.secure
+    /// [`Editor::wait_for`]: struct.Editor.html#method.wait_for
+    /// [`Editor::noninteractive_content`]: struct.Editor.html#method.noninteractive_content
+    /// [`ErrorKind::Unsupported`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.Unsupported
+    pub async fn edit_watch_async<B: AsRef<[u8]>>(&self, buf: B) -> Result<SaveStream> {
+        if self.timeout.is_some() || self.cancel.is_some() {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "timeout and cancel_handle aren't supported when watching for saves",
+            ));
+        }
+        if self.secure {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "secure mode isn't supported when watching for saves",
+            ));
+        }
+        if let Some(content) = self.scripted_content() {
+            // the content stands in for a single save before the editor exits
+            return Ok(SaveStream {
+                next: Some(Box::pin(async move { Some((Ok(content), None)) })),
+            });
+        }
+
+        let path = self.temp_file(&TempOptions::new(), None)?.into_temp_path();
+        rt::write(&path, buf.as_ref()).await?;
+        // make sure a save right away is noticed even on filesystems with coarse timestamps
+        let _ = fs::OpenOptions::new()
+            .write(true)
+            .open(&path)
+            .and_then(|f| f.set_modified(SystemTime::now() - Duration::from_secs(60)));
+
+        let (mut child, description, accepted_codes) = self.spawn_async(&path)?;
+        let watch = Watch {
+            exit: Box::pin(async move { rt::wait(&mut child).await }),
+            last: FileState::of(&path),
+            path,
+            description,
+            accepted_codes,
+            done: false,
+        };
+        Ok(SaveStream {
+            next: Some(Box::pin(watch.next())),
+        })
+    }
+
+    /// Open an existing file (or create a new one, depending on the editor's behavior) in the
+    /// editor and wait for the editor to exit without blocking the async runtime. See
+    /// [`edit_file_async`].
+    ///
+    /// [`Editor::pty`], [`Editor::forward_signals`], and [`WaitFor::Save`] aren't supported
+    /// yet, and make this return [`ErrorKind::Unsupported`].
+    ///
+    /// [`edit_file_async`]: fn.edit_file_async.html
+    /// [`Editor::pty`]: struct.Editor.html#method.pty
+    /// [`Editor::forward_signals`]: struct.Editor.html#method.forward_signals
+    /// [`WaitFor::Save`]: enum.WaitFor.html#variant.Save
+    /// [`ErrorKind::Unsupported`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.Unsupported
+    pub async fn edit_file_async<P: AsRef<Path>>(&self, file: P) -> Result<()> {
+        let file = file.as_ref();
+        if self.leaves_own_files() {
+            return Ok(());
+        }
+        let _lock = self.lock_async(file).await?;
+        self.open_and_wait_async(file).await
+    }
+
+    /// Lock `file` as set with [`Editor::lock`], without blocking the async runtime.
+    ///
+    /// [`Editor::lock`]: struct.Editor.html#method.lock
+    async fn lock_async(&self, file: &Path) -> Result<Option<Lock>> {
+        if self.lock_policy == LockPolicy::None {
+            return Ok(None);
+        }
+        let start = Instant::now();
+        loop {
+            match (lock::try_acquire(file)?, self.lock_policy) {
+                (Some(lock), _) => return Ok(Some(lock)),
+                (None, LockPolicy::Wait(timeout)) if start.elapsed() < timeout => {
+                    rt::sleep(POLL_INTERVAL.min(timeout - start.elapsed())).await;
+                }
+                (None, policy) => return Err(lock::busy(file, policy)),
+            }
+        }
+    }
+
+    /// Open `file` in the editor and wait for the editor to exit without blocking the async
+    /// runtime, without locking it.
+    async fn open_and_wait_async(&self, file: &Path) -> Result<()> {
+        if let Some(content) = self.scripted_content() {
+            return rt::write(file, &content).await;
+        }
+        if self.wait_for == WaitFor::Save {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "WaitFor::Save isn't supported when editing asynchronously",
+            ));
+        }
+
+        let (mut child, description, accepted_codes) = self.spawn_async(file)?;
+
+        let outcome = {
+            let exited = async {
+                match self.timeout {
+                    Some(timeout) => match rt::timeout(timeout, rt::wait(&mut child)).await {
+                        Some(result) => Outcome::Exited(result),
+                        None => Outcome::TimedOut(timeout),
+                    },
+                    None => Outcome::Exited(rt::wait(&mut child).await),
+                }
+            };
+            match &self.cancel {
+                Some(cancel) => {
+                    let cancelled = async {
+                        cancel.cancelled().await;
+                        Outcome::Cancelled
+                    };
+                    race(exited, cancelled).await
+                }
+                None => exited.await,
+            }
+        };
+
+        let (status, stderr) = match outcome {
+            Outcome::Exited(result) => result?,
+            Outcome::TimedOut(timeout) => {
+                rt::kill_tree(&mut child)?;
+                rt::reap(&mut child).await?;
+                return Err(Error::new(
+                    ErrorKind::TimedOut,
+                    format!(
+                        "editor '{}' was killed after running for {:?}",
+                        description, timeout
+                    ),
+                ));
+            }
+            Outcome::Cancelled => {
+                rt::kill_tree(&mut child)?;
+                rt::reap(&mut child).await?;
+                return Err(Cancelled.into());
+            }
+        };
+
+        check_status(&description, status, accepted_codes.as_deref(), stderr)
+    }
+
+    /// Spawn the first editor that can be started on `file` (see [`Editor::edit_file_session`]),
+    /// returning it along with a description of it and the exit codes it may exit with.
+    fn spawn_async(&self, file: &Path) -> Result<(rt::Child, String, Option<Vec<i32>>)> {
+        if self.pty || self.forward_signals {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "pty and forward_signals aren't supported when editing asynchronously",
+            ));
+        }
+
+        self.spawn_first(&[file], |candidate, files| {
+            let (command, description, accepted_codes) = self.editor_command(candidate, files);
+            let child = rt::spawn(command, [self.stdin, self.stdout, self.stderr])?;
+            Ok((child, description, accepted_codes))
+        })
+    }
+}
+
+/// The edited contents of a temporary file, as returned by [`edit_stream_async`].
+///
+/// This implements [`tokio::io::AsyncRead`] with the `tokio` feature and
+/// [`futures_io::AsyncRead`] otherwise. The temporary file is deleted when this is dropped.
+///
+/// [`edit_stream_async`]: fn.edit_stream_async.html
+/// [`tokio::io::AsyncRead`]: https://docs.rs/tokio/1/tokio/io/trait.AsyncRead.html
+/// [`futures_io::AsyncRead`]: https://docs.rs/futures-io/0.3/futures_io/trait.AsyncRead.html
+#[derive(Debug)]
+pub struct EditedReader {
+    file: rt::File,
+    // deletes the file when dropped
+    _path: TempPath,
+}
+
+type BoxFuture<T> = Pin<Box<dyn Future<Output = T> + Send>>;
+
+// the next item of a SaveStream and the state to continue from (if it doesn't end after the
+// item), or None once it has ended
+type Step = Option<(Result<Vec<u8>>, Option<Watch>)>;
+
+/// A [`Stream`] of the contents of a temporary file every time it is saved, as returned by
+/// [`edit_watch_async`].
+///
+/// Each item is the contents of the file after a save. The stream ends once the editor exits; if
+/// the editor exits with an error, the last item is that error. Dropping the stream deletes the
+/// temporary file.
+///
+/// [`Stream`]: https://docs.rs/futures-core/0.3/futures_core/stream/trait.Stream.html
+/// [`edit_watch_async`]: fn.edit_watch_async.html
+pub struct SaveStream {
+    next: Option<BoxFuture<Step>>,
+}
+
+impl fmt::Debug for SaveStream {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SaveStream").finish()
+    }
+}
+
+impl Stream for SaveStream {
+    type Item = Result<Vec<u8>>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        let next = match &mut self.next {
+            Some(next) => next,
+            None => return Poll::Ready(None),
+        };
+        match next.as_mut().poll(cx) {
+            Poll::Ready(Some((item, watch))) => {
+                self.next = watch.map(|watch| Box::pin(watch.next()) as BoxFuture<Step>);
+                Poll::Ready(Some(item))
+            }
+            Poll::Ready(None) => {
+                self.next = None;
+                Poll::Ready(None)
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// The state of a [`SaveStream`] between items.
+struct Watch {
+    exit: BoxFuture<Result<(ExitStatus, Option<Vec<u8>>)>>,
+    path: TempPath,
+    last: Option<FileState>,
+    description: String,
+    accepted_codes: Option<Vec<i32>>,
+    done: bool,
+}
+
+impl Watch {
+    /// Wait for the next save (or for the editor to exit), returning the item to yield and the
+    /// state to continue from.
+    async fn next(mut self) -> Step {
+        if self.done {
+            return None;
+        }
+
+        loop {
+            let exit = self.exit.as_mut();
+            let exited = race(async { Some(exit.await) }, async {
+                rt::sleep(POLL_INTERVAL).await;
+                None
+            })
+            .await;
+
+            if let Some(result) = exited {
+                self.done = true;
+                let status = result.and_then(|(status, stderr)| {
+                    check_status(
+                        &self.description,
+                        status,
+                        self.accepted_codes.as_deref(),
+                        stderr,
+                    )
+                });
+                return match status {
+                    Err(e) => Some((Err(e), Some(self))),
+                    // the file may have been saved right before the editor exited
+                    Ok(()) if self.saved() => {
+                        let contents = rt::read(&self.path).await;
+                        Some((contents, Some(self)))
+                    }
+                    Ok(()) => None,
+                };
+            }
+
+            if self.saved() {
+                // wait for the file to settle in case the editor is still writing it
+                loop {
+                    rt::sleep(POLL_INTERVAL).await;
+                    if !self.saved() {
+                        break;
+                    }
+                }
+                let contents = rt::read(&self.path).await;
+                return Some((contents, Some(self)));
+            }
+        }
+    }
+
+    /// Check whether the file has changed since it was last checked.
+    fn saved(&mut self) -> bool {
+        let state = FileState::of(&self.path);
+        if state.is_some() && state != self.last {
+            self.last = state;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// How waiting for the editor ended.
+enum Outcome {
+    Exited(Result<(ExitStatus, Option<Vec<u8>>)>),
+    TimedOut(Duration),
+    Cancelled,
+}
+
+/// Run `a` and `b` concurrently, returning the output of whichever finishes first.
+async fn race<T>(a: impl Future<Output = T>, b: impl Future<Output = T>) -> T {
+    let mut a = pin!(a);
+    let mut b = pin!(b);
+    future::poll_fn(|cx| match a.as_mut().poll(cx) {
+        Poll::Ready(output) => Poll::Ready(output),
+        Poll::Pending => b.as_mut().poll(cx),
+    })
+    .await
+}
+
+#[cfg(feature = "tokio")]
+mod rt {
+    use super::EditedReader;
+    use crate::{secure::ZEROS, session::kill_tree as kill_pid_tree, StdioMode};
+    use std::{
+        future::Future,
+        io::Result,
+        path::Path,
+        process::{self, ExitStatus},
+        time::Duration,
+    };
+    use std::{
+        pin::Pin,
+        task::{Context, Poll},
+    };
+    use tokio::{
+        fs,
+        io::{self, AsyncReadExt, AsyncWriteExt, ReadBuf},
+        process::Command,
+        time,
+    };
+    pub use tokio::{fs::File, io::AsyncRead, process::Child};
+
+    // tokio keeps the stdio set up on `command`, so `_stdio` doesn't need to be applied again
+    pub fn spawn(command: process::Command, _stdio: [StdioMode; 3]) -> Result<Child> {
+        Command::from(command).spawn()
+    }
+
+    pub async fn write(path: &Path, buf: &[u8]) -> Result<()> {
+        fs::write(path, buf).await
+    }
+
+    pub async fn read(path: &Path) -> Result<Vec<u8>> {
+        fs::read(path).await
+    }
+
+    /// Overwrite the contents of the file at `path` with zeros.
+    pub async fn shred(path: &Path) -> Result<()> {
+        let mut file = fs::OpenOptions::new().write(true).open(path).await?;
+        let mut len = file.metadata().await?.len();
+        while len > 0 {
+            let n = len.min(ZEROS.len() as u64) as usize;
+            file.write_all(&ZEROS[..n]).await?;
+            len -= n as u64;
+        }
+        file.sync_all().await
+    }
+
+    /// Copy everything from `reader` to the file at `path`.
+    pub async fn write_from<R: AsyncRead + Unpin>(path: &Path, mut reader: R) -> Result<()> {
+        let mut file = File::create(path).await?;
+        io::copy(&mut reader, &mut file).await?;
+        file.flush().await
+    }
+
+    pub async fn open(path: &Path) -> Result<File> {
+        File::open(path).await
+    }
+
+    pub async fn sleep(duration: Duration) {
+        time::sleep(duration).await
+    }
+
+    /// Run `future`, giving up after `timeout` has passed.
+    pub async fn timeout<F: Future>(timeout: Duration, future: F) -> Option<F::Output> {
+        time::timeout(timeout, future).await.ok()
+    }
+
+    /// Wait for `child` to exit, closing a piped stdin, discarding anything written to a piped
+    /// stdout, and capturing a piped stderr.
+    pub async fn wait(child: &mut Child) -> Result<(ExitStatus, Option<Vec<u8>>)> {
+        drop(child.stdin.take());
+        let stdout = child.stdout.take();
+        let stderr = child.stderr.take();
+
+        let drain = async {
+            if let Some(mut stdout) = stdout {
+                let _ = io::copy(&mut stdout, &mut io::sink()).await;
+            }
+        };
+        let capture = async {
+            let mut stderr = stderr?;
+            let mut buf = Vec::new();
+            let _ = stderr.read_to_end(&mut buf).await;
+            Some(buf)
+        };
+
+        let (status, (), stderr) = tokio::join!(child.wait(), drain, capture);
+        Ok((status?, stderr))
+    }
+
+    pub fn kill_tree(child: &mut Child) -> Result<()> {
+        match child.id() {
+            Some(pid) => kill_pid_tree(pid, || child.start_kill()),
+            // the editor has already exited
+            None => Ok(()),
+        }
+    }
+
+    /// Wait for a killed `child` to exit.
+    pub async fn reap(child: &mut Child) -> Result<()> {
+        child.wait().await.map(drop)
+    }
+
+    impl AsyncRead for EditedReader {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context,
+            buf: &mut ReadBuf,
+        ) -> Poll<Result<()>> {
+            Pin::new(&mut self.file).poll_read(cx, buf)
+        }
+    }
+}
+
+#[cfg(not(feature = "tokio"))]
+mod rt {
+    use super::EditedReader;
+    use crate::{secure::ZEROS, session::kill_tree as kill_pid_tree, StdioMode};
+    pub use async_fs::File;
+    use async_io::Timer;
+    pub use async_process::Child;
+    use async_process::Command;
+    pub use futures_lite::AsyncRead;
+    use futures_lite::{future, io, AsyncReadExt, AsyncWriteExt};
+    use std::{
+        future::Future,
+        io::Result,
+        path::Path,
+        process::{self, ExitStatus},
+        time::Duration,
+    };
+    use std::{
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    // the stdio set up on `command` is forgotten by the conversion, so it has to be set again
+    pub fn spawn(command: process::Command, stdio: [StdioMode; 3]) -> Result<Child> {
+        let [stdin, stdout, stderr] = stdio;
+        Command::from(command)
+            .stdin(stdin)
+            .stdout(stdout)
+            .stderr(stderr)
+            .spawn()
+    }
+
+    pub async fn write(path: &Path, buf: &[u8]) -> Result<()> {
+        async_fs::write(path, buf).await
+    }
+
+    pub async fn read(path: &Path) -> Result<Vec<u8>> {
+        async_fs::read(path).await
+    }
+
+    /// Overwrite the contents of the file at `path` with zeros.
+    pub async fn shred(path: &Path) -> Result<()> {
+        let mut file = async_fs::OpenOptions::new().write(true).open(path).await?;
+        let mut len = file.metadata().await?.len();
+        while len > 0 {
+            let n = len.min(ZEROS.len() as u64) as usize;
+            file.write_all(&ZEROS[..n]).await?;
+            len -= n as u64;
+        }
+        file.sync_all().await
+    }
+
+    /// Copy everything from `reader` to the file at `path`.
+    pub async fn write_from<R: AsyncRead + Unpin>(path: &Path, reader: R) -> Result<()> {
+        let mut file = File::create(path).await?;
+        io::copy(reader, &mut file).await?;
+        file.flush().await
+    }
+
+    pub async fn open(path: &Path) -> Result<File> {
+        File::open(path).await
+    }
+
+    pub async fn sleep(duration: Duration) {
+        Timer::after(duration).await;
+    }
+
+    /// Run `future`, giving up after `timeout` has passed.
+    pub async fn timeout<F: Future>(timeout: Duration, future: F) -> Option<F::Output> {
+        future::or(async { Some(future.await) }, async {
+            Timer::after(timeout).await;
+            None
+        })
+        .await
+    }
+
+    /// Wait for `child` to exit, closing a piped stdin, discarding anything written to a piped
+    /// stdout, and capturing a piped stderr.
+    pub async fn wait(child: &mut Child) -> Result<(ExitStatus, Option<Vec<u8>>)> {
+        drop(child.stdin.take());
+        let stdout = child.stdout.take();
+        let stderr = child.stderr.take();
+
+        let drain = async {
+            if let Some(stdout) = stdout {
+                let _ = io::copy(stdout, &mut io::sink()).await;
+            }
+        };
+        let capture = async {
+            let mut stderr = stderr?;
+            let mut buf = Vec::new();
+            let _ = stderr.read_to_end(&mut buf).await;
+            Some(buf)
+        };
+
+        let ((status, ()), stderr) = future::zip(future::zip(child.status(), drain), capture).await;
+        Ok((status?, stderr))
+    }
+
+    pub fn kill_tree(child: &mut Child) -> Result<()> {
+        let pid = child.id();
+        kill_pid_tree(pid, || child.kill())
+    }
+
+    /// Wait for a killed `child` to exit.
+    pub async fn reap(child: &mut Child) -> Result<()> {
+        child.status().await.map(drop)
+    }
+
+    impl AsyncRead for EditedReader {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context,
+            buf: &mut [u8],
+        ) -> Poll<Result<usize>> {
+            Pin::new(&mut self.file).poll_read(cx, buf)
+        }
+    }
+}
@@ -0,0 +1,176 @@
+//! Editing copies of files that replace the originals only once editing succeeds.
+
+use crate::attributes;
+#[cfg(unix)]
+use std::os::unix::fs::PermissionsExt;
+use std::{
+    error,
+    ffi::OsString,
+    fmt,
+    fs::{self, File, OpenOptions},
+    io::{Error, ErrorKind, Read, Result, Write},
+    path::{Path, PathBuf},
+};
+use tempfile::{Builder, NamedTempFile, TempPath};
+
+/// The copy of a file being edited.
+pub(crate) struct Copy {
+    /// The file the copy replaces, with any symlinks resolved so they aren't replaced instead.
+    pub(crate) target: PathBuf,
+    pub(crate) path: TempPath,
+    pub(crate) original: Vec<u8>,
+    source: Option<File>,
+}
+
+impl Copy {
+    /// Replace the original file with the copy, containing `edited`, giving the copy the
+    /// original's attributes. If the original was changed since it was copied, a [`Conflict`] is
+    /// returned instead.
+    ///
+    /// The attributes are only copied now since editors can change them while saving (e.g. writing
+    /// to a file clears its setuid bit, and some editors save by replacing the file).
+    pub(crate) fn replace(self, edited: Vec<u8>) -> Result<()> {
+        let theirs = match fs::read(&self.target) {
+            Ok(theirs) => Some(theirs),
+            Err(e) if e.kind() == ErrorKind::NotFound => None,
+            Err(e) => return Err(e),
+        };
+        let original = self.source.as_ref().map(|_| &self.original);
+        if theirs.as_ref() != original {
+            return Err(Conflict::error(self.target, theirs, edited));
+        }
+
+        let copy = OpenOptions::new().write(true).open(&self.path)?;
+        if let Some(source) = &self.source {
+            attributes::copy(source, &copy)?;
+        }
+        // make sure the new contents are on disk before they replace the old ones
+        copy.sync_all()?;
+        self.path.persist(&self.target).map_err(|e| e.error)
+    }
+}
+
+/// The error returned when a file was changed by another program while a copy of it was being
+/// edited with [`edit_file_atomic`] or [`edit_range`], so replacing the file with the copy would
+/// throw away the other program's changes.
+///
+/// This is wrapped in an [`io::Error`] of kind [`ErrorKind::Other`], and can be retrieved with
+/// [`io::Error::get_ref`]. The file is left as the other program wrote it, and the edited copy is
+/// deleted, but both versions are included so the caller can e.g. merge them or ask the user which
+/// to keep:
+///
+/// ```rust,ignore
+/// use edit::Conflict;
+///
+/// if let Err(e) = edit::edit_file_atomic("notes.txt") {
+///     if let Some(conflict) = e.get_ref().and_then(|e| e.downcast_ref::<Conflict>()) {
+///         std::fs::write("notes.txt.mine", conflict.ours())?;
+///     }
+/// }
+/// ```
+///
+/// [`edit_file_atomic`]: fn.edit_file_atomic.html
+/// [`edit_range`]: fn.edit_range.html
+/// [`io::Error`]: https://doc.rust-lang.org/std/io/struct.Error.html
+/// [`ErrorKind::Other`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.Other
+/// [`io::Error::get_ref`]: https://doc.rust-lang.org/std/io/struct.Error.html#method.get_ref
+#[derive(Debug)]
+pub struct Conflict {
+    path: PathBuf,
+    theirs: Option<Vec<u8>>,
+    ours: Vec<u8>,
+}
+
+impl Conflict {
+    /// Get the error for when `path` was changed to `theirs` while being edited to `ours`.
+    pub(crate) fn error(path: PathBuf, theirs: Option<Vec<u8>>, ours: Vec<u8>) -> Error {
+        Error::other(Conflict { path, theirs, ours })
+    }
+
+    /// Get the path of the file that was changed.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Get the contents the other program gave the file, or `None` if it deleted the file.
+    pub fn theirs(&self) -> Option<&[u8]> {
+        self.theirs.as_deref()
+    }
+
+    /// Get the contents of the edited copy (or with [`edit_range`], just the edited lines).
+    ///
+    /// [`edit_range`]: fn.edit_range.html
+    pub fn ours(&self) -> &[u8] {
+        &self.ours
+    }
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "'{}' was changed by another program while it was being edited",
+            self.path.display()
+        )
+    }
+}
+
+impl error::Error for Conflict {}
+
+/// Copy `file` (which doesn't have to exist) to a temporary file next to it (see
+/// [`temp_next_to`]).
+pub(crate) fn copy(file: &Path) -> Result<Copy> {
+    let target = match fs::canonicalize(file) {
+        Ok(target) => target,
+        Err(e) if e.kind() == ErrorKind::NotFound => file.to_owned(),
+        Err(e) => return Err(e),
+    };
+    let mut source = match File::open(&target) {
+        Ok(source) => Some(source),
+        Err(e) if e.kind() == ErrorKind::NotFound => None,
+        Err(e) => return Err(e),
+    };
+    let mut original = Vec::new();
+    if let Some(source) = &mut source {
+        source.read_to_end(&mut original)?;
+    }
+
+    let mut copy = temp_next_to(&target, source.is_none())?;
+    copy.write_all(&original)?;
+    Ok(Copy {
+        target,
+        path: copy.into_temp_path(),
+        original,
+        source,
+    })
+}
+
+/// Create an empty temporary file next to `target` to replace it with, where `new` is whether
+/// `target` doesn't exist yet.
+///
+/// The file is in the same directory so it can be renamed over `target`, and has a similar name
+/// (e.g. `.config-a1B2c3.toml` for `config.toml`) so editors show and highlight it the same way.
+#[cfg_attr(not(unix), allow(unused_variables))]
+pub(crate) fn temp_next_to(target: &Path, new: bool) -> Result<NamedTempFile> {
+    let mut prefix = OsString::from(".");
+    prefix.push(target.file_stem().unwrap_or_default());
+    prefix.push("-");
+    let mut suffix = OsString::new();
+    if let Some(extension) = target.extension() {
+        suffix.push(".");
+        suffix.push(extension);
+    }
+    let dir = match target.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let mut builder = Builder::new();
+    builder.prefix(&prefix).suffix(&suffix);
+    // new files get the same permissions as if the editor had created them (i.e. following the
+    // umask), rather than only being readable by the current user like temporary files
+    #[cfg(unix)]
+    if new {
+        builder.permissions(PermissionsExt::from_mode(0o666));
+    }
+    builder.tempfile_in(dir)
+}
@@ -0,0 +1,74 @@
+//! Copying the permissions, ownership, and extended attributes of files, so copies that replace
+//! (or back up) files keep their security properties.
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use std::{ffi::CString, io, os::unix::io::AsRawFd, ptr};
+use std::{fs::File, io::Result};
+
+/// Give `to` the permissions, owner and group, and extended attributes of `from`, as far as
+/// allowed.
+///
+/// Only the permissions have to be copied. The owner can only be changed by root (and the group to
+/// one the current user is in), and some extended attributes (like SELinux labels) can only be
+/// set with extra privileges, so anything that can't be copied is left as it is.
+pub(crate) fn copy(from: &File, to: &File) -> Result<()> {
+    let metadata = from.metadata()?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{fchown, MetadataExt};
+
+        if fchown(to, Some(metadata.uid()), Some(metadata.gid())).is_err() {
+            let _ = fchown(to, None, Some(metadata.gid()));
+        }
+    }
+
+    // ACLs are stored as extended attributes, so this copies them too
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    copy_xattrs(from, to);
+
+    // this comes last since changing the owner can clear the setuid and setgid bits
+    to.set_permissions(metadata.permissions())
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn copy_xattrs(from: &File, to: &File) {
+    let (from, to) = (from.as_raw_fd(), to.as_raw_fd());
+    let names = match read_with(|buf, len| unsafe { libc::flistxattr(from, buf.cast(), len) }) {
+        Some(names) => names,
+        None => return,
+    };
+    for name in names.split(|&b| b == 0).filter(|name| !name.is_empty()) {
+        let name = match CString::new(name) {
+            Ok(name) => name,
+            Err(_) => continue,
+        };
+        let value =
+            read_with(|buf, len| unsafe { libc::fgetxattr(from, name.as_ptr(), buf.cast(), len) });
+        if let Some(value) = value {
+            unsafe { libc::fsetxattr(to, name.as_ptr(), value.as_ptr().cast(), value.len(), 0) };
+        }
+    }
+}
+
+/// Call `get` (which works like `flistxattr` or `fgetxattr`) with a buffer that's big enough for
+/// what it returns.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn read_with<F: FnMut(*mut u8, usize) -> isize>(mut get: F) -> Option<Vec<u8>> {
+    loop {
+        let len = get(ptr::null_mut(), 0);
+        if len < 0 {
+            return None;
+        }
+        let mut buf = vec![0; len as usize];
+        let len = get(buf.as_mut_ptr(), buf.len());
+        if len >= 0 {
+            buf.truncate(len as usize);
+            return Some(buf);
+        }
+        // the value grew in between the calls
+        if io::Error::last_os_error().raw_os_error() != Some(libc::ERANGE) {
+            return None;
+        }
+    }
+}
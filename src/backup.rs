@@ -0,0 +1,65 @@
+//! Backing up files before they're edited.
+
+use crate::attributes;
+use std::{
+    ffi::OsString,
+    fs::{self, File, OpenOptions},
+    io::{self, ErrorKind, Result},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+/// Copy `file` to a backup next to it, if it exists, and return the backup's path.
+///
+/// The backup is named `file.bak`, or `file.<seconds since the epoch>.bak` if that already exists
+/// (with `-1`, `-2`, etc. after the timestamp if that does too), so existing backups are never
+/// overwritten.
+pub(crate) fn create(file: &Path) -> Result<Option<PathBuf>> {
+    let mut original = match File::open(file) {
+        Ok(original) => original,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    let timestamp = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    for n in 0..100 {
+        let tag = match n {
+            0 => String::new(),
+            1 => format!(".{}", timestamp),
+            n => format!(".{}-{}", timestamp, n - 1),
+        };
+        let path = backup_path(file, &tag);
+        let mut backup = match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(backup) => backup,
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        };
+        let copied =
+            io::copy(&mut original, &mut backup).and_then(|_| attributes::copy(&original, &backup));
+        if let Err(e) = copied {
+            let _ = fs::remove_file(&path);
+            return Err(e);
+        }
+        return Ok(Some(path));
+    }
+    Err(io::Error::new(
+        ErrorKind::AlreadyExists,
+        format!("too many backups of '{}' already exist", file.display()),
+    ))
+}
+
+fn backup_path(file: &Path, tag: &str) -> PathBuf {
+    let mut name = file.file_name().map(OsString::from).unwrap_or_default();
+    name.push(tag);
+    name.push(".bak");
+    file.with_file_name(name)
+}
+
+/// Check whether `file` has the same contents as its `backup`.
+pub(crate) fn unchanged(file: &Path, backup: &Path) -> bool {
+    match (fs::read(file), fs::read(backup)) {
+        (Ok(file), Ok(backup)) => file == backup,
+        _ => false,
+    }
+}
@@ -0,0 +1,150 @@
+//! Aborting an edit session from another thread or task.
+
+use std::{
+    error, fmt,
+    io::{Error, ErrorKind},
+    mem,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    task::Waker,
+};
+#[cfg(any(feature = "tokio", feature = "async-std"))]
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// A handle that can be used to cancel edit sessions from another thread or task.
+///
+/// Pass a handle to [`Editor::cancel_handle`], then call [`cancel`] on a clone of it to abort
+/// the edit session: the editor (and any processes it started) is killed, the temporary file (if
+/// any) is deleted, and the edit session fails with a [`Cancelled`] error.
+///
+/// Cancelling is permanent; once a handle has been cancelled, every edit session using it fails
+/// right away.
+///
+/// ```rust,ignore
+/// use edit::{CancelHandle, Editor};
+///
+/// let handle = CancelHandle::new();
+/// let canceller = handle.clone();
+/// std::thread::spawn(move || {
+///     std::thread::sleep(std::time::Duration::from_secs(60));
+///     canceller.cancel();
+/// });
+/// let edited = Editor::new().cancel_handle(&handle).edit("text")?;
+/// ```
+///
+/// [`Editor::cancel_handle`]: struct.Editor.html#method.cancel_handle
+/// [`cancel`]: #method.cancel
+/// [`Cancelled`]: struct.Cancelled.html
+#[derive(Clone, Debug, Default)]
+pub struct CancelHandle {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    cancelled: AtomicBool,
+    // tasks waiting for the handle to be cancelled
+    wakers: Mutex<Vec<Waker>>,
+}
+
+impl CancelHandle {
+    /// Create a new handle that hasn't been cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel every edit session using this handle.
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::SeqCst);
+        let wakers = mem::take(&mut *self.inner.wakers.lock().unwrap_or_else(|e| e.into_inner()));
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+
+    /// Check whether this handle has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Get a future that completes once this handle has been cancelled.
+    #[cfg(any(feature = "tokio", feature = "async-std"))]
+    pub(crate) fn cancelled(&self) -> Cancellation<'_> {
+        Cancellation { handle: self }
+    }
+}
+
+/// The future returned by [`CancelHandle::cancelled`].
+#[cfg(any(feature = "tokio", feature = "async-std"))]
+pub(crate) struct Cancellation<'a> {
+    handle: &'a CancelHandle,
+}
+
+#[cfg(any(feature = "tokio", feature = "async-std"))]
+impl Future for Cancellation<'_> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+        if self.handle.is_cancelled() {
+            return Poll::Ready(());
+        }
+
+        let mut wakers = self
+            .handle
+            .inner
+            .wakers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        // check again now that cancel() can't take the wakers out from under us
+        if self.handle.is_cancelled() {
+            return Poll::Ready(());
+        }
+        if !wakers.iter().any(|w| w.will_wake(cx.waker())) {
+            wakers.push(cx.waker().clone());
+        }
+        Poll::Pending
+    }
+}
+
+/// The error returned when an edit session is cancelled with a [`CancelHandle`].
+///
+/// This is wrapped in an [`io::Error`] of kind [`ErrorKind::Interrupted`] (the same kind as when
+/// the editor is interrupted by a signal; see [`Editor::forward_signals`]), and can be told apart
+/// from other errors with [`io::Error::get_ref`]:
+///
+/// ```rust,ignore
+/// use edit::Cancelled;
+///
+/// match edit::Editor::new().cancel_handle(&handle).edit("text") {
+///     Err(e) if e.get_ref().map_or(false, |e| e.is::<Cancelled>()) => println!("cancelled"),
+///     result => println!("{:?}", result),
+/// }
+/// ```
+///
+/// [`CancelHandle`]: struct.CancelHandle.html
+/// [`io::Error`]: https://doc.rust-lang.org/std/io/struct.Error.html
+/// [`ErrorKind::Interrupted`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.Interrupted
+/// [`Editor::forward_signals`]: struct.Editor.html#method.forward_signals
+/// [`io::Error::get_ref`]: https://doc.rust-lang.org/std/io/struct.Error.html#method.get_ref
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("edit session was cancelled")
+    }
+}
+
+impl error::Error for Cancelled {}
+
+impl From<Cancelled> for Error {
+    fn from(cancelled: Cancelled) -> Self {
+        Error::new(ErrorKind::Interrupted, cancelled)
+    }
+}
@@ -0,0 +1,267 @@
+//! Cleaning up edited text before it's returned, like `git commit` does with commit messages.
+
+use std::borrow::Cow;
+
+/// How to treat the line endings of text that's edited. See [`Editor::line_endings`].
+///
+/// [`Editor::line_endings`]: struct.Editor.html#method.line_endings
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LineEndings {
+    /// Leave line endings as they are, both in the text that's edited and in the text returned
+    /// (so the result may have whatever line endings the editor saved it with). This is the
+    /// default.
+    #[default]
+    Preserve,
+    /// Return text with `\n` line endings.
+    Lf,
+    /// Return text with `\r\n` line endings.
+    Crlf,
+    /// Return text with the platform's line endings: `\r\n` on Windows and `\n` elsewhere.
+    Native,
+}
+
+impl LineEndings {
+    /// Get the line ending to give edited text, if any.
+    fn ending(self) -> Option<&'static [u8]> {
+        match self {
+            LineEndings::Preserve => None,
+            LineEndings::Lf => Some(b"\n"),
+            LineEndings::Crlf => Some(b"\r\n"),
+            LineEndings::Native => Some(NATIVE),
+        }
+    }
+
+    /// Convert the line endings of the `text` to edit to the platform's, unless they should be
+    /// preserved.
+    pub(crate) fn for_editing(self, text: &[u8]) -> Cow<'_, [u8]> {
+        match self {
+            LineEndings::Preserve => Cow::Borrowed(text),
+            _ => Cow::Owned(convert_line_endings(text, NATIVE)),
+        }
+    }
+
+    /// Convert the line endings of `edited` text according to this policy.
+    pub(crate) fn apply(self, edited: String) -> String {
+        match self.ending() {
+            // only ASCII characters are replaced, so the text is still valid UTF-8
+            Some(ending) => String::from_utf8(convert_line_endings(edited.as_bytes(), ending))
+                .expect("converting line endings made text invalid UTF-8"),
+            None => edited,
+        }
+    }
+}
+
+#[cfg(windows)]
+const NATIVE: &[u8] = b"\r\n";
+#[cfg(not(windows))]
+const NATIVE: &[u8] = b"\n";
+
+/// Replace every `\n` or `\r\n` line ending in `text` with `ending`.
+fn convert_line_endings(text: &[u8], ending: &[u8]) -> Vec<u8> {
+    let mut converted = Vec::with_capacity(text.len());
+    for line in text.split_inclusive(|&b| b == b'\n') {
+        match line.strip_suffix(b"\n") {
+            Some(content) => {
+                converted.extend_from_slice(content.strip_suffix(b"\r").unwrap_or(content));
+                converted.extend_from_slice(ending);
+            }
+            None => converted.extend_from_slice(line),
+        }
+    }
+    converted
+}
+
+/// The default prefix of comment lines.
+pub(crate) const DEFAULT_COMMENT_PREFIX: &str = "#";
+
+/// Remove the lines of `text` that start with `prefix`.
+pub(crate) fn strip_comments(text: &str, prefix: &str) -> String {
+    text.split_inclusive('\n')
+        .filter(|line| !line.starts_with(prefix))
+        .collect()
+}
+
+/// What follows the comment prefix on a scissors line.
+const SCISSORS: &str = "------------------------ >8 ------------------------";
+
+/// Cut `text` off at the first scissors line (`prefix` followed by a space and [`SCISSORS`]), if
+/// it has one.
+pub(crate) fn cut_at_scissors<'a>(text: &'a str, prefix: &str) -> &'a str {
+    let mut start = 0;
+    for line in text.split_inclusive('\n') {
+        let content = line.trim_end_matches(['\n', '\r']);
+        if content
+            .strip_prefix(prefix)
+            .and_then(|rest| rest.strip_prefix(' '))
+            == Some(SCISSORS)
+        {
+            return &text[..start];
+        }
+        start += line.len();
+    }
+    text
+}
+
+/// Split the line ending (`\n` or `\r\n`) off the end of `line`.
+fn split_line_ending(line: &str) -> (&str, &str) {
+    let content = line
+        .strip_suffix("\r\n")
+        .or_else(|| line.strip_suffix('\n'))
+        .unwrap_or(line);
+    line.split_at(content.len())
+}
+
+/// Remove whitespace at the end of every line of `text`, keeping the line endings.
+pub(crate) fn trim_trailing_whitespace(text: &str) -> String {
+    text.split_inclusive('\n')
+        .flat_map(|line| {
+            let (content, ending) = split_line_ending(line);
+            [content.trim_end(), ending]
+        })
+        .collect()
+}
+
+/// Replace the line endings at the end of `text` with a single one, if there are several.
+pub(crate) fn collapse_trailing_newlines(text: &mut String) {
+    let content = text.trim_end_matches(['\n', '\r']).len();
+    // keep the first line ending after the content
+    let ending = if text[content..].starts_with("\r\n") {
+        2
+    } else {
+        1
+    };
+    text.truncate(text.len().min(content + ending));
+}
+
+/// Add a line ending to the end of `text` if it isn't empty and doesn't have one, using `\r\n` if
+/// the rest of the text does.
+pub(crate) fn ensure_final_newline(text: &mut String) {
+    if !text.is_empty() && !text.ends_with('\n') {
+        text.push_str(if text.contains("\r\n") { "\r\n" } else { "\n" });
+    }
+}
+
+/// Render `error` as comment lines starting with `prefix`, to go at the top of text that's opened
+/// again so the user can fix it.
+pub(crate) fn error_comment(error: &str, prefix: &str) -> String {
+    let mut comment = format!(
+        "{} Please fix the error below and save, or save the file unchanged to cancel:\n",
+        prefix
+    );
+    for line in error.lines() {
+        comment.push_str(prefix);
+        if !line.is_empty() {
+            comment.push(' ');
+            comment.push_str(line);
+        }
+        comment.push('\n');
+    }
+    comment.push_str(prefix);
+    comment.push('\n');
+    comment
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strip_comments_removes_only_comment_lines() {
+        let text = "# comment\nkeep # this\n  # indented\n#\nlast";
+        assert_eq!(strip_comments(text, "#"), "keep # this\n  # indented\nlast");
+        assert_eq!(strip_comments("; a\nb\n", ";"), "b\n");
+        assert_eq!(strip_comments("# only\n# comments\n", "#"), "");
+    }
+
+    #[test]
+    fn cut_at_scissors_line() {
+        let scissors = format!("# {}", SCISSORS);
+        let text = format!("message\n{}\ndiff\n", scissors);
+        assert_eq!(cut_at_scissors(&text, "#"), "message\n");
+        let text = format!("message\r\n{}\r\ndiff\r\n", scissors);
+        assert_eq!(cut_at_scissors(&text, "#"), "message\r\n");
+        assert_eq!(cut_at_scissors(&format!("{}\nx", scissors), "#"), "");
+        // the line has to be exactly the scissors, with nothing around them
+        let text = format!("a\n {}\nb\n{} \nc\n", scissors, scissors);
+        assert_eq!(cut_at_scissors(&text, "#"), text);
+    }
+
+    #[test]
+    fn cut_at_scissors_with_custom_prefix() {
+        let text = format!("message\n// {}\ndiff\n", SCISSORS);
+        assert_eq!(cut_at_scissors(&text, "//"), "message\n");
+        // scissors with the default prefix don't count
+        let text = format!("message\n# {}\ndiff\n", SCISSORS);
+        assert_eq!(cut_at_scissors(&text, "//"), text);
+    }
+
+    #[test]
+    fn trim_trailing_whitespace_keeps_line_endings() {
+        assert_eq!(
+            trim_trailing_whitespace("a \t\nb  \r\n  c  "),
+            "a\nb\r\n  c"
+        );
+        assert_eq!(trim_trailing_whitespace(" \n\n"), "\n\n");
+    }
+
+    #[test]
+    fn collapse_trailing_newlines_keeps_one() {
+        let collapse = |text: &str| {
+            let mut text = text.to_owned();
+            collapse_trailing_newlines(&mut text);
+            text
+        };
+        assert_eq!(collapse("a\n\n\n"), "a\n");
+        assert_eq!(collapse("a\r\n\r\n"), "a\r\n");
+        assert_eq!(collapse("a\n\nb"), "a\n\nb");
+        assert_eq!(collapse("a"), "a");
+        // text that's nothing but line endings keeps the first one
+        assert_eq!(collapse("\n\n\n"), "\n");
+        assert_eq!(collapse("\r\n\n"), "\r\n");
+        assert_eq!(collapse(""), "");
+    }
+
+    #[test]
+    fn ensure_final_newline_matches_line_endings() {
+        let ensure = |text: &str| {
+            let mut text = text.to_owned();
+            ensure_final_newline(&mut text);
+            text
+        };
+        assert_eq!(ensure("a"), "a\n");
+        assert_eq!(ensure("a\r\nb"), "a\r\nb\r\n");
+        assert_eq!(ensure("a\n"), "a\n");
+        assert_eq!(ensure(""), "");
+    }
+
+    #[test]
+    fn convert_mixed_line_endings() {
+        let text = b"a\r\nb\nc\r\n\nd";
+        assert_eq!(convert_line_endings(text, b"\n"), b"a\nb\nc\n\nd");
+        assert_eq!(convert_line_endings(text, b"\r\n"), b"a\r\nb\r\nc\r\n\r\nd");
+        // lone carriage returns aren't line endings
+        assert_eq!(convert_line_endings(b"a\rb\r", b"\r\n"), b"a\rb\r");
+    }
+
+    #[test]
+    fn line_endings_policy() {
+        let text = "a\r\nb\n".to_owned();
+        assert_eq!(LineEndings::Preserve.apply(text.clone()), "a\r\nb\n");
+        assert_eq!(LineEndings::Lf.apply(text.clone()), "a\nb\n");
+        assert_eq!(LineEndings::Crlf.apply(text.clone()), "a\r\nb\r\n");
+        assert!(matches!(
+            LineEndings::Preserve.for_editing(text.as_bytes()),
+            Cow::Borrowed(_)
+        ));
+        let native = if cfg!(windows) {
+            "a\r\nb\r\n"
+        } else {
+            "a\nb\n"
+        };
+        assert_eq!(LineEndings::Native.apply(text.clone()), native);
+        assert_eq!(
+            &*LineEndings::Lf.for_editing(text.as_bytes()),
+            native.as_bytes()
+        );
+    }
+}
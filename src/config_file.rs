@@ -0,0 +1,258 @@
+#[cfg(feature = "config-file")]
+use serde::Deserialize;
+#[cfg(feature = "config-file")]
+use std::{
+    env, fs,
+    io::{Error, ErrorKind, Result},
+    path::{Path, PathBuf},
+};
+
+/// The contents of a configuration file.
+///
+/// Every field is optional, so an empty (or missing) file is the same as the default settings.
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "config-file", derive(Deserialize))]
+#[cfg_attr(feature = "config-file", serde(default, rename_all = "kebab-case"))]
+pub(crate) struct ConfigFile {
+    /// An editor command to try before any others.
+    pub editor: Option<String>,
+    /// Editor commands to try (in order) before the hardcoded list of editors.
+    pub fallbacks: Vec<String>,
+    /// Whether to fall back to generic "file openers" like `xdg-open`.
+    pub generic_openers: Option<bool>,
+    /// A suffix for the names of temporary files.
+    pub suffix: Option<String>,
+}
+
+#[cfg(feature = "config-file")]
+impl ConfigFile {
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        toml::from_str(&contents).map_err(|e| Error::new(ErrorKind::InvalidData, e))
+    }
+}
+
+/// Get the path of the user configuration file, whether or not it exists.
+///
+/// The file is named `config.toml` and lives in an `edit` directory inside the platform's usual
+/// configuration directory:
+///
+/// - on Linux and other Unix-likes, `$XDG_CONFIG_HOME/edit/config.toml` (or
+///   `~/.config/edit/config.toml` if `XDG_CONFIG_HOME` is unset)
+/// - on MacOS, `~/Library/Application Support/edit/config.toml`
+/// - on Windows, `%APPDATA%\edit\config.toml`
+///
+/// Returns `None` if the configuration directory can't be determined (e.g. `HOME` is unset).
+///
+/// The file can set any of the following keys:
+///
+/// ```toml
+/// # an editor to try before VISUAL and EDITOR
+/// editor = "nvim"
+/// # editors to try (in order) before the ones this crate knows about
+/// fallbacks = ["micro", "nano"]
+/// # whether to fall back to generic file openers like xdg-open (default: true)
+/// generic-openers = false
+/// ```
+#[cfg(feature = "config-file")]
+pub fn user_config_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("edit").join("config.toml"))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+#[cfg(feature = "config-file")]
+fn config_dir() -> Option<PathBuf> {
+    env::var_os("XDG_CONFIG_HOME")
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")))
+}
+
+#[cfg(target_os = "macos")]
+#[cfg(feature = "config-file")]
+fn config_dir() -> Option<PathBuf> {
+    env::var_os("HOME").map(|home| Path::new(&home).join("Library/Application Support"))
+}
+
+#[cfg(target_os = "windows")]
+#[cfg(feature = "config-file")]
+fn config_dir() -> Option<PathBuf> {
+    env::var_os("APPDATA").map(PathBuf::from)
+}
+
+#[cfg(feature = "config-file")]
+pub(crate) fn load_user_config() -> ConfigFile {
+    user_config_path()
+        .and_then(|path| ConfigFile::load(&path).ok())
+        .unwrap_or_default()
+}
+
+/// Set the `editor` in the [user configuration file] to `command`, creating the file if it
+/// doesn't exist.
+///
+/// The rest of the file is left as it is. This is meant for remembering the user's choice of
+/// editor, e.g. one made with [`choose_editor`].
+///
+/// [user configuration file]: fn.user_config_path.html
+/// [`choose_editor`]: fn.choose_editor.html
+#[cfg(feature = "config-file")]
+pub fn save_user_editor<S: AsRef<str>>(command: S) -> Result<()> {
+    let path = user_config_path().ok_or_else(|| {
+        Error::new(
+            ErrorKind::NotFound,
+            "the configuration directory can't be determined",
+        )
+    })?;
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e),
+    };
+    let contents = set_editor(&contents, command.as_ref());
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, contents)
+}
+
+/// Set the top-level `editor` key in the configuration file `contents` to `command`.
+#[cfg(feature = "config-file")]
+fn set_editor(contents: &str, command: &str) -> String {
+    let mut escaped = String::new();
+    for c in command.chars() {
+        match c {
+            '"' | '\\' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04X}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    let line = format!("editor = \"{}\"", escaped);
+
+    // only keys before the first table belong to the top level
+    let mut lines: Vec<&str> = contents.lines().collect();
+    let top_level = lines
+        .iter()
+        .position(|l| l.trim_start().starts_with('['))
+        .unwrap_or(lines.len());
+    let existing = lines[..top_level].iter().position(|l| {
+        l.trim_start()
+            .strip_prefix("editor")
+            .is_some_and(|rest| rest.trim_start().starts_with('='))
+    });
+    match existing {
+        Some(i) => lines[i] = &line,
+        None => lines.insert(0, &line),
+    }
+
+    let mut contents = lines.join("\n");
+    contents.push('\n');
+    contents
+}
+
+/// Find the project configuration file for the current directory, if there is one.
+///
+/// This looks for a file named `.edit.toml` in the current directory and each of its parents,
+/// returning the first one found (i.e. the one closest to the current directory). It can set the
+/// same keys as the [user configuration file], plus a `suffix` for temporary files so editors can
+/// pick the right syntax highlighting:
+///
+/// ```toml
+/// editor = "code -w"
+/// suffix = ".md"
+/// ```
+///
+/// Project configuration files are only used by an [`Editor`] with [`Editor::project_config`]
+/// enabled.
+///
+/// [user configuration file]: fn.user_config_path.html
+/// [`Editor`]: struct.Editor.html
+/// [`Editor::project_config`]: struct.Editor.html#method.project_config
+#[cfg(feature = "config-file")]
+pub fn project_config_path() -> Option<PathBuf> {
+    let dir = env::current_dir().ok()?;
+    dir.ancestors()
+        .map(|dir| dir.join(".edit.toml"))
+        .find(|path| path.is_file())
+}
+
+#[cfg(feature = "config-file")]
+pub(crate) fn load_project_config() -> Option<ConfigFile> {
+    project_config_path().and_then(|path| ConfigFile::load(&path).ok())
+}
+
+#[cfg(all(test, feature = "config-file"))]
+mod tests {
+    use super::*;
+
+    fn parse(contents: &str) -> ConfigFile {
+        toml::from_str(contents).unwrap()
+    }
+
+    #[test]
+    fn parse_config() {
+        let config = parse(
+            "editor = \"nvim\"\nfallbacks = [\"micro\", \"nano\"]\ngeneric-openers = false\n\
+             suffix = \".md\"\n",
+        );
+        assert_eq!(config.editor.as_deref(), Some("nvim"));
+        assert_eq!(config.fallbacks, ["micro", "nano"]);
+        assert_eq!(config.generic_openers, Some(false));
+        assert_eq!(config.suffix.as_deref(), Some(".md"));
+    }
+
+    #[test]
+    fn parse_empty_config() {
+        let config = parse("");
+        assert_eq!(config.editor, None);
+        assert!(config.fallbacks.is_empty());
+        assert_eq!(config.generic_openers, None);
+        assert_eq!(config.suffix, None);
+        assert!(toml::from_str::<ConfigFile>("editor = 3\n").is_err());
+    }
+
+    #[test]
+    fn set_existing_editor() {
+        assert_eq!(
+            set_editor("# mine\n  editor= \"vi\"\nfallbacks = [\"nano\"]\n", "nvim"),
+            "# mine\neditor = \"nvim\"\nfallbacks = [\"nano\"]\n"
+        );
+        // a key that only starts with "editor" is another key
+        assert_eq!(
+            set_editor("editors = 1\n", "nvim"),
+            "editor = \"nvim\"\neditors = 1\n"
+        );
+    }
+
+    #[test]
+    fn set_editor_before_tables() {
+        let contents = set_editor("fallbacks = []\n\n[other]\neditor = \"vi\"\n", "nvim");
+        assert_eq!(
+            contents,
+            "editor = \"nvim\"\nfallbacks = []\n\n[other]\neditor = \"vi\"\n"
+        );
+        assert_eq!(parse(&contents).editor.as_deref(), Some("nvim"));
+    }
+
+    #[test]
+    fn set_editor_in_empty_file() {
+        assert_eq!(set_editor("", "code -w"), "editor = \"code -w\"\n");
+    }
+
+    #[test]
+    fn set_escaped_editor() {
+        for command in [
+            r#"emacsclient -a "" -c"#,
+            r"C:\Program Files\Editor\editor.exe",
+            "vim -c 'set tw=72'\t\u{7f}",
+            "nano\n--restricted",
+        ] {
+            let contents = set_editor("", command);
+            assert_eq!(contents.lines().count(), 1, "{:?}", contents);
+            assert_eq!(parse(&contents).editor.as_deref(), Some(command));
+        }
+    }
+}
@@ -0,0 +1,574 @@
+//! Finding the user's default text editor as configured in their desktop environment.
+
+use std::ffi::OsString;
+
+/// Get the commands of the user's default text editors, in order of preference.
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+pub(crate) fn default_editors() -> Vec<OsString> {
+    xdg::default_editors()
+}
+
+#[cfg(target_os = "windows")]
+pub(crate) fn default_editors() -> Vec<OsString> {
+    registry::default_editors()
+}
+
+#[cfg(target_os = "macos")]
+pub(crate) fn default_editors() -> Vec<OsString> {
+    launch_services::default_editors()
+}
+
+/// Get the commands of the current desktop environment's own file openers (e.g. `gio open` on
+/// GNOME), which are more likely to work than `xdg-open`.
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+pub(crate) fn openers() -> Vec<&'static str> {
+    xdg::openers()
+}
+
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+pub(crate) fn openers() -> Vec<&'static str> {
+    Vec::new()
+}
+
+/// The [XDG MIME Applications] `mimeapps.list` files and the [desktop entries] they refer to.
+///
+/// [XDG MIME Applications]: https://specifications.freedesktop.org/mime-apps-spec/latest/
+/// [desktop entries]: https://specifications.freedesktop.org/desktop-entry-spec/latest/
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+mod xdg {
+    use crate::split_words;
+    use std::{
+        env,
+        ffi::{OsStr, OsString},
+        fs,
+        os::unix::ffi::{OsStrExt, OsStringExt},
+        path::{Path, PathBuf},
+    };
+
+    const MIME_TYPE: &str = "text/plain";
+
+    // the file openers of desktop environments, by their (lowercased) names in XDG_CURRENT_DESKTOP
+    #[rustfmt::skip]
+    static DESKTOP_OPENERS: &[(&str, &[&str])] = &[
+        ("gnome", &["gio open"]), ("unity", &["gio open"]), ("budgie", &["gio open"]),
+        ("x-cinnamon", &["gio open"]), ("pantheon", &["gio open"]), ("mate", &["gio open"]),
+        // kde-open5 is Plasma 5's name for it
+        ("kde", &["kde-open", "kde-open5"]),
+        ("xfce", &["exo-open"]),
+    ];
+
+    pub fn openers() -> Vec<&'static str> {
+        let desktops = current_desktops();
+        let mut openers = Vec::new();
+        let commands = DESKTOP_OPENERS
+            .iter()
+            .filter(|(name, _)| desktops.iter().any(|desktop| desktop == name))
+            .flat_map(|(_, commands)| commands.iter());
+        for &command in commands {
+            if !openers.contains(&command) {
+                openers.push(command);
+            }
+        }
+        openers
+    }
+
+    /// Get the names of the current desktop environment, lowercased.
+    fn current_desktops() -> Vec<String> {
+        env::var("XDG_CURRENT_DESKTOP")
+            .unwrap_or_default()
+            .split(':')
+            .filter(|desktop| !desktop.is_empty())
+            .map(str::to_lowercase)
+            .collect()
+    }
+
+    pub fn default_editors() -> Vec<OsString> {
+        let dirs = data_dirs();
+        let mut editors = Vec::new();
+        for list in mimeapps_lists() {
+            let ids = match fs::read_to_string(&list) {
+                Ok(contents) => default_applications(&contents),
+                Err(_) => continue,
+            };
+            for id in ids {
+                if let Some(command) = find_desktop_entry(&dirs, &id).and_then(|e| exec(&e)) {
+                    if !editors.contains(&command) {
+                        editors.push(command);
+                    }
+                }
+            }
+        }
+        editors
+    }
+
+    fn home_dir(var: &str, default: &str) -> Option<PathBuf> {
+        env::var_os(var)
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(default)))
+    }
+
+    fn dir_list(var: &str, default: &str) -> Vec<PathBuf> {
+        let dirs = env::var_os(var)
+            .filter(|v| !v.is_empty())
+            .unwrap_or_else(|| default.into());
+        env::split_paths(&dirs).collect()
+    }
+
+    /// `$XDG_DATA_HOME` followed by `$XDG_DATA_DIRS`.
+    fn data_dirs() -> Vec<PathBuf> {
+        let home = home_dir("XDG_DATA_HOME", ".local/share");
+        let dirs = dir_list("XDG_DATA_DIRS", "/usr/local/share:/usr/share");
+        home.into_iter().chain(dirs).collect()
+    }
+
+    /// Every `mimeapps.list` file that could exist, in order of precedence.
+    fn mimeapps_lists() -> Vec<PathBuf> {
+        let home = home_dir("XDG_CONFIG_HOME", ".config");
+        let config_dirs = dir_list("XDG_CONFIG_DIRS", "/etc/xdg");
+        let config_dirs = home.into_iter().chain(config_dirs);
+        let app_dirs = data_dirs().into_iter().map(|dir| dir.join("applications"));
+
+        // desktop-specific lists (e.g. gnome-mimeapps.list) take precedence in each directory
+        let mut names: Vec<_> = current_desktops()
+            .into_iter()
+            .map(|desktop| format!("{}-mimeapps.list", desktop))
+            .collect();
+        names.push("mimeapps.list".into());
+
+        config_dirs
+            .chain(app_dirs)
+            .flat_map(|dir| names.iter().map(move |name| dir.join(name)))
+            .collect()
+    }
+
+    /// Get the IDs of the default applications for text files listed in a `mimeapps.list` file.
+    fn default_applications(contents: &str) -> Vec<String> {
+        entries(contents, "Default Applications")
+            .filter(|(key, _)| *key == MIME_TYPE)
+            .flat_map(|(_, ids)| ids.split(';'))
+            .map(str::trim)
+            .filter(|id| !id.is_empty())
+            .map(String::from)
+            .collect()
+    }
+
+    /// Iterate over the `key=value` pairs in a group of a desktop entry-style file.
+    fn entries<'a>(contents: &'a str, group: &'a str) -> impl Iterator<Item = (&'a str, &'a str)> {
+        let mut in_group = false;
+        contents.lines().filter_map(move |line| {
+            let line = line.trim();
+            if line.starts_with('[') && line.ends_with(']') {
+                in_group = line[1..line.len() - 1] == *group;
+                None
+            } else if !in_group || line.starts_with('#') {
+                None
+            } else {
+                let (key, value) = line.split_once('=')?;
+                Some((key.trim(), value.trim()))
+            }
+        })
+    }
+
+    /// Find the desktop entry file with the given ID.
+    ///
+    /// Desktop entries in subdirectories have IDs with dashes in place of slashes (e.g.
+    /// `kde4/kate.desktop` has the ID `kde4-kate.desktop`).
+    fn find_desktop_entry(dirs: &[PathBuf], id: &str) -> Option<PathBuf> {
+        if id.contains('/') {
+            return None;
+        }
+        let mut paths = vec![PathBuf::from(id)];
+        paths.extend(
+            id.match_indices('-')
+                .map(|(i, _)| Path::new(&id[..i]).join(&id[i + 1..])),
+        );
+        dirs.iter()
+            .map(|dir| dir.join("applications"))
+            .flat_map(|dir| paths.iter().map(move |path| dir.join(path)))
+            .find(|path| path.is_file())
+    }
+
+    /// Get the command a desktop entry runs, without any field codes (like `%f`), so the path of
+    /// the file to edit can be appended to it. Returns `None` if the entry is hidden or the
+    /// program it runs doesn't exist.
+    fn exec(path: &Path) -> Option<OsString> {
+        let contents = fs::read(path).ok()?;
+        let contents = String::from_utf8_lossy(&contents);
+        let mut exec = None;
+        for (key, value) in entries(&contents, "Desktop Entry") {
+            match key {
+                "Exec" => exec = Some(unescape(value)),
+                "Hidden" if value == "true" => return None,
+                "TryExec" if crate::get_full_editor_path(unescape(value)).is_err() => return None,
+                _ => {}
+            }
+        }
+
+        let words = split_words(OsStr::new(&exec?)).ok()?;
+        let words: Vec<_> = words
+            .iter()
+            .filter_map(|word| strip_field_codes(word.as_bytes()))
+            .collect();
+        if words.is_empty() {
+            return None;
+        }
+        Some(OsString::from_vec(words.join(&b' ')))
+    }
+
+    /// Process the escape sequences allowed in desktop entry string values.
+    fn unescape(value: &str) -> String {
+        let mut unescaped = String::with_capacity(value.len());
+        let mut chars = value.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some('s') => unescaped.push(' '),
+                    Some('n') => unescaped.push('\n'),
+                    Some('t') => unescaped.push('\t'),
+                    Some('r') => unescaped.push('\r'),
+                    Some('\\') => unescaped.push('\\'),
+                    Some(c) => {
+                        // leave other escapes (like \" in Exec) for split_words
+                        unescaped.push('\\');
+                        unescaped.push(c);
+                    }
+                    None => unescaped.push('\\'),
+                },
+                c => unescaped.push(c),
+            }
+        }
+        unescaped
+    }
+
+    /// Remove field codes from an argument of an `Exec` command and quote the rest. Returns
+    /// `None` if the argument was only a field code.
+    fn strip_field_codes(word: &[u8]) -> Option<Vec<u8>> {
+        let mut stripped = Vec::with_capacity(word.len());
+        let mut bytes = word.iter().copied();
+        while let Some(b) = bytes.next() {
+            match (b, bytes.clone().next()) {
+                (b'%', Some(b'%')) => {
+                    stripped.push(b'%');
+                    bytes.next();
+                }
+                (b'%', Some(_)) => {
+                    // e.g. %f, %U, or %i, which all expand to nothing without a file or URL
+                    bytes.next();
+                }
+                (b, _) => stripped.push(b),
+            }
+        }
+        if stripped.is_empty() && !word.is_empty() {
+            return None;
+        }
+
+        // quote the argument if needed so it's split the same way again later
+        let plain = |b: &u8| b.is_ascii_alphanumeric() || b"/-_.,:=+@%".contains(b);
+        if !stripped.is_empty() && stripped.iter().all(plain) {
+            return Some(stripped);
+        }
+        let mut quoted = vec![b'\''];
+        for b in stripped {
+            match b {
+                b'\'' => quoted.extend_from_slice(b"'\\''"),
+                b => quoted.push(b),
+            }
+        }
+        quoted.push(b'\'');
+        Some(quoted)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn entries_of_group() {
+            let contents = "\
+                [Desktop Entry]\n\
+                Name=Editor\n\
+                # Exec=commented\n\
+                Exec = editor %F \n\
+                [Desktop Action new]\n\
+                Exec=editor --new\n\
+                [Desktop Entry]\n\
+                NoValue\n\
+                Terminal=false\n";
+            assert_eq!(
+                entries(contents, "Desktop Entry").collect::<Vec<_>>(),
+                [
+                    ("Name", "Editor"),
+                    ("Exec", "editor %F"),
+                    ("Terminal", "false")
+                ]
+            );
+            assert_eq!(
+                entries(contents, "Desktop Action new").collect::<Vec<_>>(),
+                [("Exec", "editor --new")]
+            );
+            assert_eq!(entries(contents, "Missing").count(), 0);
+        }
+
+        #[test]
+        fn default_applications_for_text() {
+            let contents = "\
+                [Added Associations]\n\
+                text/plain=added.desktop;\n\
+                [Default Applications]\n\
+                text/html=browser.desktop\n\
+                text/plain= first.desktop;;second.desktop ; \n\
+                text/plain=third.desktop\n";
+            assert_eq!(
+                default_applications(contents),
+                ["first.desktop", "second.desktop", "third.desktop"]
+            );
+            assert!(default_applications("text/plain=top-level.desktop\n").is_empty());
+        }
+
+        #[test]
+        fn find_desktop_entries() {
+            let home = tempfile::tempdir().unwrap();
+            let system = tempfile::tempdir().unwrap();
+            let entry = |dir: &Path, path: &str| {
+                let path = dir.join("applications").join(path);
+                fs::create_dir_all(path.parent().unwrap()).unwrap();
+                fs::write(&path, "[Desktop Entry]\n").unwrap();
+                path
+            };
+            let kate = entry(system.path(), "kde4/kate.desktop");
+            let system_gedit = entry(system.path(), "gedit.desktop");
+            let home_gedit = entry(home.path(), "gedit.desktop");
+            // only one level of subdirectories is looked in
+            entry(system.path(), "a/b-c/d.desktop");
+            let dirs = [home.path().to_owned(), system.path().to_owned()];
+
+            assert_eq!(find_desktop_entry(&dirs, "kde4-kate.desktop"), Some(kate));
+            assert_eq!(find_desktop_entry(&dirs, "gedit.desktop"), Some(home_gedit));
+            assert_eq!(
+                find_desktop_entry(&dirs[1..], "gedit.desktop"),
+                Some(system_gedit)
+            );
+            assert_eq!(find_desktop_entry(&dirs, "a-b-c-d.desktop"), None);
+            assert_eq!(find_desktop_entry(&dirs, "a-b-c/d.desktop"), None);
+            assert_eq!(find_desktop_entry(&dirs, "kate.desktop"), None);
+        }
+
+        #[test]
+        fn exec_commands() {
+            let dir = tempfile::tempdir().unwrap();
+            let exec = |contents: &str| {
+                let path = dir.path().join("entry.desktop");
+                fs::write(&path, contents).unwrap();
+                exec(&path).map(|command| command.into_string().unwrap())
+            };
+            assert_eq!(
+                exec("[Desktop Entry]\nExec=gedit --new-window %U\n").as_deref(),
+                Some("gedit --new-window")
+            );
+            assert_eq!(
+                exec("[Desktop Entry]\nExec=\"/opt/My Editor/edit\" --name=%c --icon %i 100%%\n")
+                    .as_deref(),
+                Some("'/opt/My Editor/edit' --name= --icon 100%")
+            );
+            assert_eq!(
+                exec("[Desktop Entry]\nExec=editor\\s--flag %f\nTryExec=sh\n").as_deref(),
+                Some("editor --flag")
+            );
+            assert_eq!(
+                exec("[Desktop Entry]\nExec=editor %f\nTryExec=edit-test-missing-editor\n"),
+                None
+            );
+            assert_eq!(exec("[Desktop Entry]\nExec=editor %f\nHidden=true\n"), None);
+            assert_eq!(exec("[Desktop Entry]\nExec=%f\n"), None);
+            assert_eq!(exec("[Desktop Entry]\nName=Editor\n"), None);
+        }
+
+        #[test]
+        fn strip_field_codes_of_words() {
+            assert_eq!(strip_field_codes(b"%f"), None);
+            assert_eq!(strip_field_codes(b"%F%u"), None);
+            assert_eq!(strip_field_codes(b"--file=%f").unwrap(), b"--file=");
+            assert_eq!(strip_field_codes(b"100%%").unwrap(), b"100%");
+            assert_eq!(strip_field_codes(b"it's").unwrap(), b"'it'\\''s'");
+            assert_eq!(strip_field_codes(b"").unwrap(), b"''");
+        }
+    }
+}
+
+/// The default editor for plain text files according to LaunchServices, which is launched with
+/// `open` (waiting for it to quit) since apps can't be run directly.
+#[cfg(target_os = "macos")]
+mod launch_services {
+    use core_foundation::{
+        base::TCFType,
+        bundle::CFBundle,
+        error::CFErrorRef,
+        string::{CFString, CFStringRef},
+        url::{CFURLRef, CFURL},
+    };
+    use std::{ffi::OsString, mem, ptr};
+
+    type LSRolesMask = u32;
+
+    const ROLES_EDITOR: LSRolesMask = 0x4;
+    const ROLES_ALL: LSRolesMask = 0xFFFF_FFFF;
+
+    #[link(name = "CoreServices", kind = "framework")]
+    extern "C" {
+        // deprecated in favor of LSCopyDefaultApplicationURLForContentType, but that's only
+        // available on macOS 12 and later
+        fn LSCopyDefaultRoleHandlerForContentType(
+            content_type: CFStringRef,
+            role: LSRolesMask,
+        ) -> CFStringRef;
+    }
+
+    type CopyDefaultApplicationUrl =
+        unsafe extern "C" fn(CFStringRef, LSRolesMask, *mut CFErrorRef) -> CFURLRef;
+
+    pub fn default_editors() -> Vec<OsString> {
+        let content_type = CFString::from_static_string("public.plain-text");
+        [ROLES_EDITOR, ROLES_ALL]
+            .iter()
+            .find_map(|&roles| default_application(&content_type, roles))
+            .filter(|id| {
+                id.chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-')
+            })
+            .map(|id| format!("open -W -b {}", id).into())
+            .into_iter()
+            .collect()
+    }
+
+    /// Get the bundle identifier of the default application for a content type.
+    fn default_application(content_type: &CFString, roles: LSRolesMask) -> Option<String> {
+        let name = b"LSCopyDefaultApplicationURLForContentType\0";
+        let copy_default_application_url =
+            unsafe { libc::dlsym(libc::RTLD_DEFAULT, name.as_ptr().cast()) };
+        if copy_default_application_url.is_null() {
+            let id = unsafe {
+                LSCopyDefaultRoleHandlerForContentType(content_type.as_concrete_TypeRef(), roles)
+            };
+            if id.is_null() {
+                return None;
+            }
+            return Some(unsafe { CFString::wrap_under_create_rule(id) }.to_string());
+        }
+
+        let copy_default_application_url: CopyDefaultApplicationUrl =
+            unsafe { mem::transmute(copy_default_application_url) };
+        let url = unsafe {
+            copy_default_application_url(content_type.as_concrete_TypeRef(), roles, ptr::null_mut())
+        };
+        if url.is_null() {
+            return None;
+        }
+        let bundle = CFBundle::new(unsafe { CFURL::wrap_under_create_rule(url) })?;
+        let id = bundle
+            .info_dictionary()
+            .find(CFString::from_static_string("CFBundleIdentifier"))?
+            .downcast::<CFString>()?;
+        Some(id.to_string())
+    }
+}
+
+/// The program associated with `.txt` files in the Windows registry, either chosen by the user
+/// (e.g. with "Open with" in Explorer) or set system-wide.
+#[cfg(target_os = "windows")]
+mod registry {
+    use crate::split_words;
+    use std::{
+        env,
+        ffi::{OsStr, OsString},
+    };
+    use winreg::{
+        enums::{HKEY_CLASSES_ROOT, HKEY_CURRENT_USER},
+        RegKey,
+    };
+
+    const USER_CHOICE: &str =
+        r"Software\Microsoft\Windows\CurrentVersion\Explorer\FileExts\.txt\UserChoice";
+
+    pub fn default_editors() -> Vec<OsString> {
+        let classes = RegKey::predef(HKEY_CLASSES_ROOT);
+        let user_choice = RegKey::predef(HKEY_CURRENT_USER)
+            .open_subkey(USER_CHOICE)
+            .and_then(|key| key.get_value::<String, _>("ProgId"));
+        let system_default = classes
+            .open_subkey(".txt")
+            .and_then(|key| key.get_value::<String, _>(""));
+
+        let mut editors = Vec::new();
+        for prog_id in user_choice.into_iter().chain(system_default) {
+            if let Some(command) = open_command(&classes, &prog_id) {
+                if !editors.contains(&command) {
+                    editors.push(command);
+                }
+            }
+        }
+        editors
+    }
+
+    /// Get the command a program ID opens files with, without any placeholders (like `%1`), so
+    /// the path of the file to edit can be appended to it.
+    fn open_command(classes: &RegKey, prog_id: &str) -> Option<OsString> {
+        let key = classes
+            .open_subkey(format!(r"{}\shell\open\command", prog_id))
+            .ok()?;
+        let command = expand_env_vars(&key.get_value::<String, _>("").ok()?);
+
+        let words: Vec<_> = split_words(OsStr::new(&command))
+            .ok()?
+            .into_iter()
+            .map(|word| word.to_string_lossy().into_owned())
+            .filter(|word| !is_placeholder(word))
+            .map(|word| {
+                // quote the argument if needed so it's split the same way again later
+                if word.is_empty() || word.contains(char::is_whitespace) {
+                    format!("\"{}\"", word)
+                } else {
+                    word
+                }
+            })
+            .collect();
+        if words.is_empty() {
+            return None;
+        }
+        Some(words.join(" ").into())
+    }
+
+    /// Expand `%VARIABLE%` references, as in `%SystemRoot%\system32\notepad.exe`.
+    fn expand_env_vars(s: &str) -> String {
+        let mut expanded = String::with_capacity(s.len());
+        let mut rest = s;
+        while let Some(start) = rest.find('%') {
+            expanded.push_str(&rest[..start]);
+            rest = &rest[start..];
+            let value = rest[1..]
+                .find('%')
+                .filter(|&end| end > 0)
+                .and_then(|end| Some((end, env::var(&rest[1..end + 1]).ok()?)));
+            match value {
+                Some((end, value)) => {
+                    expanded.push_str(&value);
+                    rest = &rest[end + 2..];
+                }
+                None => {
+                    expanded.push('%');
+                    rest = &rest[1..];
+                }
+            }
+        }
+        expanded.push_str(rest);
+        expanded
+    }
+
+    /// Check whether an argument contains a placeholder for the file(s) to open (like `%1` or
+    /// `%L`) or other arguments (`%*`).
+    fn is_placeholder(word: &str) -> bool {
+        word.as_bytes().windows(2).any(|pair| {
+            pair[0] == b'%' && (pair[1].is_ascii_digit() || b"*LlVvWwIiDd".contains(&pair[1]))
+        })
+    }
+}
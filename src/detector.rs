@@ -0,0 +1,92 @@
+//! Custom sources of editor commands, registered by applications.
+
+use std::sync::{Arc, RwLock};
+
+/// A source of editor commands, such as an application's own configuration service.
+///
+/// Detectors are registered with [`register_detector`], and consulted every time an editor is
+/// detected (e.g. by [`get_editor`] or [`edit`]). Any function or closure returning a
+/// `Vec<String>` is a detector too.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// struct ConfigService;
+///
+/// impl edit::EditorDetector for ConfigService {
+///     fn detect(&self) -> Vec<String> {
+///         our_config::get("editor").into_iter().collect()
+///     }
+/// }
+///
+/// edit::register_detector(edit::DetectorPriority::BeforeEnvVars, ConfigService);
+/// ```
+///
+/// [`register_detector`]: fn.register_detector.html
+/// [`get_editor`]: fn.get_editor.html
+/// [`edit`]: fn.edit.html
+pub trait EditorDetector: Send + Sync {
+    /// Get the editor commands to try, in order of precedence.
+    ///
+    /// Commands are split into a program and arguments with [`parse_editor_command`]. If there
+    /// are none (e.g. if the configuration service can't be reached), return an empty `Vec`, and
+    /// the other sources of editors will be tried.
+    ///
+    /// [`parse_editor_command`]: fn.parse_editor_command.html
+    fn detect(&self) -> Vec<String>;
+}
+
+impl<F: Fn() -> Vec<String> + Send + Sync> EditorDetector for F {
+    fn detect(&self) -> Vec<String> {
+        self()
+    }
+}
+
+/// Where the commands of an [`EditorDetector`] are tried, relative to the other sources of
+/// editors.
+///
+/// [`EditorDetector`]: trait.EditorDetector.html
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DetectorPriority {
+    /// Try its commands before the `VISUAL` and `EDITOR` environment variables, so it overrides
+    /// the user's choice of editor. Editors set with [`Editor::editor`], [`set_default_editor`],
+    /// or in a configuration file still take precedence.
+    ///
+    /// [`Editor::editor`]: struct.Editor.html#method.editor
+    /// [`set_default_editor`]: fn.set_default_editor.html
+    BeforeEnvVars,
+    /// Try its commands after the environment variables and registered candidates, but before any
+    /// of the editors this crate knows about. This is the default.
+    #[default]
+    BeforeBuiltin,
+    /// Only try its commands when none of the editors this crate knows about can be found.
+    AfterBuiltin,
+}
+
+type Detectors = Vec<(DetectorPriority, Arc<dyn EditorDetector>)>;
+
+static DETECTORS: RwLock<Detectors> = RwLock::new(Vec::new());
+
+/// Register a custom source of editor commands (see [`EditorDetector`]) for every later edit
+/// session in this process.
+///
+/// Detectors with the same `priority` are consulted in the order they were registered.
+///
+/// [`EditorDetector`]: trait.EditorDetector.html
+pub fn register_detector<D: EditorDetector + 'static>(priority: DetectorPriority, detector: D) {
+    let mut detectors = DETECTORS.write().unwrap_or_else(|e| e.into_inner());
+    detectors.push((priority, Arc::new(detector)));
+}
+
+/// Get the commands of the detectors registered with `priority`.
+pub(crate) fn commands(priority: DetectorPriority) -> Vec<String> {
+    // the lock isn't held while detecting, so detectors can register others without deadlocking
+    let detectors: Vec<_> = DETECTORS
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .filter(|(p, _)| *p == priority)
+        .map(|(_, detector)| Arc::clone(detector))
+        .collect();
+    detectors.iter().flat_map(|d| d.detect()).collect()
+}
@@ -0,0 +1,346 @@
+//! Explaining how an editor was (or wasn't) detected.
+
+use crate::{parse_editor_command_os, Editor};
+use std::{
+    env,
+    ffi::{OsStr, OsString},
+    fmt,
+    path::{Path, PathBuf},
+};
+
+/// Where an editor command considered during detection came from.
+///
+/// See [`get_editor`] for the order in which these are checked.
+///
+/// [`get_editor`]: fn.get_editor.html
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CandidateSource {
+    /// An editor set with [`Editor::editor`] or [`set_default_editor`].
+    ///
+    /// [`Editor::editor`]: struct.Editor.html#method.editor
+    /// [`set_default_editor`]: fn.set_default_editor.html
+    Explicit,
+    /// The editor this is running inside of. See [`Editor::editor_servers`].
+    ///
+    /// [`Editor::editor_servers`]: struct.Editor.html#method.editor_servers
+    EditorServer,
+    /// The `editor` in a configuration file.
+    ConfigFile,
+    /// The `EDIT_EDITORS` environment variable.
+    EditorsVar,
+    /// An environment variable such as `VISUAL` or `EDITOR`.
+    EnvVar(String),
+    /// An editor added with [`Editor::candidate`] or [`register_candidate`].
+    ///
+    /// [`Editor::candidate`]: struct.Editor.html#method.candidate
+    /// [`register_candidate`]: fn.register_candidate.html
+    Candidate,
+    /// An editor from a detector registered with [`register_detector`].
+    ///
+    /// [`register_detector`]: fn.register_detector.html
+    Detector,
+    /// One of the `fallbacks` in a configuration file.
+    ConfigFallback,
+    /// The editor remembered from an earlier edit session. See [`Editor::remember_editor`].
+    ///
+    /// [`Editor::remember_editor`]: struct.Editor.html#method.remember_editor
+    Remembered,
+    /// An editor from this crate's built-in list.
+    Builtin,
+    /// The user's default application for text files (e.g. from `mimeapps.list` on Linux or the
+    /// `.txt` file association on Windows).
+    DesktopDefault,
+    /// A generic "file opener" such as `xdg-open`.
+    GenericOpener,
+    /// An editor added with [`Editor::fallback_candidate`] or [`register_fallback_candidate`].
+    ///
+    /// [`Editor::fallback_candidate`]: struct.Editor.html#method.fallback_candidate
+    /// [`register_fallback_candidate`]: fn.register_fallback_candidate.html
+    FallbackCandidate,
+    /// A full path from this crate's built-in list, for when `PATH` is broken.
+    BuiltinPath,
+    /// The editor set with [`Editor::unattended_editor`], when running in CI or a container.
+    ///
+    /// [`Editor::unattended_editor`]: struct.Editor.html#method.unattended_editor
+    UnattendedEditor,
+}
+
+impl CandidateSource {
+    /// Check whether commands from this source are guesses made by this crate, rather than
+    /// editors chosen by the user or the program.
+    pub(crate) fn is_guess(&self) -> bool {
+        matches!(
+            self,
+            CandidateSource::Remembered
+                | CandidateSource::Builtin
+                | CandidateSource::DesktopDefault
+                | CandidateSource::GenericOpener
+                | CandidateSource::BuiltinPath
+        )
+    }
+}
+
+impl fmt::Display for CandidateSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CandidateSource::Explicit => f.write_str("editor set by the program"),
+            CandidateSource::EditorServer => f.write_str("editor server"),
+            CandidateSource::ConfigFile => f.write_str("configuration file"),
+            CandidateSource::EditorsVar => f.write_str("$EDIT_EDITORS"),
+            CandidateSource::EnvVar(var) => write!(f, "${}", var),
+            CandidateSource::Candidate => f.write_str("candidate"),
+            CandidateSource::Detector => f.write_str("registered detector"),
+            CandidateSource::ConfigFallback => f.write_str("configuration file fallback"),
+            CandidateSource::Remembered => f.write_str("remembered"),
+            CandidateSource::Builtin => f.write_str("built-in"),
+            CandidateSource::DesktopDefault => f.write_str("desktop default"),
+            CandidateSource::GenericOpener => f.write_str("generic opener"),
+            CandidateSource::FallbackCandidate => f.write_str("fallback candidate"),
+            CandidateSource::BuiltinPath => f.write_str("built-in path"),
+            CandidateSource::UnattendedEditor => f.write_str("unattended editor"),
+        }
+    }
+}
+
+/// What happened to an editor command considered during detection.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CandidateStatus {
+    /// The editor was found at this path.
+    Found(PathBuf),
+    /// The environment variable isn't set.
+    Unset,
+    /// The environment variable is set to an empty string.
+    Empty,
+    /// The command couldn't be split into a program and arguments (e.g. because of an
+    /// unterminated quote).
+    Invalid,
+    /// The program wasn't found (e.g. it isn't in any directory in `PATH`).
+    NotFound,
+    /// The program exists at this path, but isn't executable.
+    NotExecutable(PathBuf),
+    /// The editor was found at this path, but was skipped for this reason.
+    Skipped(PathBuf, SkipReason),
+}
+
+impl fmt::Display for CandidateStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CandidateStatus::Found(path) => write!(f, "found at {}", path.display()),
+            CandidateStatus::Unset => f.write_str("not set"),
+            CandidateStatus::Empty => f.write_str("empty"),
+            CandidateStatus::Invalid => f.write_str("invalid command (unterminated quote?)"),
+            CandidateStatus::NotFound => f.write_str("not found"),
+            CandidateStatus::NotExecutable(path) => {
+                write!(f, "{} is not executable", path.display())
+            }
+            CandidateStatus::Skipped(path, reason) => {
+                write!(f, "found at {}, but skipped ({})", path.display(), reason)
+            }
+        }
+    }
+}
+
+/// Why an editor that was found was skipped, as part of a [`CandidateStatus`].
+///
+/// [`CandidateStatus`]: enum.CandidateStatus.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SkipReason {
+    /// It's a GUI editor, but there's no display to show it on. See
+    /// [`Editor::detect_headless`].
+    ///
+    /// [`Editor::detect_headless`]: struct.Editor.html#method.detect_headless
+    Headless,
+    /// It's a terminal editor, but stdin or stdout isn't a terminal. See
+    /// [`Editor::skip_terminal_editors`].
+    ///
+    /// [`Editor::skip_terminal_editors`]: struct.Editor.html#method.skip_terminal_editors
+    NoTerminal,
+    /// It was guessed by this crate, but this is running in CI or a container, where nobody is
+    /// around to use it. See [`Editor::detect_unattended`].
+    ///
+    /// [`Editor::detect_unattended`]: struct.Editor.html#method.detect_unattended
+    Unattended,
+    /// It's a GUI editor, but this is running as root under `sudo`. See [`Editor::root_policy`].
+    ///
+    /// [`Editor::root_policy`]: struct.Editor.html#method.root_policy
+    Root,
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SkipReason::Headless => f.write_str("GUI editor, but there is no display"),
+            SkipReason::NoTerminal => f.write_str("terminal editor, but there is no terminal"),
+            SkipReason::Unattended => f.write_str("guessed, but running unattended"),
+            SkipReason::Root => f.write_str("GUI editor, but running as root"),
+        }
+    }
+}
+
+/// An editor command considered during detection, as part of a [`DetectionReport`].
+///
+/// [`DetectionReport`]: struct.DetectionReport.html
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CandidateReport {
+    source: CandidateSource,
+    command: Option<OsString>,
+    status: CandidateStatus,
+}
+
+impl CandidateReport {
+    /// Get where the command came from.
+    pub fn source(&self) -> &CandidateSource {
+        &self.source
+    }
+
+    /// Get the command, or `None` if it came from an environment variable that isn't set.
+    pub fn command(&self) -> Option<&OsStr> {
+        self.command.as_deref()
+    }
+
+    /// Get what happened to the command.
+    pub fn status(&self) -> &CandidateStatus {
+        &self.status
+    }
+}
+
+impl fmt::Display for CandidateReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.command {
+            Some(command) if !command.is_empty() => write!(
+                f,
+                "{}: '{}' {}",
+                self.source,
+                command.to_string_lossy(),
+                self.status
+            ),
+            _ => write!(f, "{}: {}", self.source, self.status),
+        }
+    }
+}
+
+/// Every editor command considered while detecting an editor and what happened to each of them,
+/// as returned by [`diagnose`].
+///
+/// The [`Display`] implementation prints one candidate per line, which is suitable for e.g.
+/// `--verbose` output.
+///
+/// [`diagnose`]: fn.diagnose.html
+/// [`Display`]: https://doc.rust-lang.org/std/fmt/trait.Display.html
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DetectionReport {
+    candidates: Vec<CandidateReport>,
+}
+
+impl DetectionReport {
+    /// Get every candidate that was considered, in order of precedence.
+    pub fn candidates(&self) -> &[CandidateReport] {
+        &self.candidates
+    }
+
+    /// Get the editor that would be used, if one was found.
+    pub fn editor(&self) -> Option<&Path> {
+        self.candidates.iter().find_map(|c| match &c.status {
+            CandidateStatus::Found(path) => Some(path.as_path()),
+            _ => None,
+        })
+    }
+}
+
+impl fmt::Display for DetectionReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for candidate in &self.candidates {
+            writeln!(f, "{}", candidate)?;
+        }
+        match self.editor() {
+            Some(editor) => write!(f, "using {}", editor.display()),
+            None => f.write_str("no editor found"),
+        }
+    }
+}
+
+/// Explain how the [default editor] is detected.
+///
+/// This checks the same sources in the same order as [`get_editor`], but instead of stopping at
+/// the first editor that can be found, it reports every command it considered and why each one
+/// was or wasn't used (e.g. an environment variable isn't set, or a program isn't in `PATH`).
+///
+/// # Example
+///
+/// ```rust,ignore
+/// if verbose {
+///     eprintln!("{}", edit::diagnose());
+/// }
+/// // $VISUAL: not set
+/// // $EDITOR: 'vim' found at /usr/bin/vim
+/// // ...
+/// // using /usr/bin/vim
+/// ```
+///
+/// [default editor]: fn.get_editor.html
+/// [`get_editor`]: fn.get_editor.html
+pub fn diagnose() -> DetectionReport {
+    Editor::new().diagnose()
+}
+
+impl Editor {
+    /// Explain how this `Editor` detects an editor. See [`diagnose`].
+    ///
+    /// [`diagnose`]: fn.diagnose.html
+    pub fn diagnose(&self) -> DetectionReport {
+        let commands = match self.command() {
+            Some(command) => vec![(CandidateSource::Explicit, Some(command))],
+            None => self.sourced_commands(),
+        };
+
+        let candidates = commands
+            .into_iter()
+            .map(|(source, command)| {
+                let status = match &command {
+                    None => CandidateStatus::Unset,
+                    Some(command) if command.is_empty() => CandidateStatus::Empty,
+                    Some(command) => self.candidate_status(command, &source),
+                };
+                CandidateReport {
+                    source,
+                    command,
+                    status,
+                }
+            })
+            .collect();
+        DetectionReport { candidates }
+    }
+
+    fn candidate_status(&self, command: &OsStr, source: &CandidateSource) -> CandidateStatus {
+        if let Ok(candidate) = self.resolve(command) {
+            if source.is_guess() && self.unattended().is_some() {
+                return CandidateStatus::Skipped(candidate.editor, SkipReason::Unattended);
+            }
+            return match self.skip_reason(&candidate.editor) {
+                Some(reason) if *source != CandidateSource::Explicit => {
+                    CandidateStatus::Skipped(candidate.editor, reason)
+                }
+                _ => CandidateStatus::Found(candidate.editor),
+            };
+        }
+        match parse_editor_command_os(command) {
+            Ok((program, _)) => match find_file(&program) {
+                Some(path) => CandidateStatus::NotExecutable(path),
+                None => CandidateStatus::NotFound,
+            },
+            Err(_) => CandidateStatus::Invalid,
+        }
+    }
+}
+
+/// Find `program` the way it would be looked up to run it, but without checking whether it is
+/// executable.
+fn find_file(program: &Path) -> Option<PathBuf> {
+    if program.components().count() > 1 {
+        return Some(program.to_owned()).filter(|path| path.is_file());
+    }
+    let paths = env::var_os("PATH")?;
+    env::split_paths(&paths)
+        .map(|dir| dir.join(program))
+        .find(|path| path.is_file())
+}
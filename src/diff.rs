@@ -0,0 +1,208 @@
+//! Line-level diffs between the text that was edited and the result.
+
+use std::fmt;
+
+/// The result of editing text with [`Editor::edit_diff`]: the edited text, along with which of
+/// its lines were added, removed, or left unchanged compared to the original.
+///
+/// [`Editor::edit_diff`]: struct.Editor.html#method.edit_diff
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EditDiff {
+    pub(crate) content: String,
+    pub(crate) lines: Vec<DiffLine>,
+}
+
+impl EditDiff {
+    /// Get the edited text.
+    pub fn content(&self) -> &str {
+        &self.content
+    }
+
+    /// Get the edited text, consuming the diff.
+    pub fn into_content(self) -> String {
+        self.content
+    }
+
+    /// Get every line of the original and the edited text, in order, marked with whether it was
+    /// kept, added, or removed. Lines that were changed show up as the old line being removed and
+    /// the new one added.
+    pub fn lines(&self) -> &[DiffLine] {
+        &self.lines
+    }
+
+    /// Check whether any lines were added or removed.
+    pub fn changed(&self) -> bool {
+        self.lines
+            .iter()
+            .any(|line| !matches!(line, DiffLine::Unchanged(_)))
+    }
+}
+
+/// Formats the diff like the body of a unified diff, with every line prefixed with ` `, `+`, or
+/// `-`.
+impl fmt::Display for EditDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for line in &self.lines {
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+/// A line of an [`EditDiff`], without its line ending.
+///
+/// [`EditDiff`]: struct.EditDiff.html
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DiffLine {
+    /// A line that's in both the original and the edited text.
+    Unchanged(String),
+    /// A line the user added.
+    Added(String),
+    /// A line the user removed.
+    Removed(String),
+}
+
+impl DiffLine {
+    /// Get the text of the line.
+    pub fn text(&self) -> &str {
+        match self {
+            DiffLine::Unchanged(text) | DiffLine::Added(text) | DiffLine::Removed(text) => text,
+        }
+    }
+}
+
+/// Formats the line with ` `, `+`, or `-` in front of it, as in a unified diff.
+impl fmt::Display for DiffLine {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let prefix = match self {
+            DiffLine::Unchanged(_) => ' ',
+            DiffLine::Added(_) => '+',
+            DiffLine::Removed(_) => '-',
+        };
+        write!(f, "{}{}", prefix, self.text())
+    }
+}
+
+/// Diff the lines of `old` and `new` by finding their longest common subsequence.
+pub(crate) fn diff(old: &str, new: &str) -> Vec<DiffLine> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // most edits only touch a few lines, so only the part in between what's unchanged at the start
+    // and the end needs the (quadratic) search
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let a = &old[prefix..old.len() - suffix];
+    let b = &new[prefix..new.len() - suffix];
+
+    // lcs[i * width + j] is the length of the longest common subsequence of a[i..] and b[j..]
+    let width = b.len() + 1;
+    let mut lcs = vec![0; (a.len() + 1) * width];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i * width + j] = if a[i] == b[j] {
+                lcs[(i + 1) * width + j + 1] + 1
+            } else {
+                lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+            };
+        }
+    }
+
+    let unchanged = |line: &&str| DiffLine::Unchanged(line.to_string());
+    let mut lines: Vec<DiffLine> = old[..prefix].iter().map(unchanged).collect();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            lines.push(DiffLine::Unchanged(a[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if i < a.len()
+            && (j == b.len() || lcs[(i + 1) * width + j] >= lcs[i * width + j + 1])
+        {
+            // removals come first, like in most diffs
+            lines.push(DiffLine::Removed(a[i].to_string()));
+            i += 1;
+        } else {
+            lines.push(DiffLine::Added(b[j].to_string()));
+            j += 1;
+        }
+    }
+    lines.extend(old[old.len() - suffix..].iter().map(unchanged));
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use DiffLine::*;
+
+    fn line(f: fn(String) -> DiffLine, text: &str) -> DiffLine {
+        f(text.to_owned())
+    }
+
+    #[test]
+    fn unchanged_text() {
+        assert_eq!(
+            diff("a\nb\n", "a\r\nb"),
+            [line(Unchanged, "a"), line(Unchanged, "b")]
+        );
+        assert!(diff("", "").is_empty());
+    }
+
+    #[test]
+    fn changed_line_keeps_common_prefix_and_suffix() {
+        assert_eq!(
+            diff("a\nb\nc\nd\n", "a\nB\nc\nd\n"),
+            [
+                line(Unchanged, "a"),
+                line(Removed, "b"),
+                line(Added, "B"),
+                line(Unchanged, "c"),
+                line(Unchanged, "d"),
+            ]
+        );
+    }
+
+    #[test]
+    fn removed_before_added() {
+        assert_eq!(
+            diff("x\ny\n", "1\n2\n"),
+            [
+                line(Removed, "x"),
+                line(Removed, "y"),
+                line(Added, "1"),
+                line(Added, "2"),
+            ]
+        );
+    }
+
+    #[test]
+    fn insertions_and_deletions() {
+        assert_eq!(
+            diff("a\nb\nc\n", "b\nc\nd\n"),
+            [
+                line(Removed, "a"),
+                line(Unchanged, "b"),
+                line(Unchanged, "c"),
+                line(Added, "d"),
+            ]
+        );
+        assert_eq!(diff("", "a\n"), [line(Added, "a")]);
+        assert_eq!(diff("a\n", ""), [line(Removed, "a")]);
+    }
+
+    #[test]
+    fn display_like_unified_diff() {
+        let diff = EditDiff {
+            content: "a\nc\n".to_owned(),
+            lines: diff("a\nb\n", "a\nc\n"),
+        };
+        assert!(diff.changed());
+        assert_eq!(diff.to_string(), " a\n-b\n+c\n");
+    }
+}
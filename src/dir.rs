@@ -0,0 +1,47 @@
+//! Opening whole directories, like projects, in editors that can.
+
+use crate::{registry, Editor};
+use std::{
+    fs,
+    io::{Error, ErrorKind, Result},
+    path::Path,
+};
+
+impl Editor {
+    /// Open a directory in the editor and wait for the editor to exit. See [`edit_dir`].
+    ///
+    /// [`edit_dir`]: fn.edit_dir.html
+    pub fn edit_dir<P: AsRef<Path>>(&self, dir: P) -> Result<()> {
+        let dir = dir.as_ref();
+        if !fs::metadata(dir)?.is_dir() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("'{}' is not a directory", dir.display()),
+            ));
+        }
+        // there's nothing to write the content to
+        if self.scripted_content().is_some() {
+            return Ok(());
+        }
+
+        // editors can't open directories at a line
+        let editor = Editor {
+            line: None,
+            ..self.clone()
+        };
+        let mut session = editor.spawn_first(&[dir], |candidate, dirs| {
+            if !registry::opens_dirs(&candidate.editor) {
+                return Err(Error::new(
+                    ErrorKind::Unsupported,
+                    format!("'{}' can't open directories", candidate.editor.display()),
+                ));
+            }
+            editor.spawn_editor(candidate, dirs)
+        })?;
+        session.close_pipes();
+        match self.timeout {
+            Some(timeout) => session.wait_or_kill(timeout),
+            None => session.wait(),
+        }
+    }
+}
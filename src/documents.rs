@@ -0,0 +1,156 @@
+//! Editing several documents at once in a single file, split by separator lines.
+
+use crate::{EditParseError, Editor, TempOptions};
+use std::io::{Error, ErrorKind, Result};
+
+/// The start of every separator line, which is followed by the number of the document after it.
+const SEPARATOR: &str = "==================== document";
+
+/// Get the separator line (without a line ending) before document `n` (counting from 1) of
+/// `total`.
+fn separator(n: usize, total: usize) -> String {
+    format!("{} {} of {} ====================", SEPARATOR, n, total)
+}
+
+/// Split edited `text` back into `total` documents at the separator lines, or describe how the
+/// separators were damaged.
+fn split(text: &str, total: usize) -> std::result::Result<Vec<&str>, String> {
+    let mut documents = Vec::with_capacity(total);
+    // the start of the document being read, once the first separator has been found
+    let mut start = None;
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let content = line.trim_end_matches(['\n', '\r']);
+        let next = documents.len() + usize::from(start.is_some()) + 1;
+        if next <= total && content == separator(next, total) {
+            if let Some(start) = start {
+                documents.push(&text[start..offset]);
+            }
+            start = Some(offset + line.len());
+        } else if content.starts_with(SEPARATOR) {
+            return Err(format!("the separator '{}' was changed or moved", content));
+        } else if start.is_none() && !content.trim().is_empty() {
+            return Err("there's text before the first separator".to_owned());
+        }
+        offset += line.len();
+    }
+    match start {
+        Some(start) if documents.len() + 1 == total => {
+            documents.push(&text[start..]);
+            Ok(documents)
+        }
+        _ => {
+            let missing = documents.len() + usize::from(start.is_some()) + 1;
+            Err(format!(
+                "the separator before document {} is missing",
+                missing
+            ))
+        }
+    }
+}
+
+impl Editor {
+    /// Open several documents in the editor at once, and return each of them as edited. See
+    /// [`edit_documents`].
+    ///
+    /// [`edit_documents`]: fn.edit_documents.html
+    pub fn edit_documents<S: AsRef<[u8]>>(&self, documents: &[S]) -> Result<Vec<String>> {
+        if documents.is_empty() {
+            return Ok(Vec::new());
+        }
+        // a line that looks like a separator would be mistaken for one when splitting
+        for (i, document) in documents.iter().enumerate() {
+            let mut lines = document.as_ref().split(|&b| b == b'\n');
+            if lines.any(|line| line.starts_with(SEPARATOR.as_bytes())) {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!(
+                        "document {} has a line starting with '{}'",
+                        i + 1,
+                        SEPARATOR
+                    ),
+                ));
+            }
+        }
+        let total = documents.len();
+        let mut text = Vec::new();
+        for (i, document) in documents.iter().enumerate() {
+            text.extend_from_slice(separator(i + 1, total).as_bytes());
+            text.push(b'\n');
+            let document = document.as_ref();
+            text.extend_from_slice(document);
+            if !document.is_empty() && !document.ends_with(b"\n") {
+                text.push(b'\n');
+            }
+        }
+
+        let text = self.template(&text)?;
+        self.edit_temp(&text, &TempOptions::new(), |edited| {
+            // the whole file has to be decoded at once, since only its start has a byte order mark
+            let edited = self.decode(edited)?;
+            let documents = match split(&edited, total) {
+                Ok(documents) => documents,
+                Err(e) => return Err(EditParseError::error(edited, e)),
+            };
+            let documents = documents.into_iter();
+            Ok(documents.map(|d| self.clean_up(d.to_owned())).collect())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_documents() {
+        let text = format!(
+            "\n{}\none\n{}\r\n\n{}\nthree",
+            separator(1, 3),
+            separator(2, 3),
+            separator(3, 3)
+        );
+        assert_eq!(split(&text, 3).unwrap(), ["one\n", "\n", "three"]);
+        assert_eq!(split(&separator(1, 1), 1).unwrap(), [""]);
+    }
+
+    #[test]
+    fn damaged_separator() {
+        let text = format!("{}\none\n{} of 2\ntwo\n", separator(1, 2), SEPARATOR);
+        assert_eq!(
+            split(&text, 2).unwrap_err(),
+            "the separator '==================== document of 2' was changed or moved"
+        );
+    }
+
+    #[test]
+    fn missing_separator() {
+        let text = format!("{}\none\ntwo\n", separator(1, 2));
+        assert_eq!(
+            split(&text, 2).unwrap_err(),
+            "the separator before document 2 is missing"
+        );
+        assert_eq!(
+            split("", 2).unwrap_err(),
+            "the separator before document 1 is missing"
+        );
+    }
+
+    #[test]
+    fn reordered_separators() {
+        let text = format!("{}\ntwo\n{}\none\n", separator(2, 2), separator(1, 2));
+        assert_eq!(
+            split(&text, 2).unwrap_err(),
+            format!("the separator '{}' was changed or moved", separator(2, 2))
+        );
+    }
+
+    #[test]
+    fn text_before_first_separator() {
+        let text = format!("stray\n{}\none\n", separator(1, 1));
+        assert_eq!(
+            split(&text, 1).unwrap_err(),
+            "there's text before the first separator"
+        );
+    }
+}
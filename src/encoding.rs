@@ -0,0 +1,196 @@
+//! Decoding edited text that some (mostly older Windows) editors save in other encodings than
+//! plain UTF-8.
+
+use crate::{secure, Editor};
+#[cfg(feature = "encoding_rs")]
+use std::str;
+use std::{
+    borrow::Cow,
+    io::{Error, ErrorKind, Result},
+};
+
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+const UTF16LE_BOM: &[u8] = b"\xFF\xFE";
+const UTF16BE_BOM: &[u8] = b"\xFE\xFF";
+
+/// Decode `edited` as UTF-8, or as UTF-16 if it starts with a UTF-16 byte order mark, without any
+/// byte order mark.
+pub(crate) fn decode(mut edited: Vec<u8>) -> Result<String> {
+    if edited.starts_with(UTF8_BOM) {
+        edited.drain(..UTF8_BOM.len());
+    } else if edited.starts_with(UTF16LE_BOM) {
+        return decode_utf16(edited, u16::from_le_bytes);
+    } else if edited.starts_with(UTF16BE_BOM) {
+        return decode_utf16(edited, u16::from_be_bytes);
+    }
+    String::from_utf8(edited).map_err(|e| invalid(e.into_bytes()))
+}
+
+/// Decode `edited` as UTF-16 (after the byte order mark), with `from_bytes` turning pairs of
+/// bytes into code units.
+fn decode_utf16(mut edited: Vec<u8>, from_bytes: fn([u8; 2]) -> u16) -> Result<String> {
+    let units = edited[2..]
+        .chunks(2)
+        .map(|pair| match *pair {
+            [a, b] => Some(from_bytes([a, b])),
+            _ => None,
+        })
+        .collect::<Option<Vec<u16>>>();
+    let text = units.and_then(|units| String::from_utf16(&units).ok());
+    match text {
+        Some(text) => {
+            secure::wipe(&mut edited);
+            Ok(text)
+        }
+        None => Err(invalid(edited)),
+    }
+}
+
+fn invalid(mut edited: Vec<u8>) -> Error {
+    // the text might be sensitive, and is of no use to anyone now
+    secure::wipe(&mut edited);
+    Error::from(ErrorKind::InvalidData)
+}
+
+impl Editor {
+    /// Edit text in `encoding` rather than UTF-8. See [`edit_with_encoding`].
+    ///
+    /// This only affects functions that take and return text, like [`Editor::edit`], not ones
+    /// that work with raw bytes or edit existing files.
+    ///
+    /// [`edit_with_encoding`]: fn.edit_with_encoding.html
+    /// [`Editor::edit`]: #method.edit
+    #[cfg(feature = "encoding_rs")]
+    pub fn encoding(&mut self, encoding: &'static encoding_rs::Encoding) -> &mut Self {
+        self.encoding = Some(encoding);
+        self
+    }
+
+    /// Get the bytes to write to the file for `text` to edit: with the line endings it should be
+    /// edited with, and encoded as set with [`Editor::encoding`].
+    pub(crate) fn template<'a>(&self, text: &'a [u8]) -> Result<Cow<'a, [u8]>> {
+        let text = self.line_endings.for_editing(text);
+        #[cfg(feature = "encoding_rs")]
+        if let Some(encoding) = self.encoding {
+            return encode(&text, encoding).map(Cow::Owned);
+        }
+        Ok(text)
+    }
+
+    /// Decode `edited` text as set with [`Editor::encoding`], or with [`decode`] otherwise.
+    pub(crate) fn decode(&self, edited: Vec<u8>) -> Result<String> {
+        #[cfg(feature = "encoding_rs")]
+        if let Some(encoding) = self.encoding {
+            return decode_with(edited, encoding);
+        }
+        decode(edited)
+    }
+}
+
+/// Encode UTF-8 `text` with `encoding`, failing if it isn't valid UTF-8 or has characters the
+/// encoding can't represent.
+#[cfg(feature = "encoding_rs")]
+fn encode(text: &[u8], encoding: &'static encoding_rs::Encoding) -> Result<Vec<u8>> {
+    let text = str::from_utf8(text).map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
+    let (encoded, used, had_errors) = encoding.encode(text);
+    if had_errors {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("the text can't be encoded as {}", used.name()),
+        ));
+    }
+    Ok(encoded.into_owned())
+}
+
+/// Decode `edited` with `encoding` (or the encoding of its byte order mark, if it has one).
+#[cfg(feature = "encoding_rs")]
+fn decode_with(mut edited: Vec<u8>, encoding: &'static encoding_rs::Encoding) -> Result<String> {
+    let (text, used, had_errors) = encoding.decode(&edited);
+    let text = if had_errors {
+        Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("the edited text isn't valid {}", used.name()),
+        ))
+    } else {
+        Ok(text.into_owned())
+    };
+    // the text might be sensitive, and is of no use to anyone now
+    secure::wipe(&mut edited);
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utf16(text: &str, bom: &[u8], to_bytes: fn(u16) -> [u8; 2]) -> Vec<u8> {
+        let mut bytes = bom.to_vec();
+        bytes.extend(text.encode_utf16().flat_map(to_bytes));
+        bytes
+    }
+
+    #[test]
+    fn decode_utf8() {
+        assert_eq!(decode(b"caf\xC3\xA9\n".to_vec()).unwrap(), "café\n");
+        assert_eq!(decode(Vec::new()).unwrap(), "");
+    }
+
+    #[test]
+    fn decode_utf8_with_bom() {
+        assert_eq!(decode(b"\xEF\xBB\xBFhi".to_vec()).unwrap(), "hi");
+        // only one byte order mark is removed
+        assert_eq!(
+            decode(b"\xEF\xBB\xBF\xEF\xBB\xBFhi".to_vec()).unwrap(),
+            "\u{feff}hi"
+        );
+    }
+
+    #[test]
+    fn decode_utf16() {
+        let text = "héllo 🦀\r\n";
+        assert_eq!(
+            decode(utf16(text, UTF16LE_BOM, u16::to_le_bytes)).unwrap(),
+            text
+        );
+        assert_eq!(
+            decode(utf16(text, UTF16BE_BOM, u16::to_be_bytes)).unwrap(),
+            text
+        );
+        assert_eq!(decode(UTF16LE_BOM.to_vec()).unwrap(), "");
+    }
+
+    #[test]
+    fn decode_invalid() {
+        let kind = |edited: Vec<u8>| decode(edited).unwrap_err().kind();
+        assert_eq!(kind(b"\xC3(".to_vec()), ErrorKind::InvalidData);
+        // an odd number of bytes can't be UTF-16
+        let mut odd = utf16("hi", UTF16LE_BOM, u16::to_le_bytes);
+        odd.push(b'!');
+        assert_eq!(kind(odd), ErrorKind::InvalidData);
+        // nor can an unpaired surrogate
+        let mut unpaired = UTF16BE_BOM.to_vec();
+        unpaired.extend_from_slice(&0xD800u16.to_be_bytes());
+        assert_eq!(kind(unpaired), ErrorKind::InvalidData);
+    }
+
+    #[cfg(feature = "encoding_rs")]
+    #[test]
+    fn legacy_encodings() {
+        use encoding_rs::{SHIFT_JIS, WINDOWS_1252};
+
+        let encoded = encode("メモ\n".as_bytes(), SHIFT_JIS).unwrap();
+        assert_eq!(encoded, b"\x83\x81\x83\x82\n");
+        assert_eq!(decode_with(encoded, SHIFT_JIS).unwrap(), "メモ\n");
+        assert_eq!(
+            decode_with(b"caf\xE9".to_vec(), WINDOWS_1252).unwrap(),
+            "café"
+        );
+
+        let error = encode("メモ".as_bytes(), WINDOWS_1252).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidInput);
+        let error = encode(b"\xFF", SHIFT_JIS).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidInput);
+        let error = decode_with(b"\x83".to_vec(), SHIFT_JIS).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+    }
+}
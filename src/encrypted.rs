@@ -0,0 +1,454 @@
+//! Keeping the temporary file encrypted, with the editor only seeing the decrypted text through an
+//! in-memory file while it runs.
+//!
+//! The text is sealed with ChaCha20-Poly1305 (RFC 8439) under a random key that only ever exists
+//! in this process's memory, and a new random nonce every time the file is written.
+
+use crate::{memfd, recover, secure, Editor, Run, TempOptions, WaitFor};
+use std::{
+    convert::{TryFrom, TryInto},
+    fmt, fs,
+    io::{Error, ErrorKind, Result, Write},
+    path::Path,
+    time::{Duration, SystemTime},
+};
+
+const NONCE_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+
+impl Editor {
+    /// Set whether to keep the text encrypted whenever it's in a temporary file, and only let the
+    /// editor see it decrypted in memory. The default is `false`.
+    ///
+    /// This wraps the editor like [sops](https://github.com/getsops/sops) does: the temporary file
+    /// only ever holds the text encrypted with a random key that never leaves this process, and
+    /// while the editor runs, it edits a decrypted copy in an in-memory file (like with
+    /// [`Editor::in_memory`], which has the same limits on which editors can save to it). Once the
+    /// editor exits, the edited text is encrypted back into the temporary file before the
+    /// in-memory copy is freed.
+    ///
+    /// The encrypted file is what [`Editor::keep_on_error`] keeps if editing fails, even in
+    /// [secure mode](#method.secure), and [`KeptFile::contents`] decrypts it for as long as the
+    /// error is around, e.g. to offer the draft for editing again. Once the error is dropped, the
+    /// key is gone and so is any way to read the draft.
+    ///
+    /// In-memory files only exist on Linux, so elsewhere (or if they can't be created, or the
+    /// editor can't open them, like when files are opened through the desktop portal or given to
+    /// the user who ran `sudo`), editing fails with [`ErrorKind::Unsupported`] rather than
+    /// falling back to plain text. The same goes for other functions that would need a temporary
+    /// file, like the async ones or [`Editor::edit_file_privileged`] when it edits a copy. Files
+    /// the editor makes on its own, like swap or backup files, aren't covered either, so
+    /// configure the editor not to write them when editing secrets.
+    ///
+    /// This needs the `encrypted-temp` feature.
+    ///
+    /// ```rust,ignore
+    /// let token = edit::Editor::new().encrypt_temp(true).edit("")?;
+    /// ```
+    ///
+    /// [`Editor::in_memory`]: #method.in_memory
+    /// [`Editor::keep_on_error`]: #method.keep_on_error
+    /// [`KeptFile::contents`]: struct.KeptFile.html#method.contents
+    /// [`ErrorKind::Unsupported`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.Unsupported
+    /// [`Editor::edit_file_privileged`]: #method.edit_file_privileged
+    pub fn encrypt_temp(&mut self, enabled: bool) -> &mut Self {
+        self.encrypt_temp = enabled;
+        self
+    }
+
+    /// Edit `buf` in an encrypted temporary file created with `options`, and turn the edited
+    /// contents into the result with `finish`.
+    pub(crate) fn edit_encrypted<T, F: FnOnce(Vec<u8>, Run) -> Result<T>>(
+        &self,
+        buf: &[u8],
+        options: &TempOptions,
+        finish: F,
+    ) -> Result<T> {
+        if !self.can_edit_in_memory() {
+            return Err(unsupported());
+        }
+        let key = Key::random()?;
+        let mut file = self.create_temp_file(options, Some(buf))?;
+        file.write_all(&key.seal(buf)?)?;
+        let path = file.into_temp_path();
+
+        let mut changed = false;
+        let edited = self.edit_decrypted(&path, &key).and_then(|(edited, run)| {
+            changed = edited != buf;
+            finish(edited, run?)
+        });
+        match edited {
+            Ok(edited) => {
+                self.remove_temp(path)?;
+                Ok(edited)
+            }
+            Err(e) if self.keep_on_error => Err(recover::keep_encrypted(path, changed, key, e)),
+            Err(e) => {
+                let _ = self.remove_temp(path);
+                Err(e)
+            }
+        }
+    }
+
+    /// Decrypt the file at `path` with `key` into an in-memory file, open that in the editor, and
+    /// encrypt the edited text back into the file, returning it along with how waiting for the
+    /// editor went.
+    fn edit_decrypted(&self, path: &Path, key: &Key) -> Result<(Vec<u8>, Result<Run>)> {
+        let mut text = key.open(&fs::read(path)?)?;
+        let view = memfd::create(&text);
+        secure::wipe(&mut text);
+        let view = view?.ok_or_else(unsupported)?;
+        if self.wait_for == WaitFor::Save {
+            // make sure a save right away is noticed even with coarse timestamps
+            let _ = view
+                .file()
+                .set_modified(SystemTime::now() - Duration::from_secs(60));
+        }
+
+        let run = self.open_all_and_wait(&[view.path()]);
+        let edited = fs::read(view.path())?;
+        // the edited text is encrypted back into the file whether or not editing succeeded, so
+        // it can be kept
+        fs::write(path, key.seal(&edited)?)?;
+        Ok((edited, run))
+    }
+}
+
+fn unsupported() -> Error {
+    Error::new(
+        ErrorKind::Unsupported,
+        "encrypted temporary files need an in-memory file for the editor, which isn't available",
+    )
+}
+
+/// A key for encrypting one temporary file, which is wiped from memory when dropped.
+pub(crate) struct Key([u8; 32]);
+
+impl Key {
+    fn random() -> Result<Self> {
+        let mut key = Self([0; 32]);
+        fill_random(&mut key.0)?;
+        Ok(key)
+    }
+
+    /// Encrypt `text` under a new random nonce, returning the nonce, the encrypted text, and the
+    /// tag that authenticates it.
+    fn seal(&self, text: &[u8]) -> Result<Vec<u8>> {
+        let mut nonce = [0; NONCE_LEN];
+        fill_random(&mut nonce)?;
+        Ok(self.seal_with(&nonce, text))
+    }
+
+    fn seal_with(&self, nonce: &[u8; NONCE_LEN], text: &[u8]) -> Vec<u8> {
+        let mut sealed = Vec::with_capacity(NONCE_LEN + text.len() + TAG_LEN);
+        sealed.extend_from_slice(nonce);
+        sealed.extend_from_slice(text);
+        chacha20(&self.0, nonce, 1, &mut sealed[NONCE_LEN..]);
+        let tag = self.tag(nonce, &sealed[NONCE_LEN..]);
+        sealed.extend_from_slice(&tag);
+        sealed
+    }
+
+    /// Decrypt what [`Key::seal`] returned, or return [`ErrorKind::InvalidData`] if it was
+    /// changed since.
+    pub(crate) fn open(&self, sealed: &[u8]) -> Result<Vec<u8>> {
+        if sealed.len() < NONCE_LEN + TAG_LEN {
+            return Err(tampered());
+        }
+        let (nonce, rest) = sealed.split_at(NONCE_LEN);
+        let (encrypted, tag) = rest.split_at(rest.len() - TAG_LEN);
+        let nonce = <&[u8; NONCE_LEN]>::try_from(nonce).unwrap();
+        let expected = self.tag(nonce, encrypted);
+        // compare every byte, so how long the comparison takes doesn't say where they differ
+        if expected
+            .iter()
+            .zip(tag)
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            != 0
+        {
+            return Err(tampered());
+        }
+        let mut text = encrypted.to_vec();
+        chacha20(&self.0, nonce, 1, &mut text);
+        Ok(text)
+    }
+
+    /// Get the Poly1305 tag of `encrypted` text (with no additional data).
+    fn tag(&self, nonce: &[u8; NONCE_LEN], encrypted: &[u8]) -> [u8; TAG_LEN] {
+        let mut one_time_key = [0; 64];
+        chacha20(&self.0, nonce, 0, &mut one_time_key);
+        let mut lengths = [0; 16];
+        lengths[8..].copy_from_slice(&(encrypted.len() as u64).to_le_bytes());
+        let padding = [0; 16];
+        let mut message = Vec::with_capacity(encrypted.len() + 32);
+        message.extend_from_slice(encrypted);
+        message.extend_from_slice(&padding[..(16 - encrypted.len() % 16) % 16]);
+        message.extend_from_slice(&lengths);
+        let tag = poly1305(one_time_key[..32].try_into().unwrap(), &message);
+        secure::wipe(&mut one_time_key);
+        tag
+    }
+}
+
+impl Drop for Key {
+    fn drop(&mut self) {
+        secure::wipe(&mut self.0);
+    }
+}
+
+impl fmt::Debug for Key {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Key(..)")
+    }
+}
+
+fn tampered() -> Error {
+    Error::new(
+        ErrorKind::InvalidData,
+        "the encrypted temporary file was changed by another program",
+    )
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn fill_random(buf: &mut [u8]) -> Result<()> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        let rest = &mut buf[filled..];
+        let n = unsafe { libc::getrandom(rest.as_mut_ptr().cast(), rest.len(), 0) };
+        if n < 0 {
+            let e = Error::last_os_error();
+            if e.kind() != ErrorKind::Interrupted {
+                return Err(e);
+            }
+        } else {
+            filled += n as usize;
+        }
+    }
+    Ok(())
+}
+
+// there are no in-memory files to decrypt into elsewhere, so there's no need for keys either
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn fill_random(_buf: &mut [u8]) -> Result<()> {
+    Err(unsupported())
+}
+
+/// Encrypt or decrypt `buf` in place with the ChaCha20 stream cipher, starting at block `counter`.
+fn chacha20(key: &[u8; 32], nonce: &[u8; NONCE_LEN], mut counter: u32, buf: &mut [u8]) {
+    let mut state = [0; 16];
+    state[..4].copy_from_slice(&[0x6170_7865, 0x3320_646e, 0x7962_2d32, 0x6b20_6574]);
+    for (word, bytes) in state[4..12].iter_mut().zip(key.chunks_exact(4)) {
+        *word = u32::from_le_bytes(bytes.try_into().unwrap());
+    }
+    for (word, bytes) in state[13..].iter_mut().zip(nonce.chunks_exact(4)) {
+        *word = u32::from_le_bytes(bytes.try_into().unwrap());
+    }
+    for chunk in buf.chunks_mut(64) {
+        state[12] = counter;
+        let mut block = state;
+        for _ in 0..10 {
+            for &[a, b, c, d] in &[
+                [0, 4, 8, 12],
+                [1, 5, 9, 13],
+                [2, 6, 10, 14],
+                [3, 7, 11, 15],
+                [0, 5, 10, 15],
+                [1, 6, 11, 12],
+                [2, 7, 8, 13],
+                [3, 4, 9, 14],
+            ] {
+                quarter_round(&mut block, a, b, c, d);
+            }
+        }
+        let keystream = block
+            .iter()
+            .zip(&state)
+            .flat_map(|(word, initial)| word.wrapping_add(*initial).to_le_bytes());
+        for (byte, key) in chunk.iter_mut().zip(keystream) {
+            *byte ^= key;
+        }
+        secure::wipe_words(&mut block);
+        counter = counter.wrapping_add(1);
+    }
+    secure::wipe_words(&mut state);
+}
+
+fn quarter_round(state: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(16);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(12);
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(8);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(7);
+}
+
+/// Get the Poly1305 tag of `message` under the one-time `key`, computed with 26-bit limbs.
+fn poly1305(key: &[u8; 32], message: &[u8]) -> [u8; TAG_LEN] {
+    const MASK: u32 = 0x3ff_ffff;
+    let word = |bytes: &[u8], at: usize| u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap());
+
+    // r, clamped as the algorithm requires
+    let r = [
+        word(key, 0) & 0x3ff_ffff,
+        (word(key, 3) >> 2) & 0x3ff_ff03,
+        (word(key, 6) >> 4) & 0x3ff_c0ff,
+        (word(key, 9) >> 6) & 0x3f0_3fff,
+        (word(key, 12) >> 8) & 0x00f_ffff,
+    ]
+    .map(u64::from);
+    let s = [r[1] * 5, r[2] * 5, r[3] * 5, r[4] * 5];
+
+    let mut h = [0u32; 5];
+    for chunk in message.chunks(16) {
+        let mut block = [0; 17];
+        block[..chunk.len()].copy_from_slice(chunk);
+        // the bit above the end of the chunk
+        block[chunk.len()] = 1;
+        h[0] += word(&block, 0) & MASK;
+        h[1] += (word(&block, 3) >> 2) & MASK;
+        h[2] += (word(&block, 6) >> 4) & MASK;
+        h[3] += (word(&block, 9) >> 6) & MASK;
+        h[4] += (word(&block, 12) >> 8) | (u32::from(block[16]) << 24);
+
+        let h64 = h.map(u64::from);
+        let d = [
+            h64[0] * r[0] + h64[1] * s[3] + h64[2] * s[2] + h64[3] * s[1] + h64[4] * s[0],
+            h64[0] * r[1] + h64[1] * r[0] + h64[2] * s[3] + h64[3] * s[2] + h64[4] * s[1],
+            h64[0] * r[2] + h64[1] * r[1] + h64[2] * r[0] + h64[3] * s[3] + h64[4] * s[2],
+            h64[0] * r[3] + h64[1] * r[2] + h64[2] * r[1] + h64[3] * r[0] + h64[4] * s[3],
+            h64[0] * r[4] + h64[1] * r[3] + h64[2] * r[2] + h64[3] * r[1] + h64[4] * r[0],
+        ];
+        let mut carry = 0;
+        for (limb, d) in h.iter_mut().zip(d) {
+            let d = d + carry;
+            *limb = d as u32 & MASK;
+            carry = d >> 26;
+        }
+        h[0] += carry as u32 * 5;
+        h[1] += h[0] >> 26;
+        h[0] &= MASK;
+    }
+
+    // fully carry h, then reduce it modulo 2^130 - 5
+    for i in 1..4 {
+        h[i + 1] += h[i] >> 26;
+        h[i] &= MASK;
+    }
+    h[0] += (h[4] >> 26) * 5;
+    h[4] &= MASK;
+    h[1] += h[0] >> 26;
+    h[0] &= MASK;
+    let mut g = [0u32; 5];
+    let mut carry = 5;
+    for (g, h) in g.iter_mut().zip(&h) {
+        *g = h + carry;
+        carry = *g >> 26;
+        *g &= MASK;
+    }
+    // use g = h - (2^130 - 5) if that isn't negative
+    let negative = (g[4] | (carry << 26)).wrapping_sub(1 << 26) >> 31;
+    let keep_h = negative.wrapping_neg();
+    for (h, g) in h.iter_mut().zip(g) {
+        *h = (*h & keep_h) | (g & !keep_h);
+    }
+
+    // add s to h modulo 2^128
+    let h = [
+        h[0] | (h[1] << 26),
+        (h[1] >> 6) | (h[2] << 20),
+        (h[2] >> 12) | (h[3] << 14),
+        (h[3] >> 18) | (h[4] << 8),
+    ];
+    let mut tag = [0; TAG_LEN];
+    let mut carry = 0;
+    for (i, h) in h.iter().enumerate() {
+        let sum = u64::from(*h) + u64::from(word(key, 16 + 4 * i)) + carry;
+        tag[4 * i..4 * i + 4].copy_from_slice(&(sum as u32).to_le_bytes());
+        carry = sum >> 32;
+    }
+    tag
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    #[test]
+    fn chacha20_test_vector() {
+        // RFC 8439, section 2.4.2
+        let key: Vec<u8> = (0..32).collect();
+        let nonce = [0, 0, 0, 0, 0, 0, 0, 0x4a, 0, 0, 0, 0];
+        let mut text = b"Ladies and Gentlemen of the class of '99: If I could offer you only one \
+            tip for the future, sunscreen would be it."
+            .to_vec();
+        chacha20(key[..].try_into().unwrap(), &nonce, 1, &mut text);
+        assert_eq!(
+            hex(&text),
+            "6e2e359a2568f98041ba0728dd0d6981e97e7aec1d4360c20a27afccfd9fae0bf91b65c5524733ab8f59\
+             3dabcd62b3571639d624e65152ab8f530c359f0861d807ca0dbf500d6a6156a38e088a22b65e52bc514d\
+             16ccf806818ce91ab77937365af90bbf74a35be6b40b8eedf2785e42874d"
+        );
+    }
+
+    #[test]
+    fn poly1305_test_vector() {
+        // RFC 8439, section 2.5.2
+        let key = [
+            0x85, 0xd6, 0xbe, 0x78, 0x57, 0x55, 0x6d, 0x33, 0x7f, 0x44, 0x52, 0xfe, 0x42, 0xd5,
+            0x06, 0xa8, 0x01, 0x03, 0x80, 0x8a, 0xfb, 0x0d, 0xb2, 0xfd, 0x4a, 0xbf, 0xf6, 0xaf,
+            0x41, 0x49, 0xf5, 0x1b,
+        ];
+        assert_eq!(
+            hex(&poly1305(&key, b"Cryptographic Forum Research Group")),
+            "a8061dc1305136c6c22b8baf0c0127a9"
+        );
+    }
+
+    #[test]
+    fn seal_and_open() {
+        let mut bytes = [0; 32];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+        let key = Key(bytes);
+        let nonce = [7, 0, 0, 0, 0x40, 0x41, 0x42, 0x43, 0x44, 0x45, 0x46, 0x47];
+        let sealed = key.seal_with(&nonce, b"hello, world\n");
+        // checked against another ChaCha20-Poly1305 implementation
+        assert_eq!(
+            hex(&sealed[NONCE_LEN..]),
+            "21891d4e7e6d4cb7f69b21537c298a8d311a4582e5433e59a7b95151a4"
+        );
+        assert_eq!(key.open(&sealed).unwrap(), b"hello, world\n");
+        assert_eq!(key.open(&key.seal_with(&nonce, b"")).unwrap(), b"");
+    }
+
+    #[test]
+    fn open_tampered() {
+        let key = Key([1; 32]);
+        let sealed = key.seal_with(&[2; NONCE_LEN], b"secret");
+        for i in 0..sealed.len() {
+            let mut tampered = sealed.clone();
+            tampered[i] ^= 1;
+            assert_eq!(
+                key.open(&tampered).unwrap_err().kind(),
+                ErrorKind::InvalidData
+            );
+        }
+        assert_eq!(
+            key.open(&sealed[..NONCE_LEN + TAG_LEN - 1])
+                .unwrap_err()
+                .kind(),
+            ErrorKind::InvalidData
+        );
+        assert_eq!(
+            Key([3; 32]).open(&sealed).unwrap_err().kind(),
+            ErrorKind::InvalidData
+        );
+    }
+}
@@ -0,0 +1,255 @@
+//! Form-style editing of named fields, rendered as a `key: value` document with comments.
+
+use crate::{cleanup, Editor};
+use std::{collections::BTreeMap, fmt, io::Result, sync::Arc};
+
+type Validator = Arc<dyn Fn(&str) -> std::result::Result<(), String> + Send + Sync>;
+
+/// A set of named fields for the user to fill in. See [`edit_form`].
+///
+/// [`edit_form`]: fn.edit_form.html
+#[derive(Clone, Debug, Default)]
+pub struct Form {
+    fields: Vec<Field>,
+}
+
+#[derive(Clone)]
+struct Field {
+    name: String,
+    default: String,
+    help: String,
+    validator: Option<Validator>,
+}
+
+impl fmt::Debug for Field {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Field")
+            .field("name", &self.name)
+            .field("default", &self.default)
+            .field("help", &self.help)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Form {
+    /// Create a form without any fields.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a field named `name`, filled in with `default`, and explained by `help` (which can be
+    /// empty) in comments above it.
+    ///
+    /// Values are a single line, which is why the default can't contain line breaks.
+    ///
+    /// # Panics
+    ///
+    /// If `name` is empty, has whitespace around it, contains `:` or line breaks, or is already the
+    /// name of a field, or `default` contains line breaks, since the form couldn't be filled in.
+    pub fn field<N, D, H>(&mut self, name: N, default: D, help: H) -> &mut Self
+    where
+        N: Into<String>,
+        D: Into<String>,
+        H: Into<String>,
+    {
+        let (name, default) = (name.into(), default.into());
+        assert!(
+            !name.is_empty() && name.trim() == name && !name.contains([':', '\n', '\r']),
+            "'{}' can't be the name of a form field",
+            name
+        );
+        assert!(
+            self.fields.iter().all(|field| field.name != name),
+            "the form already has a field named '{}'",
+            name
+        );
+        assert!(
+            !default.contains(['\n', '\r']),
+            "the default of form field '{}' can't contain line breaks",
+            name
+        );
+        self.fields.push(Field {
+            name,
+            default,
+            help: help.into(),
+            validator: None,
+        });
+        self
+    }
+
+    /// Check the value of the field named `name` with `validator`, which returns an error message
+    /// for invalid values.
+    ///
+    /// # Panics
+    ///
+    /// If the form has no field named `name`.
+    pub fn validator<N, F>(&mut self, name: N, validator: F) -> &mut Self
+    where
+        N: AsRef<str>,
+        F: Fn(&str) -> std::result::Result<(), String> + Send + Sync + 'static,
+    {
+        let name = name.as_ref();
+        let field = self.fields.iter_mut().find(|field| field.name == name);
+        let field = field.unwrap_or_else(|| panic!("the form has no field named '{}'", name));
+        field.validator = Some(Arc::new(validator));
+        self
+    }
+
+    /// Render the fields as a document, with help text in comments starting with `prefix`.
+    fn render(&self, prefix: &str) -> String {
+        let mut text = String::new();
+        for (i, field) in self.fields.iter().enumerate() {
+            if i > 0 {
+                text.push('\n');
+            }
+            for line in field.help.lines() {
+                text.push_str(prefix);
+                if !line.is_empty() {
+                    text.push(' ');
+                    text.push_str(line);
+                }
+                text.push('\n');
+            }
+            text.push_str(&field.name);
+            text.push(':');
+            if !field.default.is_empty() {
+                text.push(' ');
+                text.push_str(&field.default);
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Parse the edited document back into the value of every field, or describe everything
+    /// that's wrong with it, one problem per line.
+    fn parse(
+        &self,
+        text: &str,
+        prefix: &str,
+    ) -> std::result::Result<BTreeMap<String, String>, String> {
+        let mut values = BTreeMap::new();
+        let mut errors = Vec::new();
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(prefix) {
+                continue;
+            }
+            let (name, value) = match line.split_once(':') {
+                Some((name, value)) => (name.trim(), value.trim()),
+                None => {
+                    errors.push(format!("'{}' isn't a `name: value` line", line));
+                    continue;
+                }
+            };
+            if !self.fields.iter().any(|field| field.name == name) {
+                errors.push(format!("{}: no such field", name));
+            } else if values.insert(name.to_owned(), value.to_owned()).is_some() {
+                errors.push(format!("{}: given more than once", name));
+            }
+        }
+        for field in &self.fields {
+            match values.get(&field.name) {
+                Some(value) => {
+                    if let Some(Err(e)) = field.validator.as_ref().map(|check| check(value)) {
+                        errors.push(format!("{}: {}", field.name, e));
+                    }
+                }
+                None => errors.push(format!("{}: missing", field.name)),
+            }
+        }
+        if errors.is_empty() {
+            Ok(values)
+        } else {
+            Err(errors.join("\n"))
+        }
+    }
+}
+
+impl Editor {
+    /// Open a form in the editor until every field is valid, and return the value of every field
+    /// by name, or `None` if the user aborted. See [`edit_form`].
+    ///
+    /// [`edit_form`]: fn.edit_form.html
+    pub fn edit_form(&self, form: &Form) -> Result<Option<BTreeMap<String, String>>> {
+        let prefix = self.comment_prefix.as_deref();
+        let prefix = prefix.unwrap_or(cleanup::DEFAULT_COMMENT_PREFIX);
+        self.edit_validated(form.render(prefix), |text| form.parse(text, prefix))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn form() -> Form {
+        let mut form = Form::new();
+        form.field("name", "", "The name.\n\nRequired.")
+            .field("license", "MIT", "")
+            .validator("name", |name| match name {
+                "" => Err("can't be empty".to_owned()),
+                _ => Ok(()),
+            });
+        form
+    }
+
+    #[test]
+    fn render() {
+        assert_eq!(
+            form().render("#"),
+            "# The name.\n#\n# Required.\nname:\n\nlicense: MIT\n"
+        );
+    }
+
+    #[test]
+    fn parse_values() {
+        let values = form()
+            .parse("# comment\n  name :  demo: app \n\nlicense:\n", "#")
+            .unwrap();
+        assert_eq!(values["name"], "demo: app");
+        assert_eq!(values["license"], "");
+        assert_eq!(values.len(), 2);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let form = form();
+        assert_eq!(
+            form.parse("name: a\nname: b\nlicense: MIT\n", "#")
+                .unwrap_err(),
+            "name: given more than once"
+        );
+        assert_eq!(
+            form.parse("name: a\n", "#").unwrap_err(),
+            "license: missing"
+        );
+        assert_eq!(
+            form.parse("name: a\nlicense: MIT\nauthor: me\nbogus\n", "#")
+                .unwrap_err(),
+            "author: no such field\n'bogus' isn't a `name: value` line"
+        );
+        // every problem is reported at once, including failed validators
+        assert_eq!(
+            form.parse("name:\n", "#").unwrap_err(),
+            "name: can't be empty\nlicense: missing"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "can't be the name of a form field")]
+    fn field_name_with_colon() {
+        Form::new().field("a:b", "", "");
+    }
+
+    #[test]
+    #[should_panic(expected = "can't contain line breaks")]
+    fn multiline_default() {
+        Form::new().field("a", "b\nc", "");
+    }
+
+    #[test]
+    #[should_panic(expected = "already has a field")]
+    fn duplicate_field() {
+        Form::new().field("a", "", "").field("a", "", "");
+    }
+}
@@ -0,0 +1,43 @@
+//! Telling GUI editors apart from terminal editors, and checking whether GUI editors can be shown.
+
+use crate::{registry, wsl};
+#[cfg(not(any(target_os = "macos", target_os = "android", target_os = "haiku")))]
+use std::env;
+use std::path::Path;
+
+/// Check whether `editor` is a GUI program: `Some(true)` if it is, `Some(false)` if it runs in
+/// a terminal, or `None` if it isn't known.
+pub(crate) fn is_gui(editor: &Path) -> Option<bool> {
+    registry::lookup(editor).map(|known| known.gui)
+}
+
+/// Check whether `editor` is a GUI program that can't be shown because there's no display.
+pub(crate) fn is_headless(editor: &Path) -> bool {
+    // Windows programs in WSL show up on the Windows desktop
+    is_gui(editor) == Some(true) && !display_available() && !wsl::is_windows_program(editor)
+}
+
+/// Check whether there's a display server (or desktop) for GUI programs to show windows on.
+#[cfg(not(any(
+    target_os = "windows",
+    target_os = "macos",
+    target_os = "android",
+    target_os = "haiku"
+)))]
+pub(crate) fn display_available() -> bool {
+    ["DISPLAY", "WAYLAND_DISPLAY"]
+        .iter()
+        .any(|var| env::var_os(var).is_some_and(|v| !v.is_empty()))
+}
+
+// SESSIONNAME is set for console and Remote Desktop sessions, but not for services or SSH
+// sessions, whose windows can't be seen
+#[cfg(target_os = "windows")]
+pub(crate) fn display_available() -> bool {
+    env::var_os("SESSIONNAME").is_some()
+}
+
+#[cfg(any(target_os = "macos", target_os = "android", target_os = "haiku"))]
+pub(crate) fn display_available() -> bool {
+    true
+}
@@ -0,0 +1,74 @@
+//! Describing editors to users.
+
+use crate::{gui, registry};
+use std::{
+    ffi::OsString,
+    path::{Path, PathBuf},
+};
+
+/// What's known about an editor, as returned by [`get_editor_info`].
+///
+/// [`get_editor_info`]: fn.get_editor_info.html
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EditorInfo {
+    path: PathBuf,
+    args: Vec<String>,
+    name: String,
+    gui: Option<bool>,
+    blocks: bool,
+}
+
+impl EditorInfo {
+    pub(crate) fn new(path: PathBuf, args: Vec<OsString>) -> Self {
+        let name = match registry::lookup(&path) {
+            Some(known) => known.name.to_owned(),
+            None => path
+                .file_stem()
+                .unwrap_or(path.as_os_str())
+                .to_string_lossy()
+                .into_owned(),
+        };
+        EditorInfo {
+            gui: gui::is_gui(&path),
+            blocks: registry::blocks(&path, &args),
+            args: args
+                .iter()
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect(),
+            name,
+            path,
+        }
+    }
+
+    /// Get the path of the editor program.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Get the arguments the editor is run with (before the path of the file to edit).
+    pub fn args(&self) -> &[String] {
+        &self.args
+    }
+
+    /// Get the name of the editor to show users, e.g. "Visual Studio Code" for `code`. For
+    /// editors this crate doesn't know about, this is the name of the program.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Check whether the editor opens a window of its own: `Some(true)` if it does, `Some(false)`
+    /// if it runs in the terminal, or `None` if it isn't known.
+    pub fn is_gui(&self) -> Option<bool> {
+        self.gui
+    }
+
+    /// Check whether the editor waits for the file to be closed before exiting.
+    ///
+    /// This is `false` for generic file openers like `xdg-open`, and for GUI editors run without
+    /// the argument that makes them wait (e.g. `code` without `-w`), which exit as soon as the
+    /// file is open; edit sessions with them end before the user has made any changes. Editors
+    /// this crate doesn't know about are assumed to wait.
+    pub fn blocks(&self) -> bool {
+        self.blocks
+    }
+}
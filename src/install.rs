@@ -0,0 +1,89 @@
+//! Telling users how to get an editor when none can be found.
+
+#[cfg(not(any(
+    target_os = "windows",
+    target_os = "macos",
+    target_os = "android",
+    target_os = "haiku"
+)))]
+use crate::get_full_editor_path;
+
+// package managers and the commands that install nano with them, in case several are installed
+// (e.g. yum next to dnf)
+#[cfg(not(any(
+    target_os = "windows",
+    target_os = "macos",
+    target_os = "android",
+    target_os = "haiku"
+)))]
+static PACKAGE_MANAGERS: &[(&str, &str)] = &[
+    ("apt-get", "sudo apt install nano"),
+    ("dnf", "sudo dnf install nano"),
+    ("yum", "sudo yum install nano"),
+    ("pacman", "sudo pacman -S nano"),
+    ("zypper", "sudo zypper install nano"),
+    ("apk", "sudo apk add nano"),
+    ("xbps-install", "sudo xbps-install nano"),
+    ("emerge", "sudo emerge app-editors/nano"),
+    ("pkg_add", "doas pkg_add nano"),
+    ("pkgin", "sudo pkgin install nano"),
+    ("pkg", "sudo pkg install nano"),
+];
+
+/// Get a suggestion for how the user can get an editor, for when none can be found.
+///
+/// The suggestion is a sentence fragment like "set the EDITOR environment variable to the
+/// editor you want to use, or install one (e.g. with `sudo apt install nano`)", with an install
+/// command for the platform's (or on Linux, the system's) package manager. It's included in the
+/// message of the [`ErrorKind::NotFound`] errors returned when no editor can be found, but can be
+/// used to build messages of your own too:
+///
+/// ```rust,ignore
+/// if let Err(e) = edit::edit("text") {
+///     if e.kind() == std::io::ErrorKind::NotFound {
+///         eprintln!("To edit the message, {}.", edit::install_hint());
+///     }
+/// }
+/// ```
+///
+/// [`ErrorKind::NotFound`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.NotFound
+pub fn install_hint() -> String {
+    let hint = "set the EDITOR environment variable to the editor you want to use, or install one";
+    match install_command() {
+        Some(command) => format!("{} (e.g. with `{}`)", hint, command),
+        None => hint.to_owned(),
+    }
+}
+
+#[cfg(not(any(
+    target_os = "windows",
+    target_os = "macos",
+    target_os = "android",
+    target_os = "haiku"
+)))]
+fn install_command() -> Option<&'static str> {
+    PACKAGE_MANAGERS
+        .iter()
+        .find(|(program, _)| get_full_editor_path(program).is_ok())
+        .map(|(_, command)| *command)
+}
+
+#[cfg(target_os = "windows")]
+fn install_command() -> Option<&'static str> {
+    Some("winget install Microsoft.Edit")
+}
+
+#[cfg(target_os = "macos")]
+fn install_command() -> Option<&'static str> {
+    Some("brew install nano")
+}
+
+#[cfg(target_os = "android")]
+fn install_command() -> Option<&'static str> {
+    Some("pkg install nano")
+}
+
+#[cfg(target_os = "haiku")]
+fn install_command() -> Option<&'static str> {
+    Some("pkgman install nano")
+}
@@ -11,60 +11,502 @@
 //! // after editing: 'Fill in the blank: Hello, world!'
 //! ```
 //!
+//! To combine options such as a custom editor command and a tempfile suffix, use [`Editor`]:
+//!
+//! ```rust,ignore
+//! let edited = edit::Editor::new().suffix(".md").edit("# Notes\n")?;
+//! ```
+//!
 //! [knows about]: ../src/edit/lib.rs.html#31-61
+//! [`Editor`]: struct.Editor.html
+//! [`parse_editor_command`]: fn.parse_editor_command.html
+//! [`edit_async`]: fn.edit_async.html
+//! [`edit_bytes_async`]: fn.edit_bytes_async.html
+//! [`edit_file_async`]: fn.edit_file_async.html
+//! [`edit_stream_async`]: fn.edit_stream_async.html
+//! [`edit_watch_async`]: fn.edit_watch_async.html
+//! [`edit_toml`]: fn.edit_toml.html
+//! [`edit_json`]: fn.edit_json.html
+//! [`edit_yaml`]: fn.edit_yaml.html
+//! [`edit_with_encoding`]: fn.edit_with_encoding.html
+//! [`edit_with_parser`]: fn.edit_with_parser.html
 //!
 //! Features
 //! ========
 //!
 //! The `edit` crate has the following optional features:
 //!
-//! - `better-path` *(enabled by default)* — Use
-//!   [`which`](https://docs.rs/which) to locate executable programs in `PATH`.
-//!   If this is disabled, programs are still looked up in `PATH`, but a basic
-//!   search is used that does not check for executability.
+//! - `better-path` — Use [`which`](https://docs.rs/which) to locate
+//!   executable programs in `PATH`. If this is disabled (the default),
+//!   programs are looked up in `PATH` by this crate itself, which checks that
+//!   they are executable (the executable bits on Unix, and the extensions in
+//!   `PATHEXT` on Windows) just like `which` does.
+//!
+//! - `config-file` — Read the user's preferred editor and fallbacks from a
+//!   [configuration file](fn.user_config_path.html) shared by every program
+//!   using this crate, and optionally from a
+//!   [per-project configuration file](fn.project_config_path.html).
+//!
+//! - `encoding_rs` — Add [`edit_with_encoding`] and
+//!   [`Editor::encoding`](struct.Editor.html#method.encoding) for editing
+//!   text in legacy encodings like Shift_JIS with
+//!   [encoding_rs](https://docs.rs/encoding_rs).
+//!
+//! - `encrypted-temp` — Add
+//!   [`Editor::encrypt_temp`](struct.Editor.html#method.encrypt_temp) for
+//!   keeping the temporary file encrypted, with the editor only seeing the
+//!   decrypted text in memory.
+//!
+//! - `json` — Add [`edit_json`], which opens a value as a JSON document and
+//!   deserializes it again with [serde_json](https://docs.rs/serde_json).
 //!
-//! - `quoted-env` — Use [`shell-words`](https://docs.rs/shell-words) to split
-//!   apart the values of the `VISUAL` and `EDITOR` environment variables.  If
-//!   this is disabled, the envvars are split up on whitespace.
+//! - `portal` — Allow opening files through the
+//!   [XDG desktop portal](https://flatpak.github.io/xdg-desktop-portal/)
+//!   when running inside a Flatpak or Snap sandbox; see
+//!   [`Editor::portal`](struct.Editor.html#method.portal).
+//!
+//! - `quoted-env` — Does nothing; kept for compatibility. The values of the
+//!   `VISUAL` and `EDITOR` environment variables are now always split with
+//!   [`parse_editor_command`], which understands quotes and escapes.
+//!
+//! - `structured` — Add [`edit_toml`], which opens a value as a TOML
+//!   document and deserializes it again with [serde](https://serde.rs), like
+//!   `kubectl edit` does. Other formats can be edited with [`edit_with_parser`].
+//!
+//! - `yaml` — Add [`edit_yaml`], which does the same with YAML documents
+//!   and [serde_yaml](https://docs.rs/serde_yaml).
+//!
+//! - `tokio` — Add async versions of the editing functions ([`edit_async`],
+//!   [`edit_bytes_async`], and [`edit_file_async`]) that wait for the editor
+//!   and read and write the temporary file without blocking a
+//!   [tokio](https://tokio.rs) runtime, [`edit_stream_async`] for editing
+//!   large contents without holding them in memory, and [`edit_watch_async`]
+//!   for getting the contents of a file every time it is saved. Timeouts and watching for
+//!   saves need the runtime's time driver to be enabled.
+//!
+//! - `async-std` — Add the same async functions as `tokio`, but built on
+//!   [async-process](https://docs.rs/async-process) so they can be awaited
+//!   from async-std, smol, or any other executor without pulling in tokio. If
+//!   both features are enabled, the tokio versions are used.
 
 use std::{
+    borrow::Cow,
+    collections::BTreeMap,
     env,
-    ffi::OsStr,
+    ffi::{OsStr, OsString},
     fs,
-    io::{Error, ErrorKind, Result, Write},
+    io::{self, Error, ErrorKind, IsTerminal, Result, Write},
+    mem,
+    ops::RangeBounds,
     path::{Path, PathBuf},
-    process::{Command, Stdio},
+    process::Command,
+    sync::{RwLock, RwLockReadGuard, RwLockWriteGuard},
+    time::{Duration, Instant, SystemTime},
 };
-pub use tempfile::Builder;
+use tempfile::{NamedTempFile, TempPath};
 #[cfg(feature = "which")]
 use which::which;
 
+#[cfg(any(feature = "tokio", feature = "async-std"))]
+mod async_edit;
+mod atomic;
+mod attributes;
+mod backup;
+mod cancel;
+mod cleanup;
+mod config_file;
+mod desktop;
+mod detector;
+mod diagnose;
+mod diff;
+mod dir;
+mod documents;
+mod encoding;
+#[cfg(feature = "encrypted-temp")]
+mod encrypted;
+mod form;
+mod gui;
+mod info;
+mod install;
+mod list;
+mod lock;
+mod memfd;
+mod multiplexer;
+mod outcome;
+mod parse;
+#[cfg(feature = "picker")]
+mod picker;
+#[cfg(all(feature = "portal", target_os = "linux"))]
+mod portal;
+mod privileged;
+mod pty;
+mod ramdisk;
+mod range;
+mod recover;
+mod registry;
+mod remember;
+mod sandboxed;
+mod secure;
+mod server;
+mod session;
+mod signals;
+mod sniff;
+#[cfg(any(feature = "structured", feature = "json", feature = "yaml"))]
+mod structured;
+mod sudo;
+mod temp_options;
+mod terminal;
+mod unattended;
+mod version;
+mod wsl;
+#[cfg(any(feature = "tokio", feature = "async-std"))]
+pub use async_edit::{
+    edit_async, edit_bytes_async, edit_file_async, edit_stream_async, edit_watch_async,
+    EditedReader, SaveStream,
+};
+pub use atomic::Conflict;
+pub use cancel::{CancelHandle, Cancelled};
+pub use cleanup::LineEndings;
+#[cfg(feature = "config-file")]
+pub use config_file::{project_config_path, save_user_editor, user_config_path};
+pub use detector::{register_detector, DetectorPriority, EditorDetector};
+pub use diagnose::{
+    diagnose, CandidateReport, CandidateSource, CandidateStatus, DetectionReport, SkipReason,
+};
+pub use diff::{DiffLine, EditDiff};
+pub use form::Form;
+pub use info::EditorInfo;
+pub use install::install_hint;
+pub use list::ListEntry;
+pub use lock::LockPolicy;
+pub use outcome::EditOutcome;
+use outcome::Run;
+pub use parse::EditParseError;
+use pty::Pty;
+pub use recover::KeptFile;
+pub use remember::{forget_editor, remembered_editor};
+use session::FileState;
+pub use session::{EditorSession, ExitCodePolicy, ExitError, StdioMode, WaitFor};
+use signals::SignalGuard;
+pub use temp_options::TempOptions;
+pub use version::EditorVersion;
+
 static ENV_VARS: &[&str] = &["VISUAL", "EDITOR"];
 
-// TODO: should we hardcode full paths as well in case $PATH is borked?
-#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+static EDITORS_VAR: &str = "EDIT_EDITORS";
+
+static NONINTERACTIVE_VAR: &str = "EDIT_NONINTERACTIVE_CONTENT";
+
+#[cfg(not(target_os = "windows"))]
+const EDITORS_VAR_SEPARATOR: char = ':';
+
+#[cfg(target_os = "windows")]
+const EDITORS_VAR_SEPARATOR: char = ';';
+
+/// Where the `EDIT_EDITORS` environment variable is consulted when detecting an editor.
+///
+/// `EDIT_EDITORS` holds a list of editor commands to try in order, separated by colons (or
+/// semicolons on Windows), e.g. `EDIT_EDITORS="nvim:vim -u NONE:nano"`. Empty and repeated
+/// entries are skipped. Unlike `VISUAL` and `EDITOR`, it is only read by programs using this
+/// crate, so it can be set without affecting other tools.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EditorsVar {
+    /// Try the editors in `EDIT_EDITORS` before those in `VISUAL` and `EDITOR`. This is the
+    /// default.
+    #[default]
+    BeforeEnvVars,
+    /// Try the editors in `EDIT_EDITORS` after those in `VISUAL` and `EDITOR`.
+    AfterEnvVars,
+    /// Don't read `EDIT_EDITORS` at all.
+    Ignore,
+}
+
+impl EditorsVar {
+    // the commands from `VISUAL` and `EDITOR` and those from `EDIT_EDITORS`, in this order
+    fn place<T>(
+        self,
+        env_commands: impl IntoIterator<Item = T>,
+        editors_var: impl IntoIterator<Item = T>,
+    ) -> Vec<T> {
+        let mut commands = Vec::new();
+        match self {
+            EditorsVar::BeforeEnvVars => {
+                commands.extend(editors_var);
+                commands.extend(env_commands);
+            }
+            EditorsVar::AfterEnvVars => {
+                commands.extend(env_commands);
+                commands.extend(editors_var);
+            }
+            EditorsVar::Ignore => commands.extend(env_commands),
+        }
+        commands
+    }
+}
+
+/// What to do about GUI editors when running as root under `sudo`.
+///
+/// GUI editors running as root can change files anywhere, and often can't even connect to the
+/// user's display (which belongs to the user who ran `sudo`), so unless this is
+/// [`RootPolicy::Allow`], `SUDO_EDITOR` is consulted before `VISUAL` and `EDITOR` too, like
+/// `sudoedit` does.
+///
+/// [`RootPolicy::Allow`]: #variant.Allow
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RootPolicy {
+    /// Treat root like any other user.
+    Allow,
+    /// Skip GUI editors (and generic file openers). This is the default.
+    #[default]
+    SkipGui,
+    /// Run GUI editors as the user who ran `sudo`. Temporary files are given to that user, but
+    /// files passed to e.g. [`edit_file`] must already be writable by them.
+    ///
+    /// [`edit_file`]: fn.edit_file.html
+    RunGuiAsUser,
+}
+
+/// A kind of editor, for choosing which of the editors this crate knows about to try first.
+///
+/// See [`Editor::prefer`].
+///
+/// [`Editor::prefer`]: struct.Editor.html#method.prefer
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EditorKind {
+    /// Editors that run in the terminal, like `vim` or `nano`.
+    Terminal,
+    /// Editors that open a window of their own, like Visual Studio Code or the desktop's default
+    /// text editor.
+    Gui,
+}
+
+fn is_ssh_session() -> bool {
+    env::var_os("SSH_CONNECTION").is_some() || env::var_os("SSH_TTY").is_some()
+}
+
+fn is_gui_command(command: &OsStr) -> bool {
+    parse_editor_command_os(command).is_ok_and(|(program, _)| gui::is_gui(&program) == Some(true))
+}
+
+// whether a full-screen editor (as named by VISUAL) can be used
+fn capable_terminal() -> bool {
+    let term = env::var_os("TERM").unwrap_or_default();
+    has_terminal() && !term.is_empty() && term != "dumb"
+}
+
+// whether terminal editors can be used at all
+fn has_terminal() -> bool {
+    io::stdin().is_terminal() && io::stdout().is_terminal()
+}
+
+// whether terminal editors are pleasant enough to be tried before GUI editors. On Windows, they
+// are in terminals like Windows Terminal, but not in the legacy console host.
+#[cfg(target_os = "windows")]
+fn prefer_terminal_editors() -> bool {
+    has_terminal()
+        && ["WT_SESSION", "TERM_PROGRAM", "TERM", "ConEmuPID"]
+            .iter()
+            .any(|var| env::var_os(var).is_some())
+}
+
+#[cfg(not(target_os = "windows"))]
+fn prefer_terminal_editors() -> bool {
+    true
+}
+
+fn editors_var_commands() -> Vec<String> {
+    split_editors_var(&env::var(EDITORS_VAR).unwrap_or_default())
+}
+
+// the commands in a value of `EDIT_EDITORS`, in order and without empty or repeated ones
+fn split_editors_var(value: &str) -> Vec<String> {
+    let mut commands: Vec<String> = Vec::new();
+    for command in value.split(EDITORS_VAR_SEPARATOR).map(str::trim) {
+        if !command.is_empty() && !commands.iter().any(|c| c == command) {
+            commands.push(command.to_string());
+        }
+    }
+    commands
+}
+
+// the editor environment variables to read, in order of precedence
+fn env_var_order(
+    mut env_vars: Vec<&str>,
+    visual_after_editor: bool,
+    sudo_editor: bool,
+    git_editor: bool,
+) -> Vec<&str> {
+    if visual_after_editor {
+        let visual = env_vars.iter().position(|&v| v == "VISUAL");
+        let editor = env_vars.iter().position(|&v| v == "EDITOR");
+        if let (Some(visual), Some(editor)) = (visual, editor) {
+            if visual < editor {
+                let visual = env_vars.remove(visual);
+                env_vars.insert(editor, visual);
+            }
+        }
+    }
+    if sudo_editor {
+        env_vars.insert(0, "SUDO_EDITOR");
+    }
+    if git_editor {
+        env_vars.push("GIT_EDITOR");
+    }
+    env_vars
+}
+
+#[cfg(not(any(
+    target_os = "windows", target_os = "macos", target_os = "freebsd", target_os = "dragonfly",
+    target_os = "openbsd", target_os = "netbsd", target_os = "illumos", target_os = "solaris",
+    target_os = "haiku", target_os = "android",
+)))]
 #[rustfmt::skip]
-static HARDCODED_NAMES: &[&str] = &[
-    // CLI editors
+static HARDCODED_CLI_NAMES: &[&str] = &[
     "sensible-editor", "nano", "pico", "vim", "nvim", "vi", "emacs",
-    // GUI editors
+];
+
+#[cfg(not(any(
+    target_os = "windows", target_os = "macos", target_os = "freebsd", target_os = "dragonfly",
+    target_os = "openbsd", target_os = "netbsd", target_os = "illumos", target_os = "solaris",
+    target_os = "haiku", target_os = "android",
+)))]
+#[rustfmt::skip]
+static HARDCODED_GUI_NAMES: &[&str] = &[
     "code", "atom", "subl", "gedit", "gvim",
-    // Generic "file openers"
-    "xdg-open", "gnome-open", "kde-open",
+];
+
+// Generic "file openers"
+#[cfg(not(any(
+    target_os = "windows", target_os = "macos", target_os = "freebsd", target_os = "dragonfly",
+    target_os = "openbsd", target_os = "netbsd", target_os = "illumos", target_os = "solaris",
+    target_os = "haiku", target_os = "android",
+)))]
+#[rustfmt::skip]
+static GENERIC_OPENERS: &[&str] = &[
+    "xdg-open",
+    // the openers of desktop environments, in case xdg-open isn't installed
+    "gio open", "kde-open", "kde-open5", "exo-open",
+    // from wslu, for WSL
+    "wslview",
+];
+
+#[cfg(any(target_os = "freebsd", target_os = "dragonfly"))]
+#[rustfmt::skip]
+static HARDCODED_CLI_NAMES: &[&str] = &[
+    // ee ("easy editor") is part of the base system
+    "ee", "nano", "pico", "vim", "nvim", "vi", "mg", "emacs",
+];
+
+#[cfg(target_os = "openbsd")]
+#[rustfmt::skip]
+static HARDCODED_CLI_NAMES: &[&str] = &[
+    // mg (a small emacs) is part of the base system
+    "mg", "nano", "pico", "vim", "nvim", "vi", "emacs",
+];
+
+#[cfg(target_os = "netbsd")]
+#[rustfmt::skip]
+static HARDCODED_CLI_NAMES: &[&str] = &[
+    "nano", "pico", "vim", "nvim", "vi", "mg", "emacs",
+];
+
+#[cfg(any(
+    target_os = "freebsd", target_os = "dragonfly", target_os = "openbsd", target_os = "netbsd",
+))]
+#[rustfmt::skip]
+static HARDCODED_GUI_NAMES: &[&str] = &[
+    "code", "subl", "gedit", "kate", "mousepad", "gvim",
+];
+
+// Generic "file openers"
+#[cfg(any(
+    target_os = "freebsd", target_os = "dragonfly", target_os = "openbsd", target_os = "netbsd",
+))]
+#[rustfmt::skip]
+static GENERIC_OPENERS: &[&str] = &[
+    "xdg-open",
+];
+
+#[cfg(any(target_os = "illumos", target_os = "solaris"))]
+#[rustfmt::skip]
+static HARDCODED_CLI_NAMES: &[&str] = &[
+    "nano", "pico", "vim", "vi", "emacs",
+];
+
+#[cfg(any(target_os = "illumos", target_os = "solaris"))]
+#[rustfmt::skip]
+static HARDCODED_GUI_NAMES: &[&str] = &[
+    // pluma is the text editor of MATE, the default desktop on OpenIndiana
+    "pluma", "gedit", "gvim",
+];
+
+// Generic "file openers"
+#[cfg(any(target_os = "illumos", target_os = "solaris"))]
+#[rustfmt::skip]
+static GENERIC_OPENERS: &[&str] = &[
+    "xdg-open",
+];
+
+#[cfg(target_os = "haiku")]
+#[rustfmt::skip]
+static HARDCODED_CLI_NAMES: &[&str] = &[
+    "nano", "vim", "vi",
+];
+
+#[cfg(target_os = "haiku")]
+#[rustfmt::skip]
+static HARDCODED_GUI_NAMES: &[&str] = &[
+    // StyledEdit is installed by default
+    "Pe", "Koder", "StyledEdit",
+];
+
+// Generic "file openers"
+#[cfg(target_os = "haiku")]
+#[rustfmt::skip]
+static GENERIC_OPENERS: &[&str] = &[
+    "open",
+];
+
+// Termux, which has its own prefix instead of /usr
+#[cfg(target_os = "android")]
+const TERMUX_PREFIX: &str = "/data/data/com.termux/files/usr";
+
+#[cfg(target_os = "android")]
+#[rustfmt::skip]
+static HARDCODED_CLI_NAMES: &[&str] = &[
+    "nano", "vim", "nvim", "vi", "micro", "emacs",
+];
+
+#[cfg(target_os = "android")]
+static HARDCODED_GUI_NAMES: &[&str] = &[];
+
+// Generic "file openers"
+#[cfg(target_os = "android")]
+#[rustfmt::skip]
+static GENERIC_OPENERS: &[&str] = &[
+    "termux-open",
 ];
 
 #[cfg(target_os = "macos")]
 #[rustfmt::skip]
-static HARDCODED_NAMES: &[&str] = &[
-    // CLI editors
+static HARDCODED_CLI_NAMES: &[&str] = &[
     "nano", "pico", "vim", "nvim", "vi", "emacs",
+];
+
+#[cfg(target_os = "macos")]
+#[rustfmt::skip]
+static HARDCODED_GUI_NAMES: &[&str] = &[
     // open has a special flag to open in the default text editor
     // (this really should come before the CLI editors, but in order
     // not to break compatibility, we still prefer CLI over GUI)
-    "open -Wt",
-    // GUI editors
-    "code -w", "atom -w", "subl -w", "gvim", "mate",
-    // Generic "file openers"
+    "open -W -t",
+    "code", "atom", "subl", "gvim", "mate",
+];
+
+// Generic "file openers"
+#[cfg(target_os = "macos")]
+#[rustfmt::skip]
+static GENERIC_OPENERS: &[&str] = &[
     "open -a TextEdit",
     "open -a TextMate",
     // TODO: "open -f" reads input from standard input and opens with
@@ -74,161 +516,918 @@ static HARDCODED_NAMES: &[&str] = &[
 
 #[cfg(target_os = "windows")]
 #[rustfmt::skip]
-static HARDCODED_NAMES: &[&str] = &[
-    // GUI editors
-    "code.cmd -n -w", "atom.exe -w", "subl.exe -w",
+static HARDCODED_CLI_NAMES: &[&str] = &[
+    "nvim.exe", "vim.exe", "nano.exe", "micro.exe", "hx.exe",
+    // Microsoft Edit, not to be confused with the old MS-DOS editor
+    "edit.exe",
+];
+
+#[cfg(target_os = "windows")]
+#[rustfmt::skip]
+static HARDCODED_GUI_NAMES: &[&str] = &[
+    "code.cmd", "atom.exe", "subl.exe",
     // notepad++ does not block for input
     // Installed by default
     "notepad.exe",
-    // Generic "file openers"
+];
+
+// Generic "file openers"
+#[cfg(target_os = "windows")]
+#[rustfmt::skip]
+static GENERIC_OPENERS: &[&str] = &[
     "cmd.exe /C start",
 ];
 
+// Full paths of editors that are usually installed, for when $PATH is unset or broken (e.g. in
+// cron jobs, minimal containers, or launchd agents)
+#[cfg(not(any(
+    target_os = "windows", target_os = "macos", target_os = "freebsd", target_os = "dragonfly",
+    target_os = "openbsd", target_os = "netbsd", target_os = "illumos", target_os = "solaris",
+    target_os = "haiku", target_os = "android",
+)))]
+#[rustfmt::skip]
+static HARDCODED_PATHS: &[&str] = &[
+    "/usr/bin/nano", "/bin/nano", "/usr/bin/vim", "/bin/vim", "/usr/bin/vi", "/bin/vi",
+];
+
+#[cfg(any(target_os = "freebsd", target_os = "dragonfly"))]
+#[rustfmt::skip]
+static HARDCODED_PATHS: &[&str] = &[
+    "/usr/bin/ee", "/usr/bin/vi", "/usr/local/bin/nano", "/usr/local/bin/vim",
+];
+
+#[cfg(target_os = "openbsd")]
+#[rustfmt::skip]
+static HARDCODED_PATHS: &[&str] = &[
+    "/usr/bin/mg", "/usr/bin/vi", "/usr/local/bin/nano", "/usr/local/bin/vim",
+];
+
+// packages from pkgsrc are installed to /usr/pkg
+#[cfg(target_os = "netbsd")]
+#[rustfmt::skip]
+static HARDCODED_PATHS: &[&str] = &[
+    "/usr/pkg/bin/nano", "/usr/pkg/bin/vim", "/usr/bin/vi",
+];
+
+#[cfg(any(target_os = "illumos", target_os = "solaris"))]
+#[rustfmt::skip]
+static HARDCODED_PATHS: &[&str] = &[
+    "/usr/bin/nano", "/opt/local/bin/nano", "/usr/bin/vim", "/usr/bin/vi", "/usr/xpg4/bin/vi",
+];
+
+#[cfg(target_os = "haiku")]
+#[rustfmt::skip]
+static HARDCODED_PATHS: &[&str] = &[
+    "/boot/system/bin/nano", "/boot/system/bin/vim", "/boot/system/apps/StyledEdit",
+];
+
+#[cfg(target_os = "android")]
+#[rustfmt::skip]
+static HARDCODED_PATHS: &[&str] = &[
+    "/data/data/com.termux/files/usr/bin/nano", "/data/data/com.termux/files/usr/bin/vim",
+    "/data/data/com.termux/files/usr/bin/vi",
+];
+
+#[cfg(target_os = "macos")]
+#[rustfmt::skip]
+static HARDCODED_PATHS: &[&str] = &[
+    "/usr/bin/nano", "/usr/bin/vim", "/usr/bin/vi", "/usr/bin/open -W -t",
+];
+
+#[cfg(target_os = "windows")]
+#[rustfmt::skip]
+static HARDCODED_PATHS: &[&str] = &[
+    r"C:\Windows\System32\notepad.exe", r"C:\Windows\notepad.exe",
+];
+
+struct Config {
+    editor: Option<String>,
+    env_vars: Option<Vec<String>>,
+    before: Vec<String>,
+    after: Vec<String>,
+}
+
+static CONFIG: RwLock<Config> = RwLock::new(Config {
+    editor: None,
+    env_vars: None,
+    before: Vec::new(),
+    after: Vec::new(),
+});
+
+fn config() -> RwLockReadGuard<'static, Config> {
+    CONFIG.read().unwrap_or_else(|e| e.into_inner())
+}
+
+fn config_mut() -> RwLockWriteGuard<'static, Config> {
+    CONFIG.write().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Set the editor command used by every later edit session in this process.
+///
+/// This is meant for applications that let users choose an editor in their own configuration:
+/// call it once at startup, and every later call to [`edit`], [`edit_file`], etc. (including
+/// those made through an [`Editor`] without its own [`Editor::editor`] set) will use `command`
+/// instead of detecting an editor.
+///
+/// `command` is interpreted as in [`edit_with_command`].
+///
+/// [`edit`]: fn.edit.html
+/// [`edit_file`]: fn.edit_file.html
+/// [`Editor`]: struct.Editor.html
+/// [`Editor::editor`]: struct.Editor.html#method.editor
+/// [`edit_with_command`]: fn.edit_with_command.html
+pub fn set_default_editor<S: Into<String>>(command: S) {
+    config_mut().editor = Some(command.into());
+}
+
+/// Undo [`set_default_editor`], going back to detecting an editor.
+///
+/// [`set_default_editor`]: fn.set_default_editor.html
+pub fn clear_default_editor() {
+    config_mut().editor = None;
+}
+
+/// Set the environment variables consulted (in order of precedence) when detecting an editor for
+/// every later edit session in this process.
+///
+/// The default is `VISUAL`, then `EDITOR`. An [`Editor`] with its own [`Editor::env_vars`] set
+/// ignores this setting.
+///
+/// [`Editor`]: struct.Editor.html
+/// [`Editor::env_vars`]: struct.Editor.html#method.env_vars
+pub fn set_env_vars<S: AsRef<str>>(vars: &[S]) {
+    config_mut().env_vars = Some(vars.iter().map(|v| v.as_ref().to_owned()).collect());
+}
+
+/// Register an extra editor command to try before the hardcoded list of editors.
+///
+/// Registered editors are tried after the `VISUAL` and `EDITOR` environment variables (so users
+/// can still override them), but before any of the editors this crate knows about. Editors
+/// registered earlier take precedence over those registered later. `command` is split into a
+/// program and arguments with [`parse_editor_command`].
+///
+/// This affects every later call in the process; to add a candidate for a single edit session,
+/// use [`Editor::candidate`] instead.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// edit::register_candidate("ourmagic --wait");
+/// ```
+///
+/// [`parse_editor_command`]: fn.parse_editor_command.html
+/// [`Editor::candidate`]: struct.Editor.html#method.candidate
+pub fn register_candidate<S: Into<String>>(command: S) {
+    config_mut().before.push(command.into());
+}
+
+/// Register an extra editor command to try after the hardcoded list of editors.
+///
+/// This is like [`register_candidate`], except the editor is only used as a last resort, when
+/// none of the editors this crate knows about can be found.
+///
+/// [`register_candidate`]: fn.register_candidate.html
+pub fn register_fallback_candidate<S: Into<String>>(command: S) {
+    config_mut().after.push(command.into());
+}
+
 #[cfg(feature = "better-path")]
 fn get_full_editor_path<T: AsRef<OsStr>>(binary_name: T) -> which::Result<PathBuf> {
-    which(binary_name)
+    which(&binary_name).or_else(|e| {
+        let dirs = env::join_paths(extra_search_dirs()).map_err(|_| e)?;
+        let cwd = env::current_dir().map_err(|_| e)?;
+        which::which_in(binary_name, Some(dirs), cwd)
+    })
 }
 
 #[cfg(not(feature = "better-path"))]
 fn get_full_editor_path<T: AsRef<OsStr> + AsRef<Path>>(binary_name: T) -> Result<PathBuf> {
-    if let Some(paths) = env::var_os("PATH") {
-        for dir in env::split_paths(&paths) {
-            if dir.join(&binary_name).is_file() {
-                return Ok(dir.join(&binary_name));
-            }
+    let binary_name: &Path = binary_name.as_ref();
+    // like a shell, only look up names without a directory up in PATH
+    if binary_name.components().count() > 1 {
+        return find_executable(binary_name).ok_or_else(|| Error::from(ErrorKind::NotFound));
+    }
+
+    let paths = env::var_os("PATH");
+    for dir in paths
+        .iter()
+        .flat_map(env::split_paths)
+        .chain(extra_search_dirs())
+    {
+        if let Some(path) = find_executable(&dir.join(binary_name)) {
+            return Ok(path);
         }
     }
 
     Err(Error::from(ErrorKind::NotFound))
 }
 
-#[cfg(not(feature = "quoted-env"))]
-fn string_to_cmd(s: String) -> (PathBuf, Vec<String>) {
-    let mut args = s.split_ascii_whitespace();
-    (
-        args.next().unwrap().into(),
-        args.map(String::from).collect(),
-    )
+// Directories package managers install programs to, which are searched after PATH since they
+// aren't always in it (e.g. GUI apps on MacOS get a minimal PATH without Homebrew's directory)
+#[cfg(target_os = "macos")]
+fn extra_search_dirs() -> Vec<PathBuf> {
+    ["/opt/homebrew/bin", "/usr/local/bin", "/opt/local/bin"]
+        .iter()
+        .map(PathBuf::from)
+        .collect()
 }
 
-#[cfg(feature = "quoted-env")]
-fn string_to_cmd(s: String) -> (PathBuf, Vec<String>) {
-    match shell_words::split(&s) {
-        Ok(mut v) if !v.is_empty() => (v.remove(0).into(), v),
-        _ => {
-            let mut args = s.split_ascii_whitespace();
-            (
-                args.next().unwrap().into(),
-                args.map(String::from).collect(),
-            )
-        }
+// Scoop and Chocolatey put shims for the programs they install in a directory of their own, and
+// winget puts symlinks in one
+#[cfg(target_os = "windows")]
+fn extra_search_dirs() -> Vec<PathBuf> {
+    let var = |name| {
+        env::var_os(name)
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+    };
+    let program_data = var("ProgramData").unwrap_or_else(|| PathBuf::from(r"C:\ProgramData"));
+    let scoop = var("SCOOP").or_else(|| var("USERPROFILE").map(|dir| dir.join("scoop")));
+    let scoop_global = var("SCOOP_GLOBAL").unwrap_or_else(|| program_data.join("scoop"));
+    let chocolatey = var("ChocolateyInstall").unwrap_or_else(|| program_data.join("chocolatey"));
+    let winget = var("LOCALAPPDATA").map(|dir| dir.join(r"Microsoft\WinGet\Links"));
+    let winget_global = var("ProgramFiles").map(|dir| dir.join(r"WinGet\Links"));
+
+    let mut dirs: Vec<_> = scoop.into_iter().map(|dir| dir.join("shims")).collect();
+    dirs.push(scoop_global.join("shims"));
+    dirs.push(chocolatey.join("bin"));
+    dirs.extend(winget);
+    dirs.extend(winget_global);
+    dirs
+}
+
+// Termux's directory, in case this was started by something other than a Termux shell
+#[cfg(target_os = "android")]
+fn extra_search_dirs() -> Vec<PathBuf> {
+    vec![termux_prefix().join("bin")]
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "android")))]
+fn extra_search_dirs() -> Vec<PathBuf> {
+    Vec::new()
+}
+
+#[cfg(target_os = "android")]
+fn termux_prefix() -> PathBuf {
+    env::var_os("PREFIX")
+        .filter(|v| !v.is_empty())
+        .map_or_else(|| PathBuf::from(TERMUX_PREFIX), PathBuf::from)
+}
+
+/// Get the directory to create temporary files in.
+pub(crate) fn temp_dir() -> PathBuf {
+    // the default on Android (/data/local/tmp) can't be written to by apps, so use Termux's
+    // temporary directory instead (which Termux normally sets TMPDIR to anyway)
+    #[cfg(target_os = "android")]
+    if env::var_os("TMPDIR").is_none() {
+        return termux_prefix().join("tmp");
+    }
+    env::temp_dir()
+}
+
+#[cfg(all(not(feature = "better-path"), unix))]
+fn find_executable(path: &Path) -> Option<PathBuf> {
+    use std::os::unix::fs::PermissionsExt;
+
+    // a file that can't be executed (e.g. a stray file named "vim") shouldn't hide the real editor
+    let metadata = fs::metadata(path).ok()?;
+    if metadata.is_file() && metadata.permissions().mode() & 0o111 != 0 {
+        Some(path.to_owned())
+    } else {
+        None
     }
 }
 
-fn get_full_editor_cmd(s: String) -> Result<(PathBuf, Vec<String>)> {
-    let (path, args) = string_to_cmd(s);
-    match get_full_editor_path(&path) {
-        Ok(result) => Ok((result, args)),
-        Err(_) if path.exists() => Ok((path, args)),
-        Err(_) => Err(Error::from(ErrorKind::NotFound))
+#[cfg(all(not(feature = "better-path"), not(unix), not(target_os = "windows")))]
+fn find_executable(path: &Path) -> Option<PathBuf> {
+    if path.is_file() {
+        Some(path.to_owned())
+    } else {
+        None
     }
 }
 
-fn get_editor_args() -> Result<(PathBuf, Vec<String>)> {
-    ENV_VARS
+// on Windows, programs can be run without their extension (e.g. "code" for "code.cmd"), so try
+// each extension in PATHEXT as well
+#[cfg(all(not(feature = "better-path"), target_os = "windows"))]
+fn find_executable(path: &Path) -> Option<PathBuf> {
+    let extensions = executable_extensions();
+    let has_extension = path.extension().is_some_and(|ext| {
+        let ext = format!(".{}", ext.to_string_lossy());
+        extensions.iter().any(|e| e.eq_ignore_ascii_case(&ext))
+    });
+    if has_extension && path.is_file() {
+        return Some(path.to_owned());
+    }
+
+    extensions
         .iter()
-        .filter_map(env::var_os)
-        .filter(|v| !v.is_empty())
-        .filter_map(|v| v.into_string().ok())
-        .filter_map(|s| get_full_editor_cmd(s).ok())
-        .next()
-        .or_else(|| {
-            HARDCODED_NAMES
-                .iter()
-                .map(|s| s.to_string())
-                .filter_map(|s| get_full_editor_cmd(s).ok())
-                .next()
+        .map(|ext| {
+            let mut path = path.as_os_str().to_owned();
+            path.push(ext);
+            PathBuf::from(path)
         })
-        .ok_or_else(|| Error::from(ErrorKind::NotFound))
+        .find(|path| path.is_file())
 }
 
-/// Find the system default editor, if there is one.
+#[cfg(all(not(feature = "better-path"), target_os = "windows"))]
+fn executable_extensions() -> Vec<String> {
+    env::var("PATHEXT")
+        .ok()
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| ".COM;.EXE;.BAT;.CMD".to_string())
+        .split(';')
+        .filter(|ext| !ext.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Split an editor command (such as the value of `VISUAL` or `EDITOR`) into a program and its
+/// arguments.
 ///
-/// This function checks several sources to find an editor binary (in order of precedence):
+/// Words are separated by whitespace, and quoting works much like it does in a POSIX shell:
 ///
-/// - the `VISUAL` environment variable
-/// - the `EDITOR` environment variable
-/// - hardcoded lists of common CLI editors on MacOS/Unix
-/// - hardcoded lists of GUI editors on Windows/MacOS/Unix
-/// - platform-specific generic "file openers" (e.g. `xdg-open` on Linux and `open` on MacOS)
+/// - text in single quotes (`'...'`) is taken literally
+/// - text in double quotes (`"..."`) is taken literally, except that a backslash can be used to
+///   escape `"`, `\`, `$`, or `` ` ``
+/// - outside of quotes, a backslash escapes the character after it
 ///
-/// Also, it doesn't blindly return whatever is in an environment variable. If a specified editor
-/// can't be found or isn't marked as executable (the executable bit is checked when the default
-/// feature `better-path` is enabled), this function will fall back to the next one that is.
+/// On Windows, where backslashes are path separators, backslashes are never treated as escapes.
 ///
-/// # Returns
+/// This means commands like `"/opt/My Editor/bin/edit" --wait` are split as expected.
 ///
-/// If successful, returns the name of the system default editor.
-/// Note that in most cases the full path of the editor isn't returned; what is guaranteed is the
-/// return value being suitable as the program name for e.g. [`Command::new`].
+/// # Returns
 ///
-/// On some platforms, a text editor is installed by default, so the chances of a failure are low
-/// save for `PATH` being unset or something weird like that. However, it is possible for one not
-/// to be located, and in that case `get_editor` will return [`ErrorKind::NotFound`].
+/// If successful, returns the program and its arguments.
+/// If `command` has an unterminated quote or a trailing backslash, or doesn't contain any words,
+/// returns [`ErrorKind::InvalidInput`].
 ///
 /// # Example
 ///
 /// ```rust,ignore
-/// use edit::get_editor;
-///
-/// // will print e.g. "default editor: nano"
-/// println!("default editor:", get_editor().expect("can't find an editor").to_str());
+/// let (program, args) = edit::parse_editor_command(r#""/opt/My Editor/bin/edit" --wait"#)?;
+/// assert_eq!(program, std::path::Path::new("/opt/My Editor/bin/edit"));
+/// assert_eq!(args, ["--wait"]);
 /// ```
 ///
-/// [`Command::new`]: https://doc.rust-lang.org/std/process/struct.Command.html#method.new
-/// [`ErrorKind::NotFound`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.NotFound
-pub fn get_editor() -> Result<PathBuf> {
-    get_editor_args().map(|(x, _)| x)
+/// [`ErrorKind::InvalidInput`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidInput
+pub fn parse_editor_command(command: &str) -> Result<(PathBuf, Vec<String>)> {
+    let (program, args) = parse_editor_command_os(OsStr::new(command))?;
+    // splitting only happens at ASCII characters, so every word is still valid UTF-8
+    let args = args
+        .into_iter()
+        .map(|arg| arg.to_string_lossy().into_owned())
+        .collect();
+    Ok((program, args))
 }
 
-/// Open the contents of a string or buffer in the [default editor].
-///
-/// This function saves its input to a temporary file and then opens the default editor to it.
-/// It waits for the editor to return, re-reads the (possibly changed/edited) temporary file, and
-/// then deletes it.
-///
-/// # Arguments
-///
-/// `text` is written to the temporary file before invoking the editor. (The editor opens with
-/// the contents of `text` already in the file).
+/// Split an editor command that may not be valid Unicode into a program and its arguments.
 ///
-/// # Returns
+/// This works exactly like [`parse_editor_command`], but takes and returns [`OsStr`]-based types
+/// so that e.g. an `EDITOR` containing a path in a non-UTF-8 locale encoding can still be used.
 ///
-/// If successful, returns the edited string.
-/// If the edited version of the file can't be decoded as UTF-8, returns [`ErrorKind::InvalidData`].
+/// [`parse_editor_command`]: fn.parse_editor_command.html
+/// [`OsStr`]: https://doc.rust-lang.org/std/ffi/struct.OsStr.html
+pub fn parse_editor_command_os(command: &OsStr) -> Result<(PathBuf, Vec<OsString>)> {
+    let mut words = split_words(command)?;
+    if words.is_empty() {
+        return Err(Error::new(ErrorKind::InvalidInput, "empty editor command"));
+    }
+
+    let program = words.remove(0);
+    Ok((program.into(), words))
+}
+
+// the characters with special meaning in editor commands, as code units (see os_units)
+const SPACE: u32 = b' ' as u32;
+const TAB: u32 = b'\t' as u32;
+const NEWLINE: u32 = b'\n' as u32;
+const VERTICAL_TAB: u32 = 0x0b;
+const FORM_FEED: u32 = 0x0c;
+const CARRIAGE_RETURN: u32 = b'\r' as u32;
+const SINGLE_QUOTE: u32 = b'\'' as u32;
+const DOUBLE_QUOTE: u32 = b'"' as u32;
+const BACKSLASH: u32 = b'\\' as u32;
+const DOLLAR: u32 = b'$' as u32;
+const BACKTICK: u32 = b'`' as u32;
+
+fn split_words(s: &OsStr) -> Result<Vec<OsString>> {
+    let escapes = !cfg!(windows);
+    let unterminated = || Error::new(ErrorKind::InvalidInput, "unterminated quote or escape");
+
+    let mut words = Vec::new();
+    let mut word = Vec::new();
+    let mut in_word = false;
+    let units = os_units(s);
+    let mut units = units.into_iter();
+    while let Some(c) = units.next() {
+        match c {
+            SPACE | TAB | NEWLINE | VERTICAL_TAB | FORM_FEED | CARRIAGE_RETURN => {
+                if in_word {
+                    words.push(os_from_units(&mem::take(&mut word)));
+                    in_word = false;
+                }
+            }
+            SINGLE_QUOTE => {
+                in_word = true;
+                loop {
+                    match units.next() {
+                        Some(SINGLE_QUOTE) => break,
+                        Some(c) => word.push(c),
+                        None => return Err(unterminated()),
+                    }
+                }
+            }
+            DOUBLE_QUOTE => {
+                in_word = true;
+                loop {
+                    match units.next() {
+                        Some(DOUBLE_QUOTE) => break,
+                        Some(BACKSLASH) if escapes => match units.next() {
+                            Some(c @ DOUBLE_QUOTE)
+                            | Some(c @ BACKSLASH)
+                            | Some(c @ DOLLAR)
+                            | Some(c @ BACKTICK) => word.push(c),
+                            Some(NEWLINE) => {}
+                            Some(c) => {
+                                word.push(BACKSLASH);
+                                word.push(c);
+                            }
+                            None => return Err(unterminated()),
+                        },
+                        Some(c) => word.push(c),
+                        None => return Err(unterminated()),
+                    }
+                }
+            }
+            BACKSLASH if escapes => {
+                in_word = true;
+                match units.next() {
+                    Some(NEWLINE) => {}
+                    Some(c) => word.push(c),
+                    None => return Err(unterminated()),
+                }
+            }
+            c => {
+                in_word = true;
+                word.push(c);
+            }
+        }
+    }
+
+    if in_word {
+        words.push(os_from_units(&word));
+    }
+    Ok(words)
+}
+
+// split_words works on the platform's own representation of OsStr (bytes on Unix, UTF-16 code
+// units on Windows), widened to u32, so that nothing is lost for strings that aren't Unicode
+
+#[cfg(unix)]
+fn os_units(s: &OsStr) -> Vec<u32> {
+    use std::os::unix::ffi::OsStrExt;
+    s.as_bytes().iter().map(|&b| b.into()).collect()
+}
+
+#[cfg(unix)]
+fn os_from_units(units: &[u32]) -> OsString {
+    use std::os::unix::ffi::OsStringExt;
+    OsString::from_vec(units.iter().map(|&u| u as u8).collect())
+}
+
+#[cfg(windows)]
+fn os_units(s: &OsStr) -> Vec<u32> {
+    use std::os::windows::ffi::OsStrExt;
+    s.encode_wide().map(u32::from).collect()
+}
+
+#[cfg(windows)]
+fn os_from_units(units: &[u32]) -> OsString {
+    use std::os::windows::ffi::OsStringExt;
+    OsString::from_wide(&units.iter().map(|&u| u as u16).collect::<Vec<_>>())
+}
+
+#[cfg(not(any(unix, windows)))]
+fn os_units(s: &OsStr) -> Vec<u32> {
+    s.to_string_lossy().chars().map(u32::from).collect()
+}
+
+#[cfg(not(any(unix, windows)))]
+fn os_from_units(units: &[u32]) -> OsString {
+    units
+        .iter()
+        .filter_map(|&u| char::from_u32(u))
+        .collect::<String>()
+        .into()
+}
+
+// results of looking up editor commands, keyed by the command, PATH, and the current directory
+// (which are all that a lookup depends on, other than the filesystem)
+type LookupKey = (OsString, Option<OsString>, Option<PathBuf>);
+type LookupCache = BTreeMap<LookupKey, Option<(PathBuf, Vec<OsString>)>>;
+
+static LOOKUP_CACHE: RwLock<LookupCache> = RwLock::new(BTreeMap::new());
+
+/// Forget which editors were found (or not found) by earlier lookups.
+///
+/// Looking an editor up in `PATH` can be slow (e.g. on network filesystems), so the result of
+/// looking up each editor command is cached for the rest of the process. Since the cache takes
+/// the values of `PATH` and the current directory into account, and environment variables like
+/// `EDITOR` are still read every time, this is only needed if editors are installed or removed
+/// while the program is running.
+pub fn invalidate_editor_cache() {
+    LOOKUP_CACHE
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .clear();
+}
+
+fn get_full_editor_cmd(s: &OsStr) -> Result<(PathBuf, Vec<OsString>)> {
+    let key = (s.to_owned(), env::var_os("PATH"), env::current_dir().ok());
+    let cached = LOOKUP_CACHE
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(&key)
+        .cloned();
+    let found = match cached {
+        Some(found) => found,
+        None => {
+            let found = match lookup_editor_cmd(s) {
+                Ok(found) => Some(found),
+                Err(e) if e.kind() == ErrorKind::NotFound => None,
+                Err(e) => return Err(e),
+            };
+            LOOKUP_CACHE
+                .write()
+                .unwrap_or_else(|e| e.into_inner())
+                .insert(key, found.clone());
+            found
+        }
+    };
+    found.ok_or_else(|| Error::from(ErrorKind::NotFound))
+}
+
+fn lookup_editor_cmd(s: &OsStr) -> Result<(PathBuf, Vec<OsString>)> {
+    let (path, args) = parse_editor_command_os(s)?;
+    if let Some(result) = find_program(&path) {
+        return Ok((result, args));
+    }
+
+    // an unquoted path with spaces in it (e.g. C:\Program Files\...) is split into several
+    // words, so try putting them back together
+    if path.components().count() > 1 {
+        let mut joined = path.into_os_string();
+        for (i, arg) in args.iter().enumerate() {
+            joined.push(" ");
+            joined.push(arg);
+            if let Some(result) = find_program(Path::new(&joined)) {
+                return Ok((result, args[i + 1..].to_vec()));
+            }
+        }
+    }
+
+    Err(Error::from(ErrorKind::NotFound))
+}
+
+fn find_program(path: &Path) -> Option<PathBuf> {
+    match get_full_editor_path(path) {
+        Ok(result) => Some(result),
+        Err(_) if path.exists() => Some(path.to_owned()),
+        Err(_) => None,
+    }
+}
+
+/// Find the system default editor, if there is one.
+///
+/// This function checks several sources to find an editor binary (in order of precedence):
+///
+/// - the editor this is running inside of, if enabled with [`Editor::editor_servers`]
+/// - the `editor` set in the [user configuration file] (with the `config-file` feature)
+/// - editors from detectors registered with [`DetectorPriority::BeforeEnvVars`]
+/// - the editors listed in the `EDIT_EDITORS` environment variable (see [`EditorsVar`])
+/// - the `SUDO_EDITOR` environment variable, when running as root under `sudo` (see
+///   [`Editor::root_policy`])
+/// - the `VISUAL` environment variable
+/// - the `EDITOR` environment variable
+/// - the `GIT_EDITOR` environment variable, if enabled with [`Editor::tool_env_vars`] (which
+///   also consults `SUDO_EDITOR` before `VISUAL` when running under `sudo`)
+/// - editors added with [`register_candidate`]
+/// - the `fallbacks` set in the user configuration file
+/// - editors from detectors registered with [`register_detector`] (with the default
+///   [`DetectorPriority::BeforeBuiltin`])
+/// - the editor used the last time one had to be guessed, if enabled with
+///   [`Editor::remember_editor`]
+/// - hardcoded lists of common CLI editors (on Windows, these are only tried here in terminals
+///   like Windows Terminal, and after the GUI editors in the legacy console; see
+///   [`Editor::prefer`])
+/// - the user's default text editor: the `text/plain` handler in [`mimeapps.list`] on Linux, the
+///   default editor for plain text in LaunchServices on MacOS, or the program associated with
+///   `.txt` files in the registry on Windows
+/// - hardcoded lists of GUI editors on Windows/MacOS/Unix
+/// - well-known editors installed as Flatpak or Snap apps on Linux (e.g.
+///   `flatpak run org.gnome.gedit`)
+/// - Windows editors like `notepad.exe` inside WSL (the path of the file to edit is translated
+///   with `wslpath` whenever a Windows program is used)
+/// - platform-specific generic "file openers" (e.g. the desktop environment's own opener, such as
+///   `gio open` on GNOME or `kde-open` on KDE, followed by `xdg-open` on Linux, and `open` on
+///   MacOS), unless disabled with `generic-openers = false` in the user configuration file
+/// - editors from detectors registered with [`DetectorPriority::AfterBuiltin`]
+/// - editors added with [`register_fallback_candidate`]
+/// - hardcoded full paths of editors that are usually installed (e.g. `/usr/bin/vi` or
+///   `C:\Windows\System32\notepad.exe`), in case `PATH` is unset or broken
+///
+/// Also, it doesn't blindly return whatever is in an environment variable. If a specified editor
+/// can't be found or isn't marked as executable, this function will fall back to the next one
+/// that is.
+///
+/// Well-known GUI editors are skipped when there's no display to show them on or when running as
+/// root under `sudo`, and tried last in SSH sessions; see [`Editor::detect_headless`],
+/// [`Editor::root_policy`], and [`Editor::detect_ssh`].
+/// In CI and in containers without a terminal, only editors that were explicitly chosen (as
+/// opposed to the hardcoded lists, desktop defaults, and file openers) are used; see
+/// [`Editor::detect_unattended`].
+///
+/// Programs are looked up in `PATH`, and then in directories that package managers install
+/// programs to but that aren't always in `PATH`: `/opt/homebrew/bin`, `/usr/local/bin`, and
+/// `/opt/local/bin` on MacOS, and the directories of Scoop's and Chocolatey's shims and winget's
+/// links on Windows.
+///
+/// Which editors can be found is cached; see [`invalidate_editor_cache`].
+///
+/// Editor commands are split into a program and arguments with [`parse_editor_command`], so
+/// paths with spaces in them can be quoted. Unquoted paths with spaces (e.g.
+/// `C:\Program Files\Editor\edit.exe --wait`) are recognized too, as long as the program
+/// exists. GUI editors from environment variables that this crate knows need an argument to wait
+/// for the file to be closed (e.g. `EDITOR=code`) get it added; see [`Editor::add_wait_args`].
+///
+/// # Returns
+///
+/// If successful, returns the name of the system default editor.
+/// Note that in most cases the full path of the editor isn't returned; what is guaranteed is the
+/// return value being suitable as the program name for e.g. [`Command::new`].
+///
+/// On some platforms, a text editor is installed by default, so the chances of a failure are low
+/// save for `PATH` being unset or something weird like that. However, it is possible for one not
+/// to be located, and in that case `get_editor` will return [`ErrorKind::NotFound`], with a message
+/// that suggests how to get one (see [`install_hint`]).
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use edit::get_editor;
+///
+/// // will print e.g. "default editor: nano"
+/// println!("default editor:", get_editor().expect("can't find an editor").to_str());
+/// ```
+///
+/// [user configuration file]: fn.user_config_path.html
+/// [`EditorsVar`]: enum.EditorsVar.html
+/// [`Editor::tool_env_vars`]: struct.Editor.html#method.tool_env_vars
+/// [`Editor::editor_servers`]: struct.Editor.html#method.editor_servers
+/// [`register_detector`]: fn.register_detector.html
+/// [`Editor::prefer`]: struct.Editor.html#method.prefer
+/// [`DetectorPriority::BeforeEnvVars`]: enum.DetectorPriority.html#variant.BeforeEnvVars
+/// [`DetectorPriority::BeforeBuiltin`]: enum.DetectorPriority.html#variant.BeforeBuiltin
+/// [`DetectorPriority::AfterBuiltin`]: enum.DetectorPriority.html#variant.AfterBuiltin
+/// [`Editor::add_wait_args`]: struct.Editor.html#method.add_wait_args
+/// [`Editor::detect_headless`]: struct.Editor.html#method.detect_headless
+/// [`Editor::detect_ssh`]: struct.Editor.html#method.detect_ssh
+/// [`Editor::detect_unattended`]: struct.Editor.html#method.detect_unattended
+/// [`Editor::root_policy`]: struct.Editor.html#method.root_policy
+/// [`invalidate_editor_cache`]: fn.invalidate_editor_cache.html
+/// [`parse_editor_command`]: fn.parse_editor_command.html
+/// [`register_candidate`]: fn.register_candidate.html
+/// [`register_fallback_candidate`]: fn.register_fallback_candidate.html
+/// [`mimeapps.list`]: https://specifications.freedesktop.org/mime-apps-spec/latest/
+/// [`Command::new`]: https://doc.rust-lang.org/std/process/struct.Command.html#method.new
+/// [`ErrorKind::NotFound`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.NotFound
+pub fn get_editor() -> Result<PathBuf> {
+    Editor::new().get_editor()
+}
+
+/// Find the system default editor, like [`get_editor`], and describe it.
+///
+/// The returned [`EditorInfo`] tells whether the editor is a terminal or GUI program, whether it
+/// waits for the file to be closed, and its name, which is useful for e.g. telling users what
+/// they're waiting for.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let info = edit::get_editor_info()?;
+/// if info.is_gui() == Some(true) {
+///     println!("Waiting for {} to close the file...", info.name());
+/// }
+/// ```
+///
+/// [`get_editor`]: fn.get_editor.html
+/// [`EditorInfo`]: struct.EditorInfo.html
+pub fn get_editor_info() -> Result<EditorInfo> {
+    Editor::new().get_editor_info()
+}
+
+/// Find the system default editor, like [`get_editor`], and run it to find out its version.
+///
+/// This is for working around differences between versions of an editor. Only editors this crate
+/// knows how to ask for their version (e.g. with `--version`) are run; for others, and for
+/// editors whose output doesn't contain a version, `Ok(None)` is returned.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// if let Some(version) = edit::editor_version()? {
+///     println!("using {}", version.text());
+/// }
+/// ```
+///
+/// [`get_editor`]: fn.get_editor.html
+pub fn editor_version() -> Result<Option<EditorVersion>> {
+    Editor::new().editor_version()
+}
+
+/// List every editor that could be used, in order of precedence.
+///
+/// The same sources as in [`get_editor`] are checked, but instead of stopping at the first editor
+/// that can be found, all of them are returned (without duplicates). This is useful for e.g.
+/// letting users pick from the editors that are actually installed.
+///
+/// Each candidate is returned as a program and its arguments, like [`parse_editor_command`]. The
+/// first candidate (if there is one) is the editor [`get_editor`] would return.
+///
+/// [`get_editor`]: fn.get_editor.html
+/// [`parse_editor_command`]: fn.parse_editor_command.html
+pub fn editor_candidates() -> Vec<(PathBuf, Vec<String>)> {
+    Editor::new().editor_candidates()
+}
+
+/// Let the user choose an editor from a numbered menu on the terminal.
+///
+/// The menu lists the editors from [`editor_candidates`] (in order of precedence, so the default
+/// choice is the editor [`get_editor`] would return), and is shown on stderr. The chosen editor
+/// is returned as a command to pass to e.g. [`set_default_editor`] or [`Editor::editor`], or (with
+/// the `config-file` feature) to [`save_user_editor`] so it's used in future runs too.
+///
+/// If only one editor can be found, or stdin or stdout isn't a terminal, the first editor is
+/// returned without asking. If none can be found, [`ErrorKind::NotFound`] is returned.
+///
+/// This function is only available with the `picker` feature.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// if edit::user_config_path().is_some_and(|path| !path.exists()) {
+///     let command = edit::choose_editor()?;
+///     edit::save_user_editor(&command)?;
+///     edit::set_default_editor(command);
+/// }
+/// ```
+///
+/// [`editor_candidates`]: fn.editor_candidates.html
+/// [`get_editor`]: fn.get_editor.html
+/// [`set_default_editor`]: fn.set_default_editor.html
+/// [`Editor::editor`]: struct.Editor.html#method.editor
+/// [`save_user_editor`]: fn.save_user_editor.html
+/// [`ErrorKind::NotFound`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.NotFound
+#[cfg(feature = "picker")]
+pub fn choose_editor() -> Result<String> {
+    Editor::new().choose_editor()
+}
+
+/// Build a [`Command`] that runs the system default editor.
+///
+/// The editor is found the same way as in [`get_editor`], but unlike `get_editor`, any arguments
+/// given along with the editor (e.g. the `-w` in `EDITOR="code -w"`) are kept. The returned
+/// `Command` has the editor program and those arguments set and nothing else; add the path of
+/// the file to edit (plus any other arguments, environment variables, or stdio configuration)
+/// before spawning it.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let status = edit::get_editor_command()?.arg("notes.txt").status()?;
+/// ```
+///
+/// [`Command`]: https://doc.rust-lang.org/std/process/struct.Command.html
+/// [`get_editor`]: fn.get_editor.html
+pub fn get_editor_command() -> Result<Command> {
+    Editor::new().get_editor_command()
+}
+
+/// Open the contents of a string or buffer in the [default editor].
+///
+/// This function saves its input to a temporary file and then opens the default editor to it.
+/// It waits for the editor to return, re-reads the (possibly changed/edited) temporary file, and
+/// then deletes it.
+///
+/// If the `EDIT_NONINTERACTIVE_CONTENT` environment variable is set (e.g. in tests), no editor is
+/// opened, and its value is returned as if the user had typed it in; see
+/// [`Editor::noninteractive_content`].
+///
+/// # Arguments
+///
+/// `text` is written to the temporary file before invoking the editor. (The editor opens with
+/// the contents of `text` already in the file).
+///
+/// # Returns
+///
+/// If successful, returns the edited string.
+/// The edited version of the file is decoded as UTF-8 (without a byte order mark, if the editor
+/// added one), or as UTF-16 if it starts with a UTF-16 byte order mark, as some Windows editors
+/// save files that way. If it can't be decoded, returns [`ErrorKind::InvalidData`].
 /// If no text editor could be found, returns [`ErrorKind::NotFound`].
 /// Any errors related to spawning the editor process will also be passed through.
 ///
 /// [default editor]: fn.get_editor.html
+/// [`Editor::noninteractive_content`]: struct.Editor.html#method.noninteractive_content
 /// [`ErrorKind::InvalidData`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidData
 /// [`ErrorKind::NotFound`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.NotFound
 pub fn edit<S: AsRef<[u8]>>(text: S) -> Result<String> {
-    let builder = Builder::new();
-    edit_with_builder(text, &builder)
+    Editor::new().edit(text)
+}
+
+/// Open the contents of a string or buffer in a specific editor instead of the [default editor].
+///
+/// `command` is split into a program and arguments the same way as the `VISUAL` and `EDITOR`
+/// environment variables are, so it can include flags (e.g. `"code -w"`). No other editors are
+/// tried; if the program in `command` can't be found, returns [`ErrorKind::NotFound`].
+///
+/// Other than the editor used, this function is identical to [`edit`].
+///
+/// [default editor]: fn.get_editor.html
+/// [`edit`]: fn.edit.html
+/// [`ErrorKind::NotFound`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.NotFound
+pub fn edit_with_command<S: AsRef<[u8]>>(text: S, command: &str) -> Result<String> {
+    Editor::new().editor(command).edit(text)
+}
+
+/// Open the contents of a string or buffer in the [default editor] using a temporary file whose
+/// name ends with `suffix`.
+///
+/// Editors often use the file extension to pick a syntax highlighting mode, so e.g. a suffix of
+/// `".md"` opens the text as Markdown. Other than the name of the temporary file, this function
+/// is identical to [`edit`].
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let notes = edit::edit_with_suffix("# Release notes\n", ".md")?;
+/// ```
+///
+/// [default editor]: fn.get_editor.html
+/// [`edit`]: fn.edit.html
+pub fn edit_with_suffix<S: AsRef<[u8]>, T: AsRef<OsStr>>(text: S, suffix: T) -> Result<String> {
+    Editor::new().suffix(suffix).edit(text)
+}
+
+/// Open the contents of a string or buffer in the [default editor] using a temporary file named
+/// after `name`.
+///
+/// The temporary file has the same extension as `name` and a name that starts with the rest of
+/// it, so e.g. a `name` of `"notes.md"` makes for a file like `notes-a1B2c3.md`, which editors
+/// show in their title bar and use to pick a syntax highlighting mode. Other than the name of the
+/// temporary file, this function is identical to [`edit`]. See [`Editor::file_name`].
+///
+/// [default editor]: fn.get_editor.html
+/// [`edit`]: fn.edit.html
+/// [`Editor::file_name`]: struct.Editor.html#method.file_name
+pub fn edit_with_name<S: AsRef<[u8]>, T: AsRef<Path>>(text: S, name: T) -> Result<String> {
+    Editor::new().file_name(name).edit(text)
+}
+
+/// Open the contents of a string or buffer in the [default editor] using a temporary file created
+/// in `dir`.
+///
+/// Creating the file next to related files (e.g. inside a project) lets editors resolve relative
+/// paths in the text, and load per-directory settings like `.editorconfig`. Other than where the
+/// temporary file is created, this function is identical to [`edit`]. See [`Editor::temp_dir`].
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let readme = edit::edit_in_dir("See [the changelog](CHANGELOG.md).\n", "docs")?;
+/// ```
+///
+/// [default editor]: fn.get_editor.html
+/// [`edit`]: fn.edit.html
+/// [`Editor::temp_dir`]: struct.Editor.html#method.temp_dir
+pub fn edit_in_dir<S: AsRef<[u8]>, P: AsRef<Path>>(text: S, dir: P) -> Result<String> {
+    Editor::new().temp_dir(dir).edit(text)
 }
 
 /// Open the contents of a string or buffer in the [default editor] using a temporary file with a
 /// custom path or filename.
 ///
-/// This function saves its input to a temporary file created using `builder`, then opens the
-/// default editor to it. It waits for the editor to return, re-reads the (possibly changed/edited)
-/// temporary file, and then deletes it.
+/// This function saves its input to a temporary file created with the [`TempOptions`] in
+/// `builder`, then opens the default editor to it. It waits for the editor to return, re-reads
+/// the (possibly changed/edited) temporary file, and then deletes it.
 ///
-/// Other than the custom [`Builder`], this function is identical to [`edit`].
+/// Other than the custom [`TempOptions`], this function is identical to [`edit`].
 ///
 /// # Arguments
 ///
-/// `builder` is used to create a temporary file, potentially with a custom name, path, or prefix.
+/// `builder` is used to create a temporary file, potentially with a custom name, directory, or
+/// permissions.
 ///
 /// `text` is written to the temporary file before invoking the editor. (The editor opens with
 /// the contents of `text` already in the file).
@@ -236,126 +1435,2965 @@ pub fn edit<S: AsRef<[u8]>>(text: S) -> Result<String> {
 /// # Returns
 ///
 /// If successful, returns the edited string.
-/// If the temporary file can't be created with the provided builder, may return any error returned
+/// If the temporary file can't be created with the provided options, may return any error returned
 /// by [`OpenOptions::open`].
-/// If the edited version of the file can't be decoded as UTF-8, returns [`ErrorKind::InvalidData`].
+/// If the edited version of the file can't be decoded (as described in [`edit`]), returns
+/// [`ErrorKind::InvalidData`].
 /// If no text editor could be found, returns [`ErrorKind::NotFound`].
 /// Any errors related to spawning the editor process will also be passed through.
 ///
 /// [default editor]: fn.get_editor.html
 /// [`edit`]: fn.edit.html
-/// [`Builder`]: struct.Builder.html
+/// [`TempOptions`]: struct.TempOptions.html
 /// [`OpenOptions::open`]: https://doc.rust-lang.org/std/fs/struct.OpenOptions.html#errors
 /// [`ErrorKind::InvalidData`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidData
 /// [`ErrorKind::NotFound`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.NotFound
-pub fn edit_with_builder<S: AsRef<[u8]>>(text: S, builder: &Builder) -> Result<String> {
-    String::from_utf8(edit_bytes_with_builder(text, builder)?)
-        .map_err(|_| Error::from(ErrorKind::InvalidData))
+pub fn edit_with_builder<S: AsRef<[u8]>>(text: S, builder: &TempOptions) -> Result<String> {
+    Editor::new().edit_with_builder(text, builder)
 }
 
-/// Open the contents of a string or buffer in the [default editor] and return them as raw bytes.
+/// Open the contents of a string or buffer in the [default editor], and return the edited text
+/// unless the user aborted.
 ///
-/// See [`edit`], the version of this function that takes and returns [`String`].
+/// Like `git commit`, this treats leaving the text unchanged or emptying it (or leaving nothing
+/// but whitespace) as aborting, and returns `None` in that case. Combined with
+/// [`Editor::strip_comments`], a template consisting only of comments also counts as empty, so
+/// the user can abort by saving it as-is:
 ///
-/// # Arguments
+/// ```rust,ignore
+/// let message = edit::Editor::new()
+///     .strip_comments(true)
+///     .edit_optional("\n# Enter a message. An empty message aborts.\n")?;
+/// match message {
+///     Some(message) => println!("got '{}'", message),
+///     None => println!("aborted"),
+/// }
+/// ```
 ///
-/// `buf` is written to the temporary file before invoking the editor.
+/// Other than that, this function is identical to [`edit`].
 ///
-/// # Returns
+/// [default editor]: fn.get_editor.html
+/// [`edit`]: fn.edit.html
+/// [`Editor::strip_comments`]: struct.Editor.html#method.strip_comments
+pub fn edit_optional<S: AsRef<[u8]>>(text: S) -> Result<Option<String>> {
+    Editor::new().edit_optional(text)
+}
+
+/// Open a string in the [default editor], and return the edited text, or the string itself if the
+/// user didn't change it.
 ///
-/// If successful, returns the contents of the temporary file in raw (`Vec<u8>`) form.
+/// When the user saves the text unchanged (or just quits), the returned [`Cow`] borrows `text`
+/// instead of holding a copy of it, which avoids allocating for large templates that are usually
+/// left alone, and makes it cheap to tell whether anything changed:
+///
+/// ```rust,ignore
+/// let config = edit::edit_cow(&template)?;
+/// if let Cow::Owned(config) = config {
+///     save(&config)?;
+/// }
+/// ```
+///
+/// Text that only becomes identical to `text` after cleanup (like [`Editor::strip_comments`]) is
+/// also returned borrowed, though in that case the edited copy was already read in.
+///
+/// Other than that, this function is identical to [`edit`].
 ///
 /// [default editor]: fn.get_editor.html
+/// [`Cow`]: https://doc.rust-lang.org/std/borrow/enum.Cow.html
+/// [`Editor::strip_comments`]: struct.Editor.html#method.strip_comments
 /// [`edit`]: fn.edit.html
-/// [`String`]: https://doc.rust-lang.org/std/string/struct.String.html
-pub fn edit_bytes<B: AsRef<[u8]>>(buf: B) -> Result<Vec<u8>> {
-    let builder = Builder::new();
-    edit_bytes_with_builder(buf, &builder)
+pub fn edit_cow(text: &str) -> Result<Cow<'_, str>> {
+    Editor::new().edit_cow(text)
 }
 
-/// Open the contents of a string or buffer in the [default editor] using a temporary file with a
-/// custom path or filename and return them as raw bytes.
+/// Open the contents of a string or buffer in the [default editor], and return the edited text
+/// along with details about the edit session.
 ///
-/// See [`edit_with_builder`], the version of this function that takes and returns [`String`].
+/// The returned [`EditOutcome`] has whether the text was changed, the command the editor was run
+/// with, its exit status, and how long it was open for, which is useful for e.g. logging:
 ///
-/// Other than the custom [`Builder`], this function is identical to [`edit_bytes`].
+/// ```rust,ignore
+/// let outcome = edit::edit_with_outcome("# Notes\n")?;
+/// if let Some(command) = outcome.command() {
+///     log::info!("'{}' was open for {:?}", command, outcome.duration());
+/// }
+/// if outcome.changed() {
+///     save(outcome.content());
+/// }
+/// ```
 ///
-/// # Arguments
+/// Other than that, this function is identical to [`edit`].
 ///
-/// `builder` is used to create a temporary file, potentially with a custom name, path, or prefix.
+/// [default editor]: fn.get_editor.html
+/// [`EditOutcome`]: struct.EditOutcome.html
+/// [`edit`]: fn.edit.html
+pub fn edit_with_outcome<S: AsRef<[u8]>>(text: S) -> Result<EditOutcome> {
+    Editor::new().edit_with_outcome(text)
+}
+
+/// Open the contents of a string or buffer in the [default editor], and parse the edited text
+/// with `parse`.
 ///
-/// `buf` is written to the temporary file before invoking the editor.
+/// If `parse` fails, the error is returned as an [`EditParseError`] (inside an [`io::Error`]) with
+/// the edited text, so the user's changes aren't lost. This works with any format (and is what
+/// [`edit_toml`], [`edit_json`], and [`edit_yaml`] are built on), e.g. RON with
+/// [`ron`](https://docs.rs/ron):
 ///
-/// # Returns
+/// ```rust,ignore
+/// let text = ron::ser::to_string_pretty(&settings, Default::default())?;
+/// let settings: Settings = edit::Editor::new()
+///     .suffix(".ron")
+///     .edit_with_parser(text, |text| ron::from_str(text))?;
+/// ```
 ///
-/// If successful, returns the contents of the temporary file in raw (`Vec<u8>`) form.
+/// Other than that, this function is identical to [`edit`].
 ///
 /// [default editor]: fn.get_editor.html
-/// [`edit_with_builder`]: fn.edit_with_builder.html
-/// [`String`]: https://doc.rust-lang.org/std/string/struct.String.html
-/// [`Builder`]: struct.Builder.html
-/// [`edit_bytes`]: fn.edit_bytes.html
-pub fn edit_bytes_with_builder<B: AsRef<[u8]>>(buf: B, builder: &Builder) -> Result<Vec<u8>> {
-    let mut file = builder.tempfile()?;
-    file.write_all(buf.as_ref())?;
-
-    let path = file.into_temp_path();
-    edit_file(&path)?;
-
-    let edited = fs::read(&path)?;
-
-    path.close()?;
-    Ok(edited)
+/// [`EditParseError`]: struct.EditParseError.html
+/// [`io::Error`]: https://doc.rust-lang.org/std/io/struct.Error.html
+/// [`edit_toml`]: fn.edit_toml.html
+/// [`edit_json`]: fn.edit_json.html
+/// [`edit_yaml`]: fn.edit_yaml.html
+/// [`edit`]: fn.edit.html
+pub fn edit_with_parser<S, T, E, F>(text: S, parse: F) -> Result<T>
+where
+    S: AsRef<[u8]>,
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+    F: FnOnce(&str) -> std::result::Result<T, E>,
+{
+    Editor::new().edit_with_parser(text, parse)
 }
 
-/// Open an existing file (or create a new one, depending on the editor's behavior) in the
-/// [default editor] and wait for the editor to exit.
+/// Open the contents of a string or buffer in the [default editor] until the edited text passes
+/// `validate`, and return what it returns, or `None` if the user aborted.
 ///
-/// # Arguments
+/// Like `kubectl edit`, when `validate` returns an error, the editor is opened again with the
+/// text as the user left it and the error as comment lines at the top, which are removed again
+/// before the text is validated (as long as the user leaves them alone):
 ///
-/// A [`Path`] to a file, new or existing, to open in the default editor.
+/// ```text
+/// # Please fix the error below and save, or save the file unchanged to cancel:
+/// # retries must be a number
+/// #
+/// name = "web"
+/// retries = many
+/// ```
 ///
-/// # Returns
+/// The comments start with the [comment prefix] (`#` by default). The user can abort by saving
+/// the text unchanged (including the very first time) or emptying it, as with [`edit_optional`]:
 ///
-/// A Result is returned in case of errors finding or spawning the editor, but the contents of the
-/// file are not read and returned as in [`edit`] and [`edit_bytes`].
+/// ```rust,ignore
+/// let port = edit::edit_validated("port = 8080\n", |text| {
+///     let value = text.trim().strip_prefix("port = ").ok_or("expected `port = <number>`")?;
+///     value.parse::<u16>().map_err(|e| format!("invalid port: {}", e))
+/// })?;
+/// ```
+///
+/// Other than that, this function is identical to [`edit`].
 ///
 /// [default editor]: fn.get_editor.html
-/// [`Path`]: https://doc.rust-lang.org/std/path/struct.Path.html
+/// [comment prefix]: struct.Editor.html#method.comment_prefix
+/// [`edit_optional`]: fn.edit_optional.html
 /// [`edit`]: fn.edit.html
-/// [`edit_bytes`]: fn.edit_bytes.html
-pub fn edit_file<P: AsRef<Path>>(file: P) -> Result<()> {
-    let (editor, args) = get_editor_args()?;
-    let status = Command::new(&editor)
-        .args(&args)
-        .arg(file.as_ref())
-        .stdin(Stdio::inherit())
-        .stdout(Stdio::inherit())
-        .stderr(Stdio::inherit())
-        .output()?
-        .status;
-
-    if status.success() {
-        Ok(())
-    } else {
-        let full_command = if args.is_empty() {
-            format!(
-                "{} {}",
-                editor.to_string_lossy(),
-                file.as_ref().to_string_lossy()
-            )
-        } else {
-            format!(
-                "{} {} {}",
-                editor.to_string_lossy(),
-                args.join(" "),
-                file.as_ref().to_string_lossy()
-            )
-        };
+pub fn edit_validated<S, T, E, F>(text: S, validate: F) -> Result<Option<T>>
+where
+    S: AsRef<[u8]>,
+    E: std::fmt::Display,
+    F: FnMut(&str) -> std::result::Result<T, E>,
+{
+    Editor::new().edit_validated(text, validate)
+}
 
-        Err(Error::new(
-            ErrorKind::Other,
-            format!("editor '{}' exited with error: {}", full_command, status),
-        ))
+/// Open a template in the [default editor] until the edited text parses as a `T` with
+/// [`FromStr`], and return the parsed value, or `None` if the user aborted.
+///
+/// This is handy for quick interactive input of simple values like numbers, durations, or cron
+/// expressions. Whitespace around the edited text is ignored, and when it doesn't parse, the
+/// editor is opened again with the parse error at the top, as with [`edit_validated`]. Enable
+/// [`Editor::strip_comments`] to explain the value in comments:
+///
+/// ```rust,ignore
+/// let retries: u32 = edit::Editor::new()
+///     .strip_comments(true)
+///     .edit_parse("# How many times to retry failed requests:\n3\n")?
+///     .unwrap_or(3);
+/// ```
+///
+/// As with [`edit_validated`], saving the template unchanged or emptying it counts as aborting.
+///
+/// [default editor]: fn.get_editor.html
+/// [`FromStr`]: https://doc.rust-lang.org/std/str/trait.FromStr.html
+/// [`edit_validated`]: fn.edit_validated.html
+/// [`Editor::strip_comments`]: struct.Editor.html#method.strip_comments
+pub fn edit_parse<T, S>(template: S) -> Result<Option<T>>
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+    S: AsRef<[u8]>,
+{
+    Editor::new().edit_parse(template)
+}
+
+/// Open a [`Form`] in the [default editor] for the user to fill in, and return the value of every
+/// field by name, or `None` if the user aborted.
+///
+/// The fields are rendered as `name: value` lines, each with its help text in comments above it:
+///
+/// ```rust,ignore
+/// let mut form = edit::Form::new();
+/// form.field("name", "", "The name of the project.")
+///     .field("license", "MIT", "An SPDX license expression.")
+///     .validator("name", |name| match name {
+///         "" => Err("can't be empty".to_owned()),
+///         _ => Ok(()),
+///     });
+/// if let Some(values) = edit::edit_form(&form)? {
+///     println!("creating {}", values["name"]);
+/// }
+/// ```
+///
+/// opens
+///
+/// ```text
+/// # The name of the project.
+/// name:
+///
+/// # An SPDX license expression.
+/// license: MIT
+/// ```
+///
+/// Comments, blank lines, and whitespace around names and values are ignored. If a field is
+/// missing, given more than once, or fails its validator, or there are lines that aren't fields,
+/// the editor is opened again with every problem at the top, as with [`edit_validated`]. Saving
+/// the form unchanged or emptying it counts as aborting.
+///
+/// [`Form`]: struct.Form.html
+/// [default editor]: fn.get_editor.html
+/// [`edit_validated`]: fn.edit_validated.html
+pub fn edit_form(form: &Form) -> Result<Option<BTreeMap<String, String>>> {
+    Editor::new().edit_form(form)
+}
+
+/// Open a list of items in the [default editor] for the user to reorder, remove, or change the
+/// action of, like `git rebase -i` does with commits, and return the items in their new order.
+///
+/// Every item is put on its own line, numbered from 1, and after `action` if it isn't `None`:
+///
+/// ```text
+/// pick 1 Add a README
+/// pick 2 Fix typo
+/// pick 3 Release 1.0
+///
+/// # Lines can be reordered to change the order of the items, or removed to drop them.
+/// # ...
+/// ```
+///
+/// Each returned [`ListEntry`] has the index of the item in `items`, the word before its number
+/// (if any, and whatever the user changed it to), and the text after its number, so the user can
+/// also annotate items. It's up to the caller to decide which actions are valid:
+///
+/// ```rust,ignore
+/// let commits = ["Add a README", "Fix typo", "Release 1.0"];
+/// if let Some(entries) = edit::edit_list(&commits, Some("pick"))? {
+///     for entry in entries {
+///         match entry.action() {
+///             Some("pick") | Some("p") => pick(entry.index()),
+///             Some("drop") | Some("d") => {}
+///             _ => return Err(format!("unknown action for '{}'", entry.text()).into()),
+///         }
+///     }
+/// }
+/// ```
+///
+/// Items can be listed more than once. Lines starting with the [comment prefix] and blank lines
+/// are ignored, and if a line doesn't have the number of an item, the editor is opened again with
+/// the problem at the top, as with [`edit_validated`]. Saving the list unchanged returns every
+/// item as it was, and removing every item aborts, returning `None`. Items have to fit on one
+/// line, so if any of them contains a line break, [`ErrorKind::InvalidInput`] is returned before
+/// the editor is opened.
+///
+/// [default editor]: fn.get_editor.html
+/// [`ListEntry`]: struct.ListEntry.html
+/// [comment prefix]: struct.Editor.html#method.comment_prefix
+/// [`edit_validated`]: fn.edit_validated.html
+/// [`ErrorKind::InvalidInput`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidInput
+pub fn edit_list<S: AsRef<str>>(
+    items: &[S],
+    action: Option<&str>,
+) -> Result<Option<Vec<ListEntry>>> {
+    Editor::new().edit_list(items, action)
+}
+
+/// Open several documents in the [default editor] at once, and return each of them as edited.
+///
+/// The documents are put in a single file, each after a separator line, so the user can edit
+/// related snippets side by side in one editor session:
+///
+/// ```text
+/// ==================== document 1 of 2 ====================
+/// [server]
+/// port = 8080
+/// ==================== document 2 of 2 ====================
+/// [client]
+/// retries = 3
+/// ```
+///
+/// A line ending is added to documents that don't end with one, so the next separator is on its
+/// own line. The edited file is split back into documents at the separators, which have to be
+/// left as they are; if one was changed or removed, an [`EditParseError`] (inside an
+/// [`io::Error`]) is returned with the whole edited text, so the user's changes aren't lost.
+/// Every document is then cleaned up on its own, as with [`edit`]. Since a document couldn't be
+/// split back out if it had a line of its own that looked like a separator, any document with a
+/// line starting with `==================== document` is rejected with
+/// [`ErrorKind::InvalidInput`] before the editor is opened.
+///
+/// ```rust,ignore
+/// let edited = edit::edit_documents(&[server_config, client_config])?;
+/// fs::write("server.toml", &edited[0])?;
+/// fs::write("client.toml", &edited[1])?;
+/// ```
+///
+/// [default editor]: fn.get_editor.html
+/// [`EditParseError`]: struct.EditParseError.html
+/// [`io::Error`]: https://doc.rust-lang.org/std/io/struct.Error.html
+/// [`edit`]: fn.edit.html
+/// [`ErrorKind::InvalidInput`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidInput
+pub fn edit_documents<S: AsRef<[u8]>>(documents: &[S]) -> Result<Vec<String>> {
+    Editor::new().edit_documents(documents)
+}
+
+/// Open a value in the [default editor] as a TOML document, and deserialize the edited document
+/// back into a value, like `kubectl edit` does with resources.
+///
+/// The value is serialized with [`toml`](https://docs.rs/toml) into a temporary file ending in
+/// `.toml` (for syntax highlighting), unless another suffix was set with [`Editor::suffix`]. If the
+/// edited document can't be deserialized, the error is returned as an [`EditParseError`] (inside
+/// an [`io::Error`]) with the edited text, so it can be fixed instead of starting over:
+///
+/// ```rust,ignore
+/// #[derive(serde::Serialize, serde::Deserialize)]
+/// struct Settings {
+///     name: String,
+///     retries: u32,
+/// }
+///
+/// let settings: Settings = edit::edit_toml(&settings)?;
+/// ```
+///
+/// This function is only available with the `structured` feature.
+///
+/// [default editor]: fn.get_editor.html
+/// [`Editor::suffix`]: struct.Editor.html#method.suffix
+/// [`EditParseError`]: struct.EditParseError.html
+/// [`io::Error`]: https://doc.rust-lang.org/std/io/struct.Error.html
+#[cfg(feature = "structured")]
+pub fn edit_toml<T: serde::Serialize + serde::de::DeserializeOwned>(value: &T) -> Result<T> {
+    Editor::new().edit_toml(value)
+}
+
+/// Open a value in the [default editor] as a JSON document, and deserialize the edited document
+/// back into a value.
+///
+/// The value is serialized with [`serde_json`](https://docs.rs/serde_json) into a temporary file
+/// ending in `.json`, unless another suffix was set with [`Editor::suffix`]. Other than that,
+/// this function is identical to [`edit_toml`], including returning an [`EditParseError`] with
+/// the edited text if it can't be deserialized:
+///
+/// ```rust,ignore
+/// let manifest: Manifest = edit::edit_json(&manifest)?;
+/// ```
+///
+/// This function is only available with the `json` feature.
+///
+/// [default editor]: fn.get_editor.html
+/// [`Editor::suffix`]: struct.Editor.html#method.suffix
+/// [`edit_toml`]: fn.edit_toml.html
+/// [`EditParseError`]: struct.EditParseError.html
+#[cfg(feature = "json")]
+pub fn edit_json<T: serde::Serialize + serde::de::DeserializeOwned>(value: &T) -> Result<T> {
+    Editor::new().edit_json(value)
+}
+
+/// Open a value in the [default editor] as a YAML document, and deserialize the edited document
+/// back into a value, like `kubectl edit` does with resources.
+///
+/// The value is serialized with [`serde_yaml`](https://docs.rs/serde_yaml) into a temporary file
+/// ending in `.yaml`, unless another suffix was set with [`Editor::suffix`]. Other than that,
+/// this function is identical to [`edit_toml`], including returning an [`EditParseError`] with
+/// the edited text if it can't be deserialized:
+///
+/// ```rust,ignore
+/// let deployment: Deployment = edit::edit_yaml(&deployment)?;
+/// ```
+///
+/// This function is only available with the `yaml` feature.
+///
+/// [default editor]: fn.get_editor.html
+/// [`Editor::suffix`]: struct.Editor.html#method.suffix
+/// [`edit_toml`]: fn.edit_toml.html
+/// [`EditParseError`]: struct.EditParseError.html
+#[cfg(feature = "yaml")]
+pub fn edit_yaml<T: serde::Serialize + serde::de::DeserializeOwned>(value: &T) -> Result<T> {
+    Editor::new().edit_yaml(value)
+}
+
+/// Open the contents of a string or buffer in the [default editor], and return the edited text
+/// along with a line-level diff against the original, so e.g. review tools can show exactly what
+/// the user changed.
+///
+/// Lines are compared without their line endings, and after any cleanup of the edited text (like
+/// [`Editor::strip_comments`]), so removed comments show up as removed lines:
+///
+/// ```rust,ignore
+/// let diff = edit::edit_diff("name = \"old\"\n")?;
+/// for line in diff.lines() {
+///     if let edit::DiffLine::Added(line) = line {
+///         println!("added: {}", line);
+///     }
+/// }
+/// print!("{}", diff); // in unified diff format
+/// ```
+///
+/// Other than that, this function is identical to [`edit`].
+///
+/// [default editor]: fn.get_editor.html
+/// [`Editor::strip_comments`]: struct.Editor.html#method.strip_comments
+/// [`edit`]: fn.edit.html
+pub fn edit_diff<S: AsRef<[u8]>>(text: S) -> Result<EditDiff> {
+    Editor::new().edit_diff(text)
+}
+
+/// Open the contents of a string in the [default editor] as text in a legacy `encoding`, like
+/// Shift_JIS or Windows-1252, for editors or locales that don't use UTF-8.
+///
+/// `text` (which has to be valid UTF-8) is encoded with `encoding` before it's written to the
+/// temporary file, and the edited file is decoded with it again, unless it starts with a byte
+/// order mark, which takes precedence. If `text` has characters `encoding` can't represent,
+/// returns [`ErrorKind::InvalidInput`], and if the edited file isn't valid in the encoding,
+/// returns [`ErrorKind::InvalidData`], rather than silently replacing characters.
+///
+/// ```rust,ignore
+/// let memo = edit::edit_with_encoding("メモ\n", encoding_rs::SHIFT_JIS)?;
+/// ```
+///
+/// Encodings are used as [`encoding_rs`] uses them for encoding, so e.g. UTF-16 text is written
+/// as UTF-8. Other than that, this function is identical to [`edit`]. It's only available with
+/// the `encoding_rs` feature.
+///
+/// [default editor]: fn.get_editor.html
+/// [`ErrorKind::InvalidInput`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidInput
+/// [`ErrorKind::InvalidData`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidData
+/// [`encoding_rs`]: https://docs.rs/encoding_rs
+/// [`edit`]: fn.edit.html
+#[cfg(feature = "encoding_rs")]
+pub fn edit_with_encoding<S: AsRef<[u8]>>(
+    text: S,
+    encoding: &'static encoding_rs::Encoding,
+) -> Result<String> {
+    Editor::new().encoding(encoding).edit(text)
+}
+
+/// Open the contents of a string or buffer in the [default editor] and return them as raw bytes.
+///
+/// See [`edit`], the version of this function that takes and returns [`String`].
+///
+/// # Arguments
+///
+/// `buf` is written to the temporary file before invoking the editor.
+///
+/// # Returns
+///
+/// If successful, returns the contents of the temporary file in raw (`Vec<u8>`) form.
+///
+/// # Other encodings
+///
+/// This crate only decodes UTF-8 and UTF-16 text itself. For users whose editor or locale uses a
+/// legacy encoding like Shift_JIS or Windows-1252, use [`edit_with_encoding`] (with the
+/// `encoding_rs` feature) instead of encoding and decoding the bytes by hand.
+///
+/// [default editor]: fn.get_editor.html
+/// [`edit`]: fn.edit.html
+/// [`String`]: https://doc.rust-lang.org/std/string/struct.String.html
+/// [`edit_with_encoding`]: fn.edit_with_encoding.html
+pub fn edit_bytes<B: AsRef<[u8]>>(buf: B) -> Result<Vec<u8>> {
+    Editor::new().edit_bytes(buf)
+}
+
+/// Open the contents of a string or buffer in the [default editor] using a temporary file with a
+/// custom path or filename and return them as raw bytes.
+///
+/// See [`edit_with_builder`], the version of this function that takes and returns [`String`].
+///
+/// Other than the custom [`TempOptions`], this function is identical to [`edit_bytes`].
+///
+/// # Arguments
+///
+/// `builder` is used to create a temporary file, potentially with a custom name, directory, or
+/// permissions.
+///
+/// `buf` is written to the temporary file before invoking the editor.
+///
+/// # Returns
+///
+/// If successful, returns the contents of the temporary file in raw (`Vec<u8>`) form.
+///
+/// [default editor]: fn.get_editor.html
+/// [`edit_with_builder`]: fn.edit_with_builder.html
+/// [`String`]: https://doc.rust-lang.org/std/string/struct.String.html
+/// [`TempOptions`]: struct.TempOptions.html
+/// [`edit_bytes`]: fn.edit_bytes.html
+pub fn edit_bytes_with_builder<B: AsRef<[u8]>>(buf: B, builder: &TempOptions) -> Result<Vec<u8>> {
+    Editor::new().edit_bytes_with_builder(buf, builder)
+}
+
+/// Open an existing file (or create a new one, depending on the editor's behavior) in the
+/// [default editor] and wait for the editor to exit.
+///
+/// # Arguments
+///
+/// A [`Path`] to a file, new or existing, to open in the default editor.
+///
+/// # Returns
+///
+/// A Result is returned in case of errors finding or spawning the editor, but the contents of the
+/// file are not read and returned as in [`edit`] and [`edit_bytes`].
+///
+/// [default editor]: fn.get_editor.html
+/// [`Path`]: https://doc.rust-lang.org/std/path/struct.Path.html
+/// [`edit`]: fn.edit.html
+/// [`edit_bytes`]: fn.edit_bytes.html
+pub fn edit_file<P: AsRef<Path>>(file: P) -> Result<()> {
+    Editor::new().edit_file(file)
+}
+
+/// Open several existing files (or create new ones, depending on the editor's behavior) in one
+/// invocation of the [default editor] and wait for the editor to exit.
+///
+/// This is like [`edit_file`], but lets the user review and edit related files together, e.g. a
+/// changelog and the file with the version number in it, instead of one after another. Most
+/// editors open the files in tabs or buffers of one window; the first file is the one shown
+/// first, and the only one opened at the line set with [`Editor::line`] or watched for saves with
+/// [`WaitFor::Save`]. Through the desktop portal, which can only open one file at a time, the
+/// files are opened one after another. If `files` is empty, nothing is opened.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// edit::edit_files(&["CHANGELOG.md", "Cargo.toml"])?;
+/// ```
+///
+/// [default editor]: fn.get_editor.html
+/// [`edit_file`]: fn.edit_file.html
+/// [`Editor::line`]: struct.Editor.html#method.line
+/// [`WaitFor::Save`]: enum.WaitFor.html#variant.Save
+pub fn edit_files<P: AsRef<Path>>(files: &[P]) -> Result<()> {
+    Editor::new().edit_files(files)
+}
+
+/// Open a copy of a file in the [default editor], and replace the file with it once the editor
+/// exits successfully.
+///
+/// Editors write files in place, so if the editor or the machine dies while saving, the file can
+/// be left half-written. This function instead copies the file to a temporary file next to it, and
+/// only once the editor has exited successfully, atomically renames the copy over the original.
+/// If editing fails, or the copy wasn't changed, the original is left untouched. If the original
+/// is a symlink, the file it points to is replaced.
+///
+/// If the file is changed by another program while the copy is being edited, it isn't replaced,
+/// and a [`Conflict`] error with both versions is returned instead. (Use [`Editor::lock`] to keep
+/// other programs using this crate from editing the file at the same time in the first place.)
+///
+/// The copy is given the permissions of the original, and as far as the current user is allowed
+/// to, its owner and group and its extended attributes (including ACLs on Linux), so editing e.g.
+/// files in `/etc` as root doesn't change who can access them.
+///
+/// The file doesn't have to exist; it is created if the editor saves any text to the copy. Other
+/// than how the file is written, this function is identical to [`edit_file`].
+///
+/// [default editor]: fn.get_editor.html
+/// [`edit_file`]: fn.edit_file.html
+/// [`Conflict`]: struct.Conflict.html
+/// [`Editor::lock`]: struct.Editor.html#method.lock
+pub fn edit_file_atomic<P: AsRef<Path>>(file: P) -> Result<()> {
+    Editor::new().edit_file_atomic(file)
+}
+
+/// Open a file the current user may not be able to write to, like a system configuration file,
+/// in the [default editor], without running the editor as root.
+///
+/// If the file can be written to (e.g. because this is already running as root), this is the same
+/// as [`edit_file`]. Otherwise, like `sudoedit`, the file is copied to a temporary file owned by
+/// the current user, the editor is run on the copy as the current user, and if the copy was
+/// changed, it's written back to the file as root.
+///
+/// If `sudo` is installed, this is done by `sudo -e` itself, with `SUDO_EDITOR` set to the
+/// default editor (unless the editor's command can't be expressed that way, e.g. since its path
+/// has a space in it). Otherwise, `doas` or `pkexec` is used to read and write the file (which may
+/// ask for a password twice), and the copy is edited the same way as in [`edit`]. Without a
+/// terminal to ask for a password on, `pkexec` is preferred. Files are written in place, so they
+/// keep their permissions and owner.
+///
+/// This isn't supported on Windows, where a [`ErrorKind::PermissionDenied`] error is returned if
+/// the file can't be written to.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// edit::edit_file_privileged("/etc/hosts")?;
+/// ```
+///
+/// [default editor]: fn.get_editor.html
+/// [`edit_file`]: fn.edit_file.html
+/// [`edit`]: fn.edit.html
+/// [`ErrorKind::PermissionDenied`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.PermissionDenied
+pub fn edit_file_privileged<P: AsRef<Path>>(file: P) -> Result<()> {
+    Editor::new().edit_file_privileged(file)
+}
+
+/// Back up an existing file, then open it in the [default editor] and wait for the editor to exit.
+///
+/// Before the editor is started, the file is copied to `<file>.bak` (or, if that already exists,
+/// a timestamped name like `<file>.1700000000.bak`) in the same directory, so callers can offer to
+/// undo the changes. Existing backups are never overwritten, and are given the same permissions,
+/// owner, and extended attributes as the file (as far as allowed, like in [`edit_file_atomic`]).
+///
+/// Other than the backup, this function is identical to [`edit_file`].
+///
+/// # Returns
+///
+/// If successful, returns the path of the backup, or `None` if there was no file to back up or
+/// the file wasn't changed (in which case the backup is deleted again). If the editor fails, the
+/// backup is kept and the error is returned.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// if let Some(backup) = edit::edit_file_with_backup("config.toml")? {
+///     println!("the old configuration was saved to {}", backup.display());
+/// }
+/// ```
+///
+/// [default editor]: fn.get_editor.html
+/// [`edit_file`]: fn.edit_file.html
+/// [`edit_file_atomic`]: fn.edit_file_atomic.html
+pub fn edit_file_with_backup<P: AsRef<Path>>(file: P) -> Result<Option<PathBuf>> {
+    Editor::new().edit_file_with_backup(file)
+}
+
+/// Open a range of lines of an existing file in the [default editor], and put the edited lines
+/// back in their place once the editor exits.
+///
+/// Lines are numbered from 1, so `edit_range("data.csv", 120..160)` edits lines 120 through 159
+/// (and `120..=160` through 160). Only those lines are read into memory and written to the
+/// temporary file the editor opens, so this works on files too large to open in the editor as a
+/// whole. The edited lines may be more or fewer than the original ones. If the range extends past
+/// the end of the file, only the lines that exist are edited (so a range starting just after the
+/// last line adds lines to the end of the file, on a line of their own even if the last line had
+/// no line ending). A range starting any later than that is rejected with
+/// [`ErrorKind::InvalidInput`] before the editor is opened.
+///
+/// Like in [`edit_file_atomic`], the file is replaced with a spliced copy once editing succeeds,
+/// and isn't touched at all if editing fails or the lines weren't changed. If another program
+/// changes the file in the meantime, a [`Conflict`] error is returned instead, where
+/// [`Conflict::ours`] is only the edited lines.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// edit::edit_range("generated.rs", 120..160)?;
+/// ```
+///
+/// [default editor]: fn.get_editor.html
+/// [`edit_file_atomic`]: fn.edit_file_atomic.html
+/// [`Conflict`]: struct.Conflict.html
+/// [`Conflict::ours`]: struct.Conflict.html#method.ours
+/// [`ErrorKind::InvalidInput`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidInput
+pub fn edit_range<P: AsRef<Path>, R: RangeBounds<usize>>(file: P, lines: R) -> Result<()> {
+    Editor::new().edit_range(file, lines)
+}
+
+/// Open a directory, like a newly generated project, in the [default editor] and wait for the
+/// editor to exit.
+///
+/// Only editors known to be able to open directories are used: Visual Studio Code, VSCodium, Atom,
+/// Sublime Text, Zed, and TextMate (which open it as a project, in a new window that is waited
+/// for, e.g. `code -n -w <dir>` or `subl -w <dir>`), and Vim, gVim, MacVim, Neovim, Emacs, and
+/// Helix (which show a file browser). Other editors detected as the default are skipped, and if
+/// an editor was chosen explicitly (e.g. with [`Editor::editor`]) that can't open directories, an
+/// [`ErrorKind::Unsupported`] error is returned without starting it.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// edit::edit_dir("my-new-project")?;
+/// ```
+///
+/// [default editor]: fn.get_editor.html
+/// [`Editor::editor`]: struct.Editor.html#method.editor
+/// [`ErrorKind::Unsupported`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.Unsupported
+pub fn edit_dir<P: AsRef<Path>>(dir: P) -> Result<()> {
+    Editor::new().edit_dir(dir)
+}
+
+/// Open an existing file (or create a new one, depending on the editor's behavior) in a specific
+/// editor instead of the [default editor] and wait for the editor to exit.
+///
+/// `command` is interpreted as in [`edit_with_command`]. Other than the editor used, this function
+/// is identical to [`edit_file`].
+///
+/// [default editor]: fn.get_editor.html
+/// [`edit_with_command`]: fn.edit_with_command.html
+/// [`edit_file`]: fn.edit_file.html
+pub fn edit_file_with_command<P: AsRef<Path>>(file: P, command: &str) -> Result<()> {
+    Editor::new().editor(command).edit_file(file)
+}
+
+/// Open an existing file (or create a new one, depending on the editor's behavior) in the
+/// [default editor] without waiting for the editor to exit.
+///
+/// This is like [`edit_file`], but returns an [`EditorSession`] as soon as the editor has been
+/// spawned. The session can be used to poll the editor, wait for it (optionally with a timeout),
+/// or kill it, which is useful for e.g. TUI applications that want to keep drawing their own
+/// interface while the editor is open.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use std::time::Duration;
+///
+/// let mut session = edit::edit_file_session("notes.txt")?;
+/// if session.wait_timeout(Duration::from_secs(60))?.is_none() {
+///     session.kill()?;
+/// }
+/// ```
+///
+/// [default editor]: fn.get_editor.html
+/// [`edit_file`]: fn.edit_file.html
+/// [`EditorSession`]: struct.EditorSession.html
+pub fn edit_file_session<P: AsRef<Path>>(file: P) -> Result<EditorSession> {
+    Editor::new().edit_file_session(file)
+}
+
+/// A builder for configuring an edit session.
+///
+/// The free functions in this crate ([`edit`], [`edit_file`], etc.) all use an `Editor` with the
+/// default settings. Constructing one explicitly lets several options be combined in one call
+/// instead of needing a separate function for every combination:
+///
+/// ```rust,ignore
+/// use edit::Editor;
+///
+/// let edited = Editor::new()
+///     .editor("code -w")
+///     .suffix(".md")
+///     .edit("# Release notes\n")?;
+/// ```
+///
+/// [`edit`]: fn.edit.html
+/// [`edit_file`]: fn.edit_file.html
+#[derive(Clone, Debug, Default)]
+pub struct Editor {
+    command: Option<String>,
+    env_vars: Option<Vec<String>>,
+    visual_needs_terminal: bool,
+    tool_env_vars: bool,
+    allow_headless_gui: bool,
+    ignore_ssh: bool,
+    skip_terminal_editors: bool,
+    terminal_emulator: bool,
+    multiplexer_popup: bool,
+    ignore_unattended: bool,
+    unattended_editor: Option<String>,
+    root_policy: RootPolicy,
+    preferred_kind: Option<EditorKind>,
+    remember_editor: bool,
+    noninteractive_content: Option<Vec<u8>>,
+    line: Option<usize>,
+    editor_servers: bool,
+    keep_wait_args: bool,
+    editors_var: EditorsVar,
+    candidates: Vec<String>,
+    fallback_candidates: Vec<String>,
+    #[cfg(feature = "config-file")]
+    skip_user_config: bool,
+    #[cfg(feature = "config-file")]
+    project_config: bool,
+    prefix: Option<OsString>,
+    suffix: Option<OsString>,
+    auto_suffix: bool,
+    temp_dir: Option<PathBuf>,
+    strip_comments: bool,
+    scissors: bool,
+    comment_prefix: Option<String>,
+    trim_trailing_whitespace: bool,
+    collapse_trailing_newlines: bool,
+    ensure_final_newline: bool,
+    line_endings: LineEndings,
+    #[cfg(feature = "encoding_rs")]
+    encoding: Option<&'static encoding_rs::Encoding>,
+    keep_on_error: bool,
+    lock_policy: LockPolicy,
+    secure: bool,
+    in_memory: bool,
+    #[cfg(feature = "encrypted-temp")]
+    encrypt_temp: bool,
+    ram_temp_dir: bool,
+    timeout: Option<Duration>,
+    wait_for: WaitFor,
+    exit_code_policy: ExitCodePolicy,
+    accepted_exit_codes: Vec<i32>,
+    forward_signals: bool,
+    pty: bool,
+    shell: bool,
+    #[cfg(feature = "portal")]
+    portal: bool,
+    cancel: Option<CancelHandle>,
+    stdin: StdioMode,
+    stdout: StdioMode,
+    stderr: StdioMode,
+    current_dir: Option<PathBuf>,
+    envs: Vec<(OsString, Option<OsString>)>,
+    env_clear: bool,
+}
+
+impl Editor {
+    /// Create a new `Editor` with the default settings.
+    ///
+    /// By default, the editor is detected the same way as in [`get_editor`] and temporary files
+    /// are created with the defaults of [`TempOptions`].
+    ///
+    /// [`get_editor`]: fn.get_editor.html
+    /// [`TempOptions`]: struct.TempOptions.html
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use `command` as the editor instead of detecting one.
+    ///
+    /// `command` is split into a program and arguments the same way as the `VISUAL` and `EDITOR`
+    /// environment variables are. If the program can't be found, the edit session fails with
+    /// [`ErrorKind::NotFound`] rather than falling back to another editor.
+    ///
+    /// [`ErrorKind::NotFound`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.NotFound
+    pub fn editor<S: Into<String>>(&mut self, command: S) -> &mut Self {
+        self.command = Some(command.into());
+        self
+    }
+
+    /// Set the environment variables consulted (in order of precedence) when detecting an editor.
+    ///
+    /// The default is `VISUAL`, then `EDITOR` (or whatever was set with [`set_env_vars`]).
+    /// Passing an empty slice skips these variables, so (unless `EDIT_EDITORS` is set) only the
+    /// hardcoded list of editors is searched.
+    ///
+    /// [`set_env_vars`]: fn.set_env_vars.html
+    pub fn env_vars<S: AsRef<str>>(&mut self, vars: &[S]) -> &mut Self {
+        self.env_vars = Some(vars.iter().map(|v| v.as_ref().to_owned()).collect());
+        self
+    }
+
+    /// Prefer `EDITOR` over `VISUAL` when there's no capable terminal. The default is `false`.
+    ///
+    /// Traditionally, `VISUAL` names a full-screen editor and `EDITOR` a line editor that works
+    /// on any terminal. If this is enabled and stdin or stdout isn't a terminal, or `TERM` is
+    /// unset, empty, or `dumb`, `EDITOR` is consulted before `VISUAL` (like `mailx` and
+    /// `crontab` do). Otherwise, `VISUAL` still takes precedence.
+    pub fn visual_needs_terminal(&mut self, enabled: bool) -> &mut Self {
+        self.visual_needs_terminal = enabled;
+        self
+    }
+
+    /// Set whether to skip GUI editors when there's no display to show them on. The default is
+    /// `true`.
+    ///
+    /// Over SSH or on a bare console, a GUI editor (e.g. `code` from a `VISUAL` set for a
+    /// desktop session) would fail or wait forever for a window nobody can see. With this
+    /// enabled, well-known GUI editors and file openers are skipped if neither `DISPLAY` nor
+    /// `WAYLAND_DISPLAY` is set (or on Windows, if this isn't running in a console or Remote
+    /// Desktop session), and the next editor is tried instead. Editors set with
+    /// [`Editor::editor`] or [`set_default_editor`] are never skipped.
+    ///
+    /// [`Editor::editor`]: #method.editor
+    /// [`set_default_editor`]: fn.set_default_editor.html
+    pub fn detect_headless(&mut self, enabled: bool) -> &mut Self {
+        self.allow_headless_gui = !enabled;
+        self
+    }
+
+    /// Set whether to prefer terminal editors in SSH sessions. The default is `true`.
+    ///
+    /// GUI editors are rarely what users want when logged in remotely, even if they're installed
+    /// (and can be shown with X forwarding). With this enabled, well-known GUI editors and file
+    /// openers are tried after every other editor when `SSH_CONNECTION` or `SSH_TTY` is set, even
+    /// if e.g. `VISUAL` names one. Editors set with [`Editor::editor`] or [`set_default_editor`]
+    /// are still used regardless.
+    ///
+    /// [`Editor::editor`]: #method.editor
+    /// [`set_default_editor`]: fn.set_default_editor.html
+    pub fn detect_ssh(&mut self, enabled: bool) -> &mut Self {
+        self.ignore_ssh = !enabled;
+        self
+    }
+
+    /// Try the editors of `kind` this crate knows about before those of the other kind.
+    ///
+    /// This only reorders the hardcoded lists of editors (and the user's default text editor,
+    /// which counts as a GUI editor); editors from environment variables, configuration files,
+    /// etc. still take precedence. By default, terminal editors are tried first, except on
+    /// Windows outside of terminals like Windows Terminal (see [`get_editor`]).
+    ///
+    /// [`get_editor`]: fn.get_editor.html
+    pub fn prefer(&mut self, kind: EditorKind) -> &mut Self {
+        self.preferred_kind = Some(kind);
+        self
+    }
+
+    /// Set whether to remember which editor was used when it had to be guessed, and try it again
+    /// first the next time an editor has to be guessed. The default is `false`.
+    ///
+    /// Without this, which of the editors this crate knows about is used depends on e.g. the
+    /// order of `PATH`, which may not be the same every time. With this enabled, once an editor
+    /// from the hardcoded lists (or the desktop's default editor, or a generic file opener) has
+    /// been opened, it is tried before all of them in later edit sessions, even in other
+    /// processes; editors from environment variables, configuration files, etc. still take
+    /// precedence. See [`remembered_editor`] and [`forget_editor`].
+    ///
+    /// [`remembered_editor`]: fn.remembered_editor.html
+    /// [`forget_editor`]: fn.forget_editor.html
+    pub fn remember_editor(&mut self, enabled: bool) -> &mut Self {
+        self.remember_editor = enabled;
+        self
+    }
+
+    /// Set whether to skip terminal editors when stdin or stdout isn't a terminal. The default
+    /// is `false`.
+    ///
+    /// A GUI application launched from e.g. Finder or Explorer has no terminal for an editor like
+    /// `vim` to run in, so if `EDITOR=vim` is set, editing fails (or hangs without anything
+    /// showing up). With this enabled, well-known terminal editors are skipped in that case, so
+    /// a GUI editor is used instead; if none can be found, editing fails with an error that
+    /// says which editors were skipped. This has no effect if [`Editor::pty`] is enabled, and
+    /// editors set with [`Editor::editor`] or [`set_default_editor`] are never skipped.
+    ///
+    /// [`Editor::pty`]: #method.pty
+    /// [`Editor::editor`]: #method.editor
+    /// [`set_default_editor`]: fn.set_default_editor.html
+    pub fn skip_terminal_editors(&mut self, enabled: bool) -> &mut Self {
+        self.skip_terminal_editors = enabled;
+        self
+    }
+
+    /// Set whether to run terminal editors in a new terminal emulator window when stdin or stdout
+    /// isn't a terminal. The default is `false`.
+    ///
+    /// This lets GUI applications honor e.g. `EDITOR=vim`: the editor is run in the terminal
+    /// emulator named by the `TERMINAL` environment variable or a well-known one such as
+    /// `x-terminal-emulator`, `gnome-terminal`, `konsole`, or `alacritty` on Linux, and in
+    /// Terminal.app on MacOS if nothing else is installed, and the edit session lasts until the
+    /// editor exits. On Windows, the editor gets a console window of its own, which opens in
+    /// Windows Terminal if it is the default terminal.
+    ///
+    /// Only well-known terminal editors are run in a terminal emulator. This has no effect if
+    /// [`Editor::pty`] is enabled, and takes precedence over [`Editor::skip_terminal_editors`]
+    /// as long as a terminal emulator can be found.
+    ///
+    /// [`Editor::pty`]: #method.pty
+    /// [`Editor::skip_terminal_editors`]: #method.skip_terminal_editors
+    pub fn terminal_emulator(&mut self, enabled: bool) -> &mut Self {
+        self.terminal_emulator = enabled;
+        self
+    }
+
+    /// Set whether to run editors in a popup when running inside tmux or zellij. The default is
+    /// `false`.
+    ///
+    /// Running a terminal editor in the same terminal as a full-screen program (like a TUI
+    /// application built with this crate) means they fight over it. With this enabled, the
+    /// editor is run in a `tmux display-popup` or a floating pane made with `zellij run` instead,
+    /// and the edit session lasts until it exits. GUI editors are run as usual, and so is
+    /// everything if [`Editor::pty`] is enabled.
+    ///
+    /// The popup is started in the directory set with [`Editor::current_dir`] (or the current
+    /// directory), but it gets the environment of the tmux or zellij session, so the environment
+    /// variables set with [`Editor::env`] don't apply to it.
+    ///
+    /// [`Editor::pty`]: #method.pty
+    /// [`Editor::current_dir`]: #method.current_dir
+    /// [`Editor::env`]: #method.env
+    pub fn multiplexer_popup(&mut self, enabled: bool) -> &mut Self {
+        self.multiplexer_popup = enabled;
+        self
+    }
+
+    /// Set whether to avoid guessing an editor in CI and in containers without a terminal. The
+    /// default is `true`.
+    ///
+    /// There's usually nobody around to use an editor in CI runners (detected from the `CI`
+    /// environment variable and those set by CI services that don't set it) or in a Docker or
+    /// Podman container that isn't run interactively, so instead of starting whatever editor or
+    /// file opener can be found and waiting forever, only editors that were explicitly chosen
+    /// are used there: the ones set with [`Editor::editor`], in the environment variables and
+    /// configuration file, or added with [`Editor::candidate`] and
+    /// [`Editor::fallback_candidate`]. If none of them can be found, the
+    /// [`Editor::unattended_editor`] is used, or editing fails with [`ErrorKind::Unsupported`].
+    ///
+    /// [`Editor::editor`]: #method.editor
+    /// [`Editor::candidate`]: #method.candidate
+    /// [`Editor::fallback_candidate`]: #method.fallback_candidate
+    /// [`Editor::unattended_editor`]: #method.unattended_editor
+    /// [`ErrorKind::Unsupported`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.Unsupported
+    pub fn detect_unattended(&mut self, enabled: bool) -> &mut Self {
+        self.ignore_unattended = !enabled;
+        self
+    }
+
+    /// Use `command` as the editor in CI and in containers without a terminal if no editor was
+    /// explicitly chosen (see [`Editor::detect_unattended`]).
+    ///
+    /// This is meant for non-interactive commands, e.g. `true` to leave the contents as they
+    /// are, or a script that fills in a template.
+    ///
+    /// [`Editor::detect_unattended`]: #method.detect_unattended
+    pub fn unattended_editor<S: Into<String>>(&mut self, command: S) -> &mut Self {
+        self.unattended_editor = Some(command.into());
+        self
+    }
+
+    /// Set what to do about GUI editors when running as root under `sudo`. The default is
+    /// [`RootPolicy::SkipGui`].
+    ///
+    /// "Running as root under `sudo`" means the effective user ID is 0 and `SUDO_USER` is set.
+    /// Editors set with [`Editor::editor`] or [`set_default_editor`] are never skipped.
+    ///
+    /// [`RootPolicy::SkipGui`]: enum.RootPolicy.html#variant.SkipGui
+    /// [`Editor::editor`]: #method.editor
+    /// [`set_default_editor`]: fn.set_default_editor.html
+    pub fn root_policy(&mut self, policy: RootPolicy) -> &mut Self {
+        self.root_policy = policy;
+        self
+    }
+
+    /// Don't open an editor at all; instead, act as if the user replaced the contents with
+    /// `content` and quit.
+    ///
+    /// This is meant for tests and scripted runs of programs that would otherwise wait for a
+    /// human. The content is written to the file being edited, so e.g. [`Editor::edit`] returns
+    /// it and [`Editor::edit_file`] overwrites the file with it. [`Editor::edit_file_session`]
+    /// isn't affected, since there is no editor to return a session for.
+    ///
+    /// The same can be done for text edited in temporary files without changing the program by
+    /// setting the `EDIT_NONINTERACTIVE_CONTENT` environment variable, which is used if this isn't
+    /// set. Since the variable could be set for a whole test run, it never overwrites the
+    /// caller's own files: with only it set, [`Editor::edit_file`] and the like leave the files as
+    /// they are, as if the user quit without saving, and only this option overwrites them.
+    ///
+    /// [`Editor::edit`]: #method.edit
+    /// [`Editor::edit_file`]: #method.edit_file
+    /// [`Editor::edit_file_session`]: #method.edit_file_session
+    pub fn noninteractive_content<B: Into<Vec<u8>>>(&mut self, content: B) -> &mut Self {
+        self.noninteractive_content = Some(content.into());
+        self
+    }
+
+    /// Set whether to open files in the editor this is running inside of, if any. The default is
+    /// `false`.
+    ///
+    /// When a program using this crate is run in the built-in terminal of an editor, opening a
+    /// second editor (often in that same terminal) is rarely what the user wants. With this
+    /// enabled, the file is opened in the running editor instead, before anything else is tried:
+    ///
+    /// - in Neovim's `:terminal`, with [neovim-remote] (`nvr --remote-wait`), since Neovim itself
+    ///   can't wait for a remote file to be closed
+    /// - in Emacs (e.g. in `shell-mode` or `vterm`), with `emacsclient -a ''`, which starts an
+    ///   Emacs server if there isn't one
+    /// - in Visual Studio Code's terminal, with `code -r -w`
+    ///
+    /// The edit session lasts until the file is closed in the editor (or, in Emacs, until it's
+    /// marked as done with `C-x #`). Only editors set with [`Editor::editor`] or
+    /// [`set_default_editor`] take precedence over these.
+    ///
+    /// [neovim-remote]: https://github.com/mhinz/neovim-remote
+    /// [`Editor::editor`]: #method.editor
+    /// [`set_default_editor`]: fn.set_default_editor.html
+    pub fn editor_servers(&mut self, enabled: bool) -> &mut Self {
+        self.editor_servers = enabled;
+        self
+    }
+
+    /// Set whether to add missing arguments that make GUI editors wait for the file to be closed
+    /// to editors from environment variables. The default is `true`.
+    ///
+    /// Many GUI editors return as soon as the file is open (or handed off to an existing window),
+    /// so e.g. `EDITOR=code` makes the edit session end immediately, before the user has made any
+    /// changes. When enabled, editors that this crate knows need an argument to wait (like `-w`
+    /// for Visual Studio Code, or `-f` for gVim) get it if they don't have it (or a long form of
+    /// it, like `--wait`) already. Editors this crate doesn't know about are left as they are.
+    pub fn add_wait_args(&mut self, enabled: bool) -> &mut Self {
+        self.keep_wait_args = !enabled;
+        self
+    }
+
+    /// Also consult the editor environment variables of other well-known tools. The default is
+    /// `false`.
+    ///
+    /// If enabled, `GIT_EDITOR` is consulted after the other environment variables (see
+    /// [`Editor::env_vars`]), for users who only set their editor for git. When running under
+    /// `sudo` (i.e. when `SUDO_USER` is set), `SUDO_EDITOR` is consulted before all of them, as
+    /// `sudoedit` does.
+    ///
+    /// [`Editor::env_vars`]: #method.env_vars
+    pub fn tool_env_vars(&mut self, enabled: bool) -> &mut Self {
+        self.tool_env_vars = enabled;
+        self
+    }
+
+    /// Set where the `EDIT_EDITORS` environment variable is consulted. See [`EditorsVar`].
+    ///
+    /// [`EditorsVar`]: enum.EditorsVar.html
+    pub fn editors_var(&mut self, editors_var: EditorsVar) -> &mut Self {
+        self.editors_var = editors_var;
+        self
+    }
+
+    /// Add an editor command to try before the hardcoded list of editors.
+    ///
+    /// Like [`register_candidate`], but only for this `Editor`. Candidates added here take
+    /// precedence over those registered globally.
+    ///
+    /// [`register_candidate`]: fn.register_candidate.html
+    pub fn candidate<S: Into<String>>(&mut self, command: S) -> &mut Self {
+        self.candidates.push(command.into());
+        self
+    }
+
+    /// Add an editor command to try after the hardcoded list of editors.
+    ///
+    /// Like [`register_fallback_candidate`], but only for this `Editor`. Candidates added here are
+    /// tried after those registered globally.
+    ///
+    /// [`register_fallback_candidate`]: fn.register_fallback_candidate.html
+    pub fn fallback_candidate<S: Into<String>>(&mut self, command: S) -> &mut Self {
+        self.fallback_candidates.push(command.into());
+        self
+    }
+
+    /// Set whether to read the [user configuration file]. The default is `true`.
+    ///
+    /// [user configuration file]: fn.user_config_path.html
+    #[cfg(feature = "config-file")]
+    pub fn user_config(&mut self, enabled: bool) -> &mut Self {
+        self.skip_user_config = !enabled;
+        self
+    }
+
+    /// Set whether to look for a [project configuration file]. The default is `false`.
+    ///
+    /// If enabled, the `editor` and `fallbacks` set in the project configuration file take
+    /// precedence over those in the user configuration file, and its `suffix` is used for
+    /// temporary files unless one was set with [`Editor::suffix`].
+    ///
+    /// [project configuration file]: fn.project_config_path.html
+    /// [`Editor::suffix`]: struct.Editor.html#method.suffix
+    #[cfg(feature = "config-file")]
+    pub fn project_config(&mut self, enabled: bool) -> &mut Self {
+        self.project_config = enabled;
+        self
+    }
+
+    /// Set a prefix for the names of temporary files. See [`TempOptions::prefix`].
+    ///
+    /// [`TempOptions::prefix`]: struct.TempOptions.html#method.prefix
+    pub fn prefix<S: AsRef<OsStr>>(&mut self, prefix: S) -> &mut Self {
+        self.prefix = Some(prefix.as_ref().to_owned());
+        self
+    }
+
+    /// Set a suffix for the names of temporary files. See [`TempOptions::suffix`].
+    ///
+    /// Editors often use the file extension to pick a syntax highlighting mode, so a suffix like
+    /// `".md"` can make for a nicer editing experience.
+    ///
+    /// [`TempOptions::suffix`]: struct.TempOptions.html#method.suffix
+    pub fn suffix<S: AsRef<OsStr>>(&mut self, suffix: S) -> &mut Self {
+        self.suffix = Some(suffix.as_ref().to_owned());
+        self
+    }
+
+    /// Set whether to pick a suffix for the names of temporary files from the text being edited.
+    /// The default is `false`.
+    ///
+    /// If enabled, text that looks like JSON, YAML, TOML, or Markdown is edited in a file with the
+    /// matching extension (e.g. `.json`), so editors highlight it. The format is guessed with
+    /// cheap heuristics, so this is only used when no suffix was set with [`Editor::suffix`] or
+    /// in a configuration file. Files passed to e.g. [`Editor::edit_file`] are never renamed.
+    ///
+    /// [`Editor::suffix`]: #method.suffix
+    /// [`Editor::edit_file`]: #method.edit_file
+    pub fn auto_suffix(&mut self, enabled: bool) -> &mut Self {
+        self.auto_suffix = enabled;
+        self
+    }
+
+    /// Set the directory to create temporary files in, instead of the system's temporary
+    /// directory. See [`edit_in_dir`].
+    ///
+    /// This takes precedence over [`Editor::ram_temp_dir`]. The directory must already exist.
+    ///
+    /// [`edit_in_dir`]: fn.edit_in_dir.html
+    /// [`Editor::ram_temp_dir`]: #method.ram_temp_dir
+    pub fn temp_dir<P: AsRef<Path>>(&mut self, dir: P) -> &mut Self {
+        self.temp_dir = Some(dir.as_ref().to_owned());
+        self
+    }
+
+    /// Set the prefix and suffix for the names of temporary files from a file name, so they look
+    /// like `name` with random characters before the extension (e.g. `notes-a1B2c3.md` for
+    /// `notes.md`).
+    ///
+    /// This is like setting [`Editor::prefix`] to the file stem of `name` followed by `-`, and
+    /// [`Editor::suffix`] to its extension (if any) with the dot.
+    ///
+    /// [`Editor::prefix`]: #method.prefix
+    /// [`Editor::suffix`]: #method.suffix
+    pub fn file_name<P: AsRef<Path>>(&mut self, name: P) -> &mut Self {
+        let name = name.as_ref();
+        let mut prefix = name.file_stem().unwrap_or_default().to_owned();
+        prefix.push("-");
+        self.prefix = Some(prefix);
+        if let Some(extension) = name.extension() {
+            let mut suffix = OsString::from(".");
+            suffix.push(extension);
+            self.suffix = Some(suffix);
+        }
+        self
+    }
+
+    /// Set whether to remove comment lines from the edited text, like `git commit` does. The
+    /// default is `false`.
+    ///
+    /// With this enabled, every line starting with `#` (or the prefix set with
+    /// [`Editor::comment_prefix`]) is removed from text returned by e.g. [`Editor::edit`], so
+    /// templates can include instructions for the user that never end up in the result:
+    ///
+    /// ```rust,ignore
+    /// let message = edit::Editor::new()
+    ///     .strip_comments(true)
+    ///     .edit("\n# Describe your changes above. Lines starting with '#' are ignored.\n")?;
+    /// ```
+    ///
+    /// Only text is cleaned up; functions returning raw bytes (like [`Editor::edit_bytes`]) and
+    /// editing files in place aren't affected.
+    ///
+    /// [`Editor::comment_prefix`]: #method.comment_prefix
+    /// [`Editor::edit`]: #method.edit
+    /// [`Editor::edit_bytes`]: #method.edit_bytes
+    pub fn strip_comments(&mut self, enabled: bool) -> &mut Self {
+        self.strip_comments = enabled;
+        self
+    }
+
+    /// Set whether to remove everything from a scissors line on from the edited text, like `git
+    /// commit --cleanup=scissors` does. The default is `false`.
+    ///
+    /// A scissors line is exactly `# ------------------------ >8 ------------------------` (with
+    /// the prefix set with [`Editor::comment_prefix`] instead of `#`). Anything below it in the
+    /// template, like a diff to show what is being described, is dropped from the result even if
+    /// it isn't commented out, and even if [`Editor::strip_comments`] isn't enabled:
+    ///
+    /// ```rust,ignore
+    /// let template = format!(
+    ///     "\n# ------------------------ >8 ------------------------\n{}",
+    ///     diff
+    /// );
+    /// let message = edit::Editor::new().scissors(true).edit(template)?;
+    /// ```
+    ///
+    /// Like [`Editor::strip_comments`], this only affects functions returning text.
+    ///
+    /// [`Editor::comment_prefix`]: #method.comment_prefix
+    /// [`Editor::strip_comments`]: #method.strip_comments
+    pub fn scissors(&mut self, enabled: bool) -> &mut Self {
+        self.scissors = enabled;
+        self
+    }
+
+    /// Set the prefix of the lines removed by [`Editor::strip_comments`] and of scissors lines
+    /// (see [`Editor::scissors`]). The default is `#`.
+    ///
+    /// [`Editor::strip_comments`]: #method.strip_comments
+    /// [`Editor::scissors`]: #method.scissors
+    pub fn comment_prefix<S: Into<String>>(&mut self, prefix: S) -> &mut Self {
+        self.comment_prefix = Some(prefix.into());
+        self
+    }
+
+    /// Set whether to remove whitespace at the end of every line of the edited text, like `git
+    /// commit --cleanup=whitespace` does. The default is `false`.
+    ///
+    /// Line endings are kept, including `\r\n`. Like [`Editor::strip_comments`], this only affects
+    /// functions returning text, and is done after comments are removed.
+    ///
+    /// [`Editor::strip_comments`]: #method.strip_comments
+    pub fn trim_trailing_whitespace(&mut self, enabled: bool) -> &mut Self {
+        self.trim_trailing_whitespace = enabled;
+        self
+    }
+
+    /// Set whether to replace several line endings at the end of the edited text (i.e. blank lines
+    /// at the end) with a single one. The default is `false`.
+    ///
+    /// Combined with [`Editor::trim_trailing_whitespace`], this also removes lines at the end that
+    /// only have whitespace on them. Like [`Editor::strip_comments`], this only affects functions
+    /// returning text, and is done after comments are removed.
+    ///
+    /// [`Editor::trim_trailing_whitespace`]: #method.trim_trailing_whitespace
+    /// [`Editor::strip_comments`]: #method.strip_comments
+    pub fn collapse_trailing_newlines(&mut self, enabled: bool) -> &mut Self {
+        self.collapse_trailing_newlines = enabled;
+        self
+    }
+
+    /// Set whether to add a line ending to the end of the edited text if it doesn't have one
+    /// (and isn't empty). The default is `false`.
+    ///
+    /// The line ending added is `\r\n` if the text already uses those, and `\n` otherwise. Like
+    /// [`Editor::strip_comments`], this only affects functions returning text, and is done after
+    /// comments are removed.
+    ///
+    /// [`Editor::strip_comments`]: #method.strip_comments
+    pub fn ensure_final_newline(&mut self, enabled: bool) -> &mut Self {
+        self.ensure_final_newline = enabled;
+        self
+    }
+
+    /// Set how to treat the line endings of edited text. The default is
+    /// [`LineEndings::Preserve`].
+    ///
+    /// Editors differ in which line endings they save files with (e.g. older versions of Notepad
+    /// always use `\r\n`), which can surprise programs expecting one kind. With any other policy,
+    /// the text is given the platform's line endings before it's opened, so the user sees what
+    /// their editor expects, and the edited text is given the line endings of the policy before
+    /// it's returned (and before any other cleanup, like [`Editor::strip_comments`]).
+    ///
+    /// Like [`Editor::strip_comments`], this only affects functions that edit text, not raw bytes
+    /// or files.
+    ///
+    /// [`LineEndings::Preserve`]: enum.LineEndings.html#variant.Preserve
+    /// [`Editor::strip_comments`]: #method.strip_comments
+    pub fn line_endings(&mut self, policy: LineEndings) -> &mut Self {
+        self.line_endings = policy;
+        self
+    }
+
+    /// Set whether to keep the temporary file when editing a string or buffer fails. The default
+    /// is `false`.
+    ///
+    /// Normally, the temporary file is deleted however the edit session ends, so if e.g. the
+    /// editor crashes or the edited text can't be decoded, any changes the user made are lost.
+    /// With this enabled, the temporary file is kept if its contents were changed, and the
+    /// returned error wraps a [`KeptFile`] with its path, so users can be told where their draft
+    /// is. Files aren't kept when an edit session is cancelled with a [`CancelHandle`].
+    ///
+    /// [`KeptFile`]: struct.KeptFile.html
+    /// [`CancelHandle`]: struct.CancelHandle.html
+    pub fn keep_on_error(&mut self, enabled: bool) -> &mut Self {
+        self.keep_on_error = enabled;
+        self
+    }
+
+    /// Set what to do when a file passed to e.g. [`Editor::edit_file`] is already being edited by
+    /// another process. The default is [`LockPolicy::None`].
+    ///
+    /// Unless this is [`LockPolicy::None`], files are locked while they're edited, with a lock
+    /// file next to them (e.g. `.config.toml.lock` for `config.toml`) that's deleted afterwards.
+    /// This only keeps out other processes that lock files the same way (like other programs using
+    /// this crate), and fails if the lock file can't be created. Locks are only held by functions
+    /// that wait for the editor, so e.g. [`Editor::edit_file_session`] doesn't lock files. Strings
+    /// and buffers are edited in temporary files of their own, so they're never locked.
+    ///
+    /// [`Editor::edit_file`]: #method.edit_file
+    /// [`LockPolicy::None`]: enum.LockPolicy.html#variant.None
+    /// [`Editor::edit_file_session`]: #method.edit_file_session
+    pub fn lock(&mut self, policy: LockPolicy) -> &mut Self {
+        self.lock_policy = policy;
+        self
+    }
+
+    /// Set whether to make sure temporary files can't be accessed by other users, for editing
+    /// sensitive text like passwords or API keys. The default is `false`.
+    ///
+    /// If enabled, temporary files are created with permissions that only let the current user
+    /// read and write them, in a directory no other user can access. If the temporary directory
+    /// (e.g. `TMPDIR`) isn't already private, a private directory is created in it, but only if
+    /// the temporary directory can't be written to by other users or has the sticky bit set (like
+    /// `/tmp`). If any of this can't be ensured, editing fails with an
+    /// [`ErrorKind::PermissionDenied`] error instead of risking exposing the text.
+    ///
+    /// Temporary files are also overwritten with zeros before they're deleted, so the text can't
+    /// be recovered from the disk afterwards (on filesystems that overwrite data in place), and
+    /// they're never kept by [`Editor::keep_on_error`] unless they're encrypted. Buffers this crate makes copies of are
+    /// zeroed too, but the input and returned text belong to the caller, who may want to zero
+    /// them with e.g. the [`zeroize`] crate once they're done with them. [`edit_stream_async`] and
+    /// [`edit_watch_async`] leave deleting the temporary file to a value the caller drops, where
+    /// it can't be overwritten without blocking, so they return [`ErrorKind::Unsupported`] in
+    /// this mode.
+    ///
+    /// Temporary files aren't given to the user who ran `sudo` in this mode, even with
+    /// [`RootPolicy::RunGuiAsUser`]. On Windows, temporary directories are already private to each
+    /// user, so they are used as-is.
+    ///
+    /// On machines where temporary storage is shared or unencrypted, also enable
+    /// [`Editor::in_memory`] or [`Editor::ram_temp_dir`] to keep the text off the disk entirely,
+    /// or [`Editor::encrypt_temp`] (with the `encrypted-temp` feature) to only ever write it
+    /// encrypted:
+    ///
+    /// ```rust,ignore
+    /// let token = edit::Editor::new()
+    ///     .secure(true)
+    ///     .in_memory(true)
+    ///     .ram_temp_dir(true)
+    ///     .edit("")?;
+    /// ```
+    ///
+    /// [`ErrorKind::PermissionDenied`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.PermissionDenied
+    /// [`Editor::keep_on_error`]: #method.keep_on_error
+    /// [`zeroize`]: https://docs.rs/zeroize
+    /// [`edit_stream_async`]: fn.edit_stream_async.html
+    /// [`edit_watch_async`]: fn.edit_watch_async.html
+    /// [`ErrorKind::Unsupported`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.Unsupported
+    /// [`Editor::in_memory`]: #method.in_memory
+    /// [`Editor::ram_temp_dir`]: #method.ram_temp_dir
+    /// [`Editor::encrypt_temp`]: #method.encrypt_temp
+    /// [`RootPolicy::RunGuiAsUser`]: enum.RootPolicy.html#variant.RunGuiAsUser
+    pub fn secure(&mut self, enabled: bool) -> &mut Self {
+        self.secure = enabled;
+        self
+    }
+
+    /// Set whether to edit strings and buffers in files that only exist in memory, so the text is
+    /// never written to disk. The default is `false`.
+    ///
+    /// On Linux, this creates an anonymous file with `memfd_create` and has the editor open it
+    /// through `/proc/<pid>/fd/<fd>`. Such a path has no extension, so the suffix set with e.g.
+    /// [`Editor::suffix`] is ignored, and nothing is kept by [`Editor::keep_on_error`] since
+    /// there's no file to keep. Editors that save by replacing files (rather than writing to them)
+    /// can't save to this kind of path, but most fall back to writing to it.
+    ///
+    /// Elsewhere, or if in-memory files can't be created (e.g. on old kernels, or if `/proc` isn't
+    /// mounted), a regular temporary file is used instead, as it is when files are opened through
+    /// the desktop portal or given to the user who ran `sudo`. Combine this with
+    /// [`Editor::secure`] to make sure the fallback is private.
+    ///
+    /// [`Editor::suffix`]: #method.suffix
+    /// [`Editor::keep_on_error`]: #method.keep_on_error
+    /// [`Editor::secure`]: #method.secure
+    pub fn in_memory(&mut self, enabled: bool) -> &mut Self {
+        self.in_memory = enabled;
+        self
+    }
+
+    /// Set whether to create temporary files on a RAM-backed filesystem when there is one, rather
+    /// than in the usual temporary directory. The default is `false`.
+    ///
+    /// This keeps sensitive text off the disk and makes saving very large buffers faster on slow
+    /// disks. On Linux, `XDG_RUNTIME_DIR` is used if it's on a tmpfs, or `/dev/shm` otherwise
+    /// (e.g. if the temporary file will be given to the user who ran `sudo`). If neither can be
+    /// used, or on other platforms, temporary files are created in the usual directory.
+    ///
+    /// Unlike [`Editor::in_memory`], this works with every editor, but the files still have names
+    /// that other processes of the same user can find.
+    ///
+    /// [`Editor::in_memory`]: #method.in_memory
+    pub fn ram_temp_dir(&mut self, enabled: bool) -> &mut Self {
+        self.ram_temp_dir = enabled;
+        self
+    }
+
+    /// Kill the editor if it runs for longer than `timeout`.
+    ///
+    /// When the timeout is exceeded, the editor and any processes it started are killed (see
+    /// [`EditorSession::kill_tree`]) and the edit session fails with [`ErrorKind::TimedOut`]. When
+    /// editing a string or buffer, the temporary file is deleted as usual.
+    ///
+    /// This has no effect on [`Editor::edit_file_session`], which doesn't wait for the editor.
+    ///
+    /// [`EditorSession::kill_tree`]: struct.EditorSession.html#method.kill_tree
+    /// [`ErrorKind::TimedOut`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.TimedOut
+    /// [`Editor::edit_file_session`]: struct.Editor.html#method.edit_file_session
+    pub fn timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Set what to wait for before the edit session is considered finished. The default is
+    /// [`WaitFor::Exit`].
+    ///
+    /// This has no effect on [`Editor::edit_file_session`], which doesn't wait for the editor.
+    ///
+    /// [`WaitFor::Exit`]: enum.WaitFor.html#variant.Exit
+    /// [`Editor::edit_file_session`]: struct.Editor.html#method.edit_file_session
+    pub fn wait_for(&mut self, wait_for: WaitFor) -> &mut Self {
+        self.wait_for = wait_for;
+        self
+    }
+
+    /// Set which exit codes of the editor are treated as errors. The default is
+    /// [`ExitCodePolicy::Known`].
+    ///
+    /// [`ExitCodePolicy::Known`]: enum.ExitCodePolicy.html#variant.Known
+    pub fn exit_code_policy(&mut self, policy: ExitCodePolicy) -> &mut Self {
+        self.exit_code_policy = policy;
+        self
+    }
+
+    /// Don't treat the editor exiting with `code` as an error, no matter which editor is used.
+    ///
+    /// This takes precedence over [`ExitCodePolicy::Strict`].
+    ///
+    /// [`ExitCodePolicy::Strict`]: enum.ExitCodePolicy.html#variant.Strict
+    pub fn accept_exit_code(&mut self, code: i32) -> &mut Self {
+        self.accepted_exit_codes.push(code);
+        self
+    }
+
+    /// Forward interrupts to the editor and restore the terminal afterwards. The default is
+    /// `false`. This only has an effect on Unix.
+    ///
+    /// If enabled, SIGINT and SIGTERM received by this process while the editor is running are
+    /// caught and forwarded to the editor instead of terminating this process. Once the editor
+    /// exits, the terminal modes are restored to what they were before the editor started (in
+    /// case the editor left the terminal in raw mode), the previous signal handlers are
+    /// reinstated, and the edit session fails with [`ErrorKind::Interrupted`].
+    ///
+    /// Since signal handlers are process-wide, edit sessions with this enabled shouldn't run
+    /// concurrently.
+    ///
+    /// [`ErrorKind::Interrupted`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.Interrupted
+    pub fn forward_signals(&mut self, forward: bool) -> &mut Self {
+        self.forward_signals = forward;
+        self
+    }
+
+    /// Run the editor in a newly allocated pseudo-terminal. The default is `false`.
+    ///
+    /// Terminal editors like `vim` or `nano` need a terminal to work, so they fail or garble
+    /// their output when this process's stdio is redirected. With this enabled, the editor's
+    /// stdio is connected to a pseudo-terminal instead, and input and output are copied between
+    /// it and this process's stdin and stdout. (If stdin is a terminal, it is put in raw mode for
+    /// the duration of the edit session.) This overrides the [`Editor::stdin`],
+    /// [`Editor::stdout`], and [`Editor::stderr`] settings.
+    ///
+    /// This is only supported on Unix; on other platforms, edit sessions with this enabled fail
+    /// with [`ErrorKind::Unsupported`].
+    ///
+    /// [`Editor::stdin`]: struct.Editor.html#method.stdin
+    /// [`Editor::stdout`]: struct.Editor.html#method.stdout
+    /// [`Editor::stderr`]: struct.Editor.html#method.stderr
+    /// [`ErrorKind::Unsupported`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.Unsupported
+    pub fn pty(&mut self, pty: bool) -> &mut Self {
+        self.pty = pty;
+        self
+    }
+
+    /// Open the file at line `line` (counting from 1), if the editor is known to support that
+    /// (e.g. `vim +<line>` or `code -g <file>:<line>`). Other editors open the file as usual, and
+    /// so do commands run through the shell (see [`Editor::shell`]).
+    ///
+    /// [`Editor::shell`]: #method.shell
+    pub fn line(&mut self, line: usize) -> &mut Self {
+        self.line = Some(line);
+        self
+    }
+
+    /// Run editor commands through the shell, like git does. The default is `false`.
+    ///
+    /// With this enabled, an editor command like the value of `EDITOR` is run as
+    /// `sh -c '<command> "$@"' <command> <file>` (or `cmd.exe /S /C "<command> "<file>""` on
+    /// Windows) instead of being split into a program and arguments by this crate. This allows
+    /// commands that use shell features such as functions, `~`, or variable expansion, e.g.
+    /// `EDITOR='f() { nvim "$@"; }; f'`.
+    ///
+    /// Commands that use shell syntax can't be checked for existence before running them, so
+    /// they are always used if nothing with a higher precedence is found; other commands are
+    /// still only used if their program can be found.
+    pub fn shell(&mut self, shell: bool) -> &mut Self {
+        self.shell = shell;
+        self
+    }
+
+    /// Open files with the host's default application through the [XDG desktop portal] when
+    /// running inside a Flatpak or Snap sandbox, where host editors can't be run directly. The
+    /// default is `false`.
+    ///
+    /// The application the file is opened with can't be waited on, so edit sessions end when
+    /// the file is saved, as with [`WaitFor::Save`]; [`Editor::timeout`] limits how long to wait
+    /// for that. Outside a sandbox (or on platforms other than Linux), this has no effect.
+    ///
+    /// This only applies to edit sessions that wait for editing to finish (like
+    /// [`Editor::edit`]), not to [`Editor::edit_file_session`] or asynchronous edit sessions.
+    ///
+    /// [XDG desktop portal]: https://flatpak.github.io/xdg-desktop-portal/
+    /// [`WaitFor::Save`]: enum.WaitFor.html#variant.Save
+    /// [`Editor::timeout`]: #method.timeout
+    /// [`Editor::edit`]: #method.edit
+    /// [`Editor::edit_file_session`]: #method.edit_file_session
+    #[cfg(feature = "portal")]
+    pub fn portal(&mut self, enabled: bool) -> &mut Self {
+        self.portal = enabled;
+        self
+    }
+
+    /// Allow edit sessions to be cancelled with `handle` (see [`CancelHandle`]).
+    ///
+    /// Once `handle` is cancelled, the editor and any processes it started are killed, the
+    /// temporary file is deleted, and the edit session fails with a [`Cancelled`] error. Sessions
+    /// started with [`Editor::edit_file_session`] notice the cancellation the next time they are
+    /// waited on or polled.
+    ///
+    /// [`CancelHandle`]: struct.CancelHandle.html
+    /// [`Cancelled`]: struct.Cancelled.html
+    /// [`Editor::edit_file_session`]: #method.edit_file_session
+    pub fn cancel_handle(&mut self, handle: &CancelHandle) -> &mut Self {
+        self.cancel = Some(handle.clone());
+        self
+    }
+
+    /// Capture what the editor prints to stderr instead of letting it through to this process's
+    /// stderr. The default is `false`.
+    ///
+    /// If the editor exits with an error, the captured output is included in the returned
+    /// error; see [`ExitError`]. This is the same as `.stderr(StdioMode::Piped)`.
+    ///
+    /// [`ExitError`]: struct.ExitError.html
+    pub fn capture_stderr(&mut self, capture: bool) -> &mut Self {
+        self.stderr = if capture {
+            StdioMode::Piped
+        } else {
+            StdioMode::Inherit
+        };
+        self
+    }
+
+    /// Set how the editor's stdin is set up. The default is [`StdioMode::Inherit`].
+    ///
+    /// [`StdioMode::Inherit`]: enum.StdioMode.html#variant.Inherit
+    pub fn stdin(&mut self, mode: StdioMode) -> &mut Self {
+        self.stdin = mode;
+        self
+    }
+
+    /// Set how the editor's stdout is set up. The default is [`StdioMode::Inherit`].
+    ///
+    /// [`StdioMode::Inherit`]: enum.StdioMode.html#variant.Inherit
+    pub fn stdout(&mut self, mode: StdioMode) -> &mut Self {
+        self.stdout = mode;
+        self
+    }
+
+    /// Set how the editor's stderr is set up. The default is [`StdioMode::Inherit`].
+    ///
+    /// [`StdioMode::Inherit`]: enum.StdioMode.html#variant.Inherit
+    pub fn stderr(&mut self, mode: StdioMode) -> &mut Self {
+        self.stderr = mode;
+        self
+    }
+
+    /// Set the working directory of the editor.
+    ///
+    /// Relative paths given to [`Editor::edit_file`] are still interpreted relative to the working
+    /// directory of this process.
+    ///
+    /// [`Editor::edit_file`]: struct.Editor.html#method.edit_file
+    pub fn current_dir<P: AsRef<Path>>(&mut self, dir: P) -> &mut Self {
+        self.current_dir = Some(dir.as_ref().to_owned());
+        self
+    }
+
+    /// Set an environment variable for the editor. See [`Command::env`].
+    ///
+    /// This only affects the editor process; it has no effect on which editor is detected.
+    ///
+    /// [`Command::env`]: https://doc.rust-lang.org/std/process/struct.Command.html#method.env
+    pub fn env<K: AsRef<OsStr>, V: AsRef<OsStr>>(&mut self, key: K, val: V) -> &mut Self {
+        self.envs
+            .push((key.as_ref().to_owned(), Some(val.as_ref().to_owned())));
+        self
+    }
+
+    /// Remove an environment variable for the editor. See [`Command::env_remove`].
+    ///
+    /// [`Command::env_remove`]: https://doc.rust-lang.org/std/process/struct.Command.html#method.env_remove
+    pub fn env_remove<K: AsRef<OsStr>>(&mut self, key: K) -> &mut Self {
+        self.envs.push((key.as_ref().to_owned(), None));
+        self
+    }
+
+    /// Clear the editor's environment, so it only has the variables set with [`Editor::env`]. See
+    /// [`Command::env_clear`].
+    ///
+    /// [`Editor::env`]: struct.Editor.html#method.env
+    /// [`Command::env_clear`]: https://doc.rust-lang.org/std/process/struct.Command.html#method.env_clear
+    pub fn env_clear(&mut self) -> &mut Self {
+        self.env_clear = true;
+        self.envs.clear();
+        self
+    }
+
+    fn command(&self) -> Option<OsString> {
+        self.command
+            .clone()
+            .or_else(|| config().editor.clone())
+            .map(OsString::from)
+    }
+
+    #[cfg(feature = "config-file")]
+    fn config_files(&self) -> Vec<config_file::ConfigFile> {
+        let mut files = Vec::new();
+        if self.project_config {
+            files.extend(config_file::load_project_config());
+        }
+        if !self.skip_user_config {
+            files.push(config_file::load_user_config());
+        }
+        files
+    }
+
+    #[cfg(not(feature = "config-file"))]
+    fn config_files(&self) -> Vec<config_file::ConfigFile> {
+        Vec::new()
+    }
+
+    /// Check whether this is running unattended (see [`Editor::detect_unattended`]), and if so,
+    /// describe where.
+    fn unattended(&self) -> Option<&'static str> {
+        if self.ignore_unattended {
+            None
+        } else {
+            unattended::detect(has_terminal())
+        }
+    }
+
+    fn candidate_commands(&self) -> Vec<(CandidateSource, OsString)> {
+        let unattended = self.unattended().is_some();
+        self.sourced_commands()
+            .into_iter()
+            .filter(|(source, _)| !(unattended && source.is_guess()))
+            .filter_map(|(source, command)| Some((source, command?)))
+            .filter(|(_, command)| !command.is_empty())
+            .collect()
+    }
+
+    /// Get every editor command to consider along with where it came from, in order of
+    /// precedence. Environment variables are included even if they aren't set (as `None`).
+    fn sourced_commands(&self) -> Vec<(CandidateSource, Option<OsString>)> {
+        let files = self.config_files();
+        let mut openers = Vec::new();
+        if files.iter().find_map(|f| f.generic_openers).unwrap_or(true) {
+            // the desktop environment's own opener is the most likely to work
+            openers = desktop::openers();
+            let generic: Vec<_> = GENERIC_OPENERS
+                .iter()
+                .filter(|opener| !openers.contains(opener))
+                .collect();
+            openers.extend(generic);
+        }
+        let config = config();
+        let env_vars: Vec<&str> = match self.env_vars.as_ref().or(config.env_vars.as_ref()) {
+            Some(env_vars) => env_vars.iter().map(String::as_str).collect(),
+            None => ENV_VARS.to_vec(),
+        };
+        let env_vars = env_var_order(
+            env_vars,
+            self.visual_needs_terminal && !capable_terminal(),
+            env::var_os("SUDO_USER").is_some()
+                && (self.tool_env_vars || self.sudo_user().is_some()),
+            self.tool_env_vars,
+        );
+
+        let from = |source: CandidateSource, command: &str| (source, Some(command.into()));
+        // `EDITOR=code` is a common mistake, which makes editing end as soon as the file is open
+        let fix = |command: OsString| match command.to_str() {
+            Some(command) if !self.keep_wait_args => registry::with_wait_args(command).into(),
+            _ => command,
+        };
+        let env_commands = env_vars.into_iter().map(|var| {
+            (
+                CandidateSource::EnvVar(var.to_string()),
+                env::var_os(var).map(fix),
+            )
+        });
+        let editors_var = editors_var_commands()
+            .into_iter()
+            .map(|command| (CandidateSource::EditorsVar, Some(fix(command.into()))));
+        let detected = |priority| {
+            detector::commands(priority)
+                .into_iter()
+                .map(|command| (CandidateSource::Detector, Some(command.into())))
+        };
+
+        let mut commands = Vec::new();
+        if self.editor_servers {
+            commands.extend(
+                server::commands()
+                    .into_iter()
+                    .map(|command| (CandidateSource::EditorServer, Some(command.into()))),
+            );
+        }
+        commands.extend(
+            files
+                .iter()
+                .filter_map(|f| f.editor.as_deref())
+                .map(|command| from(CandidateSource::ConfigFile, command)),
+        );
+        commands.extend(detected(DetectorPriority::BeforeEnvVars));
+        commands.extend(self.editors_var.place(env_commands, editors_var));
+        let candidates = self.candidates.iter().chain(&config.before);
+        commands.extend(candidates.map(|c| from(CandidateSource::Candidate, c)));
+        let fallbacks = files.iter().flat_map(|f| &f.fallbacks);
+        commands.extend(fallbacks.map(|c| from(CandidateSource::ConfigFallback, c)));
+        commands.extend(detected(DetectorPriority::BeforeBuiltin));
+        if self.remember_editor {
+            commands.push((
+                CandidateSource::Remembered,
+                remembered_editor().map(Into::into),
+            ));
+        }
+        let terminal_first = match self.preferred_kind {
+            Some(kind) => kind == EditorKind::Terminal,
+            None => prefer_terminal_editors(),
+        };
+        let cli_names = || {
+            HARDCODED_CLI_NAMES
+                .iter()
+                .map(|c| from(CandidateSource::Builtin, c))
+        };
+        if terminal_first {
+            commands.extend(cli_names());
+        }
+        // the user's default text editor is likely a GUI editor, but it's still a better guess
+        // than any of the hardcoded ones
+        commands.extend(
+            desktop::default_editors()
+                .into_iter()
+                .map(|command| (CandidateSource::DesktopDefault, Some(command))),
+        );
+        // the lists only have the names of GUI editors, which may need arguments to wait for the
+        // file to be closed
+        commands.extend(
+            HARDCODED_GUI_NAMES
+                .iter()
+                .map(|c| from(CandidateSource::Builtin, &registry::with_wait_args(c))),
+        );
+        if !terminal_first {
+            commands.extend(cli_names());
+        }
+        commands.extend(
+            sandboxed::installed_editors()
+                .into_iter()
+                .map(|command| (CandidateSource::Builtin, Some(command))),
+        );
+        if wsl::is_wsl() {
+            commands.extend(
+                wsl::WSL_NAMES
+                    .iter()
+                    .map(|c| from(CandidateSource::Builtin, c)),
+            );
+        }
+        commands.extend(
+            openers
+                .iter()
+                .map(|c| from(CandidateSource::GenericOpener, c)),
+        );
+        commands.extend(detected(DetectorPriority::AfterBuiltin));
+        let fallbacks = config.after.iter().chain(&self.fallback_candidates);
+        commands.extend(fallbacks.map(|c| from(CandidateSource::FallbackCandidate, c)));
+        commands.extend(
+            HARDCODED_PATHS
+                .iter()
+                .map(|c| from(CandidateSource::BuiltinPath, c)),
+        );
+
+        if let Some(command) = self.unattended_editor.as_deref() {
+            if self.unattended().is_some() {
+                commands.push(from(CandidateSource::UnattendedEditor, command));
+            }
+        }
+
+        if !self.ignore_ssh && is_ssh_session() {
+            // the sort is stable, so the GUI and other editors each stay in order
+            commands.sort_by_key(|(_, command)| command.as_deref().is_some_and(is_gui_command));
+        }
+        commands
+    }
+
+    fn find_editors(&self) -> impl Iterator<Item = (PathBuf, Vec<OsString>)> + '_ {
+        let commands = match self.command() {
+            Some(command) => vec![command],
+            None => self
+                .candidate_commands()
+                .into_iter()
+                .map(|(_, command)| command)
+                .collect(),
+        };
+        let explicit = self.command().is_some();
+        commands
+            .into_iter()
+            .filter_map(|s| get_full_editor_cmd(&s).ok())
+            .filter(move |(editor, _)| explicit || self.skip_reason(editor).is_none())
+    }
+
+    /// Check whether a detected editor should be skipped, and if so, why.
+    fn skip_reason(&self, editor: &Path) -> Option<SkipReason> {
+        if !self.allow_headless_gui && gui::is_headless(editor) {
+            return Some(SkipReason::Headless);
+        }
+        if self.skip_terminal_editors
+            && !self.pty
+            && gui::is_gui(editor) == Some(false)
+            && !has_terminal()
+            && !(self.terminal_emulator && terminal::available())
+        {
+            return Some(SkipReason::NoTerminal);
+        }
+        if self.root_policy == RootPolicy::SkipGui
+            && gui::is_gui(editor) == Some(true)
+            && self.sudo_user().is_some()
+        {
+            return Some(SkipReason::Root);
+        }
+        None
+    }
+
+    /// Get the content to use instead of opening an editor, if any (see
+    /// [`Editor::noninteractive_content`]).
+    fn scripted_content(&self) -> Option<Vec<u8>> {
+        self.noninteractive_content.clone().or_else(|| {
+            env::var_os(NONINTERACTIVE_VAR).map(|v| v.to_string_lossy().into_owned().into_bytes())
+        })
+    }
+
+    /// Check whether to leave the caller's own files as they are instead of editing them, which is
+    /// the case when only the `EDIT_NONINTERACTIVE_CONTENT` environment variable is set: it's
+    /// meant for stubbing out edits of temporary files, and shouldn't overwrite real ones.
+    fn leaves_own_files(&self) -> bool {
+        self.noninteractive_content.is_none() && env::var_os(NONINTERACTIVE_VAR).is_some()
+    }
+
+    /// Get the user who ran `sudo`, if this is running as root under `sudo` and the
+    /// [`RootPolicy`] cares.
+    fn sudo_user(&self) -> Option<sudo::SudoUser> {
+        match self.root_policy {
+            RootPolicy::Allow => None,
+            RootPolicy::SkipGui | RootPolicy::RunGuiAsUser => sudo::invoking_user(),
+        }
+    }
+
+    /// Create a temporary file to edit `content` in with `options`, taking anything they don't
+    /// set from this `Editor`.
+    fn temp_file(&self, options: &TempOptions, content: Option<&[u8]>) -> Result<NamedTempFile> {
+        // only edit_temp_run knows how to keep the file encrypted
+        #[cfg(feature = "encrypted-temp")]
+        if self.encrypt_temp {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "encrypted temporary files are only supported when editing strings and buffers",
+            ));
+        }
+        self.create_temp_file(options, content)
+    }
+
+    /// Create a temporary file like [`Editor::temp_file`], even if it should be encrypted.
+    fn create_temp_file(
+        &self,
+        options: &TempOptions,
+        content: Option<&[u8]>,
+    ) -> Result<NamedTempFile> {
+        let mut options = options.clone();
+        if options.prefix.is_none() {
+            options.prefix = self.prefix.clone();
+        }
+        if options.suffix.is_none() {
+            options.suffix = self.temp_suffix(content);
+        }
+        let builder = options.builder(self.secure);
+
+        let user = match self.root_policy {
+            RootPolicy::RunGuiAsUser if !self.secure => self.sudo_user(),
+            _ => None,
+        };
+        let mut dir = options.dir.clone().or_else(|| self.temp_dir.clone());
+        if dir.is_none() && self.ram_temp_dir {
+            dir = ramdisk::dir(user.is_some());
+        }
+        let dir = dir.unwrap_or_else(temp_dir);
+
+        if self.secure {
+            let file = builder.tempfile_in(secure::private_dir(&dir)?)?;
+            secure::check_file(file.as_file())?;
+            return Ok(file);
+        }
+        let file = builder.tempfile_in(dir)?;
+        if let Some(user) = user {
+            sudo::give(file.as_file(), &user)?;
+        }
+        Ok(file)
+    }
+
+    /// Create an in-memory file containing `buf` to edit, if enabled and possible.
+    fn memory_file(&self, buf: &[u8]) -> Result<Option<memfd::MemFile>> {
+        if !self.in_memory || !self.can_edit_in_memory() {
+            return Ok(None);
+        }
+        let file = memfd::create(buf)?;
+        if let (Some(file), WaitFor::Save) = (&file, self.wait_for) {
+            // make sure a save right away is noticed even with coarse timestamps
+            let _ = file
+                .file()
+                .set_modified(SystemTime::now() - Duration::from_secs(60));
+        }
+        Ok(file)
+    }
+
+    /// Check whether the editor could open an in-memory file, which it can't through the desktop
+    /// portal or as the user who ran `sudo`.
+    fn can_edit_in_memory(&self) -> bool {
+        let gives_away = self.root_policy == RootPolicy::RunGuiAsUser && self.sudo_user().is_some();
+        !self.uses_portal() && !gives_away
+    }
+
+    /// Check whether `editor` should be run in a new terminal emulator window.
+    fn needs_terminal_emulator(&self, editor: &Path) -> bool {
+        self.terminal_emulator && !self.pty && gui::is_gui(editor) == Some(false) && !has_terminal()
+    }
+
+    fn get_editor_args(&self) -> Result<(PathBuf, Vec<OsString>)> {
+        match self.command() {
+            Some(command) => get_full_editor_cmd(&command),
+            None => self.find_editors().next().ok_or_else(|| self.not_found()),
+        }
+    }
+
+    /// Get the error for when no editor can be found.
+    fn not_found(&self) -> Error {
+        match self.unattended() {
+            Some(place) => Error::new(
+                ErrorKind::Unsupported,
+                format!(
+                    "no editor was chosen, and none was guessed since this seems to be running {}",
+                    place
+                ),
+            ),
+            None => Error::new(
+                ErrorKind::NotFound,
+                format!("no editor found; to fix this, {}", install_hint()),
+            ),
+        }
+    }
+
+    /// List every editor this `Editor` could open, in order of precedence. See
+    /// [`editor_candidates`].
+    ///
+    /// If a specific editor was set with [`Editor::editor`] or [`set_default_editor`], it is the
+    /// only candidate.
+    ///
+    /// [`editor_candidates`]: fn.editor_candidates.html
+    /// [`Editor::editor`]: struct.Editor.html#method.editor
+    /// [`set_default_editor`]: fn.set_default_editor.html
+    pub fn editor_candidates(&self) -> Vec<(PathBuf, Vec<String>)> {
+        let mut candidates = Vec::new();
+        for (editor, args) in self.find_editors() {
+            let args = args
+                .iter()
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect();
+            let candidate = (editor, args);
+            if !candidates.contains(&candidate) {
+                candidates.push(candidate);
+            }
+        }
+        candidates
+    }
+
+    /// Get the suffix for a temporary file that will contain `content`, if known.
+    fn temp_suffix(&self, content: Option<&[u8]>) -> Option<OsString> {
+        self.suffix
+            .clone()
+            .or_else(|| {
+                self.config_files()
+                    .into_iter()
+                    .find_map(|f| f.suffix)
+                    .map(OsString::from)
+            })
+            .or_else(|| {
+                content
+                    .filter(|_| self.auto_suffix)
+                    .and_then(sniff::suffix)
+                    .map(OsString::from)
+            })
+    }
+
+    /// Find the editor this `Editor` would open. See [`get_editor`].
+    ///
+    /// [`get_editor`]: fn.get_editor.html
+    pub fn get_editor(&self) -> Result<PathBuf> {
+        self.get_editor_args().map(|(x, _)| x)
+    }
+
+    /// Describe the editor this `Editor` would open. See [`get_editor_info`].
+    ///
+    /// [`get_editor_info`]: fn.get_editor_info.html
+    pub fn get_editor_info(&self) -> Result<EditorInfo> {
+        let (editor, args) = self.get_editor_args()?;
+        Ok(EditorInfo::new(editor, args))
+    }
+
+    /// Find out the version of the editor this `Editor` would open. See [`editor_version`].
+    ///
+    /// [`editor_version`]: fn.editor_version.html
+    pub fn editor_version(&self) -> Result<Option<EditorVersion>> {
+        version::probe(&self.get_editor()?)
+    }
+
+    /// Build a [`Command`] that runs the editor this `Editor` would open. See
+    /// [`get_editor_command`].
+    ///
+    /// [`Command`]: https://doc.rust-lang.org/std/process/struct.Command.html
+    /// [`get_editor_command`]: fn.get_editor_command.html
+    pub fn get_editor_command(&self) -> Result<Command> {
+        let (editor, args) = self.get_editor_args()?;
+        let mut command = Command::new(editor);
+        command.args(args);
+        Ok(command)
+    }
+
+    /// Open the contents of a string or buffer in the editor. See [`edit`].
+    ///
+    /// [`edit`]: fn.edit.html
+    pub fn edit<S: AsRef<[u8]>>(&self, text: S) -> Result<String> {
+        self.edit_with_builder(text, &TempOptions::new())
+    }
+
+    /// Open the contents of a string or buffer in the editor using a temporary file created with
+    /// the options in `builder`. See [`edit_with_builder`].
+    ///
+    /// Options that aren't set in `builder` are taken from this `Editor`.
+    ///
+    /// [`edit_with_builder`]: fn.edit_with_builder.html
+    pub fn edit_with_builder<S: AsRef<[u8]>>(
+        &self,
+        text: S,
+        builder: &TempOptions,
+    ) -> Result<String> {
+        let text = self.template(text.as_ref())?;
+        self.edit_temp(&text, builder, |edited| self.finish_text(edited))
+    }
+
+    /// Open the contents of a string or buffer in the editor, and return the edited text unless
+    /// the user aborted. See [`edit_optional`].
+    ///
+    /// [`edit_optional`]: fn.edit_optional.html
+    pub fn edit_optional<S: AsRef<[u8]>>(&self, text: S) -> Result<Option<String>> {
+        let text = self.template(text.as_ref())?;
+        self.edit_temp(&text, &TempOptions::new(), |edited| {
+            if edited == *text {
+                return Ok(None);
+            }
+            let edited = self.finish_text(edited)?;
+            Ok(Some(edited).filter(|edited| !edited.trim().is_empty()))
+        })
+    }
+
+    /// Open a string in the editor, and return the edited text, or the string itself if it wasn't
+    /// changed. See [`edit_cow`].
+    ///
+    /// [`edit_cow`]: fn.edit_cow.html
+    pub fn edit_cow<'a>(&self, text: &'a str) -> Result<Cow<'a, str>> {
+        let template = self.template(text.as_bytes())?;
+        self.edit_temp(&template, &TempOptions::new(), |edited| {
+            // without any cleanup, unchanged bytes are the text itself (unless decoding would
+            // strip a byte order mark from it)
+            if edited == text.as_bytes() && !self.cleans_up() && !text.starts_with('\u{feff}') {
+                return Ok(Cow::Borrowed(text));
+            }
+            let edited = self.finish_text(edited)?;
+            Ok(if edited == text {
+                Cow::Borrowed(text)
+            } else {
+                Cow::Owned(edited)
+            })
+        })
+    }
+
+    /// Open the contents of a string or buffer in the editor, and return the edited text along
+    /// with details about the edit session. See [`edit_with_outcome`].
+    ///
+    /// [`edit_with_outcome`]: fn.edit_with_outcome.html
+    pub fn edit_with_outcome<S: AsRef<[u8]>>(&self, text: S) -> Result<EditOutcome> {
+        let text = self.template(text.as_ref())?;
+        self.edit_temp_run(&text, &TempOptions::new(), |edited, run| {
+            Ok(EditOutcome {
+                changed: edited != *text,
+                content: self.finish_text(edited)?,
+                run,
+            })
+        })
+    }
+
+    /// Open the contents of a string or buffer in the editor, and return the edited text along
+    /// with a line-level diff against the original. See [`edit_diff`].
+    ///
+    /// [`edit_diff`]: fn.edit_diff.html
+    pub fn edit_diff<S: AsRef<[u8]>>(&self, text: S) -> Result<EditDiff> {
+        let original = String::from_utf8_lossy(text.as_ref()).into_owned();
+        let content = self.edit(text)?;
+        Ok(EditDiff {
+            lines: diff::diff(&original, &content),
+            content,
+        })
+    }
+
+    /// Open the contents of a string or buffer in the editor and return them as raw bytes. See
+    /// [`edit_bytes`].
+    ///
+    /// [`edit_bytes`]: fn.edit_bytes.html
+    pub fn edit_bytes<B: AsRef<[u8]>>(&self, buf: B) -> Result<Vec<u8>> {
+        self.edit_bytes_with_builder(buf, &TempOptions::new())
+    }
+
+    /// Open the contents of a string or buffer in the editor using a temporary file created with
+    /// the options in `builder` and return them as raw bytes. See [`edit_bytes_with_builder`].
+    ///
+    /// Options that aren't set in `builder` are taken from this `Editor`.
+    ///
+    /// [`edit_bytes_with_builder`]: fn.edit_bytes_with_builder.html
+    pub fn edit_bytes_with_builder<B: AsRef<[u8]>>(
+        &self,
+        buf: B,
+        builder: &TempOptions,
+    ) -> Result<Vec<u8>> {
+        self.edit_temp(buf.as_ref(), builder, Ok)
+    }
+
+    /// Check whether [`Editor::finish_text`] might change edited text other than decoding it.
+    fn cleans_up(&self) -> bool {
+        self.line_endings != LineEndings::Preserve
+            || self.scissors
+            || self.strip_comments
+            || self.trim_trailing_whitespace
+            || self.collapse_trailing_newlines
+            || self.ensure_final_newline
+    }
+
+    /// Turn the edited contents of a temporary file into text, cleaned up as configured.
+    fn finish_text(&self, edited: Vec<u8>) -> Result<String> {
+        Ok(self.clean_up(self.decode(edited)?))
+    }
+
+    /// Convert the line endings of decoded edited `text`, and clean it up as configured.
+    fn clean_up(&self, text: String) -> String {
+        let mut text = self.line_endings.apply(text);
+        let prefix = self.comment_prefix.as_deref();
+        let prefix = prefix.unwrap_or(cleanup::DEFAULT_COMMENT_PREFIX);
+        if self.scissors {
+            let len = cleanup::cut_at_scissors(&text, prefix).len();
+            text.truncate(len);
+        }
+        if self.strip_comments {
+            text = cleanup::strip_comments(&text, prefix);
+        }
+        if self.trim_trailing_whitespace {
+            text = cleanup::trim_trailing_whitespace(&text);
+        }
+        if self.collapse_trailing_newlines {
+            cleanup::collapse_trailing_newlines(&mut text);
+        }
+        if self.ensure_final_newline {
+            cleanup::ensure_final_newline(&mut text);
+        }
+        text
+    }
+
+    /// Edit `buf` in a temporary file created with `options`, and turn the edited contents into
+    /// the result with `finish`.
+    fn edit_temp<T, F: FnOnce(Vec<u8>) -> Result<T>>(
+        &self,
+        buf: &[u8],
+        options: &TempOptions,
+        finish: F,
+    ) -> Result<T> {
+        self.edit_temp_run(buf, options, |edited, _| finish(edited))
+    }
+
+    /// Like [`Editor::edit_temp`], but also pass how waiting for the editor went to `finish`.
+    fn edit_temp_run<T, F: FnOnce(Vec<u8>, Run) -> Result<T>>(
+        &self,
+        buf: &[u8],
+        options: &TempOptions,
+        finish: F,
+    ) -> Result<T> {
+        #[cfg(feature = "encrypted-temp")]
+        if self.encrypt_temp {
+            return self.edit_encrypted(buf, options, finish);
+        }
+        if let Some(file) = self.memory_file(buf)? {
+            let run = self.open_all_and_wait(&[file.path()])?;
+            return finish(fs::read(file.path())?, run);
+        }
+
+        let mut file = self.temp_file(options, Some(buf))?;
+        file.write_all(buf)?;
+        if self.wait_for == WaitFor::Save || self.uses_portal() {
+            // make sure a save right away is noticed even on filesystems with coarse timestamps
+            let _ = file
+                .as_file()
+                .set_modified(SystemTime::now() - Duration::from_secs(60));
+        }
+
+        let path = file.into_temp_path();
+        let edited = self
+            .open_all_and_wait(&[&path])
+            .and_then(|run| Ok((fs::read(&path)?, run)))
+            .and_then(|(edited, run)| finish(edited, run));
+        match edited {
+            Ok(edited) => {
+                self.remove_temp(path)?;
+                Ok(edited)
+            }
+            Err(e) if self.keep_on_error && !self.secure => Err(recover::keep(path, buf, e)),
+            Err(e) => {
+                let _ = self.remove_temp(path);
+                Err(e)
+            }
+        }
+    }
+
+    /// Delete a temporary file that was edited, overwriting it first in secure mode.
+    fn remove_temp(&self, path: TempPath) -> Result<()> {
+        if self.secure {
+            secure::shred(&path)?;
+        }
+        path.close()
+    }
+
+    /// Open an existing file (or create a new one, depending on the editor's behavior) in the
+    /// editor and wait for the editor to exit. See [`edit_file`].
+    ///
+    /// [`edit_file`]: fn.edit_file.html
+    pub fn edit_file<P: AsRef<Path>>(&self, file: P) -> Result<()> {
+        let file = file.as_ref();
+        if self.leaves_own_files() {
+            return Ok(());
+        }
+        let _lock = lock::acquire(file, self.lock_policy)?;
+        self.open_and_wait(file)
+    }
+
+    /// Open several existing files (or create new ones, depending on the editor's behavior) in
+    /// one invocation of the editor and wait for the editor to exit. See [`edit_files`].
+    ///
+    /// [`edit_files`]: fn.edit_files.html
+    pub fn edit_files<P: AsRef<Path>>(&self, files: &[P]) -> Result<()> {
+        if files.is_empty() {
+            return Ok(());
+        }
+        if self.leaves_own_files() {
+            return Ok(());
+        }
+        let files: Vec<&Path> = files.iter().map(AsRef::as_ref).collect();
+        let _locks = files
+            .iter()
+            .map(|file| lock::acquire(file, self.lock_policy))
+            .collect::<Result<Vec<_>>>()?;
+        self.open_all_and_wait(&files).map(drop)
+    }
+
+    /// Open `file` in the editor and wait for the editor to exit, without locking it.
+    fn open_and_wait(&self, file: &Path) -> Result<()> {
+        self.open_all_and_wait(&[file]).map(drop)
+    }
+
+    /// Open `files` (of which there must be at least one) in one editor and wait for the editor to
+    /// exit, without locking them. With [`WaitFor::Save`], only the first file is watched.
+    fn open_all_and_wait(&self, files: &[&Path]) -> Result<Run> {
+        if let Some(content) = self.scripted_content() {
+            for file in files {
+                fs::write(file, &content)?;
+            }
+            return Ok(Run::default());
+        }
+        let before = FileState::of(files[0]);
+        let start = Instant::now();
+
+        // the portal can only open one file at a time
+        #[cfg(all(feature = "portal", target_os = "linux"))]
+        if self.uses_portal() {
+            for file in files {
+                let before = FileState::of(file);
+                portal::edit_file(file, before, self.timeout, self.cancel.as_ref())?;
+            }
+            return Ok(Run {
+                duration: start.elapsed(),
+                ..Run::default()
+            });
+        }
+
+        let mut session = self.spawn_first(files, |candidate, files| {
+            self.spawn_editor(candidate, files)
+        })?;
+        let command = Some(session.command().to_owned());
+        session.close_pipes();
+        let status = match (self.wait_for, self.timeout) {
+            (WaitFor::Exit, Some(timeout)) => {
+                session.wait_or_kill(timeout).map(|()| session.status())
+            }
+            (WaitFor::Exit, None) => session.wait().map(|()| session.status()),
+            (WaitFor::Save, timeout) => session.wait_for_save(files[0], before, timeout),
+        }?;
+        Ok(Run {
+            command,
+            status,
+            duration: start.elapsed(),
+        })
+    }
+
+    /// Open a copy of a file in the editor, and replace the file with it once the editor exits
+    /// successfully. See [`edit_file_atomic`].
+    ///
+    /// With [`Editor::keep_on_error`] enabled, a changed copy is kept if editing fails.
+    ///
+    /// [`edit_file_atomic`]: fn.edit_file_atomic.html
+    /// [`Editor::keep_on_error`]: #method.keep_on_error
+    pub fn edit_file_atomic<P: AsRef<Path>>(&self, file: P) -> Result<()> {
+        if self.leaves_own_files() {
+            return Ok(());
+        }
+        let _lock = lock::acquire(file.as_ref(), self.lock_policy)?;
+        let copy = atomic::copy(file.as_ref())?;
+        match self.open_and_wait(&copy.path) {
+            Ok(()) => {}
+            Err(e) if self.keep_on_error && !self.secure => {
+                return Err(recover::keep(copy.path, &copy.original, e));
+            }
+            Err(e) => return Err(e),
+        }
+        let edited = fs::read(&copy.path)?;
+        if edited == copy.original {
+            return copy.path.close();
+        }
+        copy.replace(edited)
+    }
+
+    /// Back up an existing file, then open it in the editor and wait for the editor to exit. See
+    /// [`edit_file_with_backup`].
+    ///
+    /// [`edit_file_with_backup`]: fn.edit_file_with_backup.html
+    pub fn edit_file_with_backup<P: AsRef<Path>>(&self, file: P) -> Result<Option<PathBuf>> {
+        let file = file.as_ref();
+        if self.leaves_own_files() {
+            return Ok(None);
+        }
+        let _lock = lock::acquire(file, self.lock_policy)?;
+        let backup = backup::create(file)?;
+        self.open_and_wait(file)?;
+        match backup {
+            Some(backup) if backup::unchanged(file, &backup) => {
+                fs::remove_file(backup)?;
+                Ok(None)
+            }
+            backup => Ok(backup),
+        }
+    }
+
+    /// Open an existing file (or create a new one, depending on the editor's behavior) in the
+    /// editor without waiting for the editor to exit. See [`edit_file_session`].
+    ///
+    /// If an editor is found but can't be started (e.g. because it is a broken wrapper script),
+    /// the next editor that would be detected is tried instead, unless a specific editor was set
+    /// with [`Editor::editor`] or [`set_default_editor`]. If none of the editors can be started,
+    /// the returned error lists every editor that was tried.
+    ///
+    /// [`edit_file_session`]: fn.edit_file_session.html
+    /// [`Editor::editor`]: struct.Editor.html#method.editor
+    /// [`set_default_editor`]: fn.set_default_editor.html
+    pub fn edit_file_session<P: AsRef<Path>>(&self, file: P) -> Result<EditorSession> {
+        self.spawn_first(&[file.as_ref()], |candidate, files| {
+            self.spawn_editor(candidate, files)
+        })
+    }
+
+    /// Start the first editor that can be started on `files` using `spawn`, as described in
+    /// [`Editor::edit_file_session`].
+    fn spawn_first<T, F>(&self, files: &[&Path], mut spawn: F) -> Result<T>
+    where
+        F: FnMut(&Candidate, &[PathBuf]) -> Result<T>,
+    {
+        if self.cancel.as_ref().is_some_and(|c| c.is_cancelled()) {
+            return Err(Cancelled.into());
+        }
+
+        let mut files: Vec<PathBuf> = files.iter().map(|&file| file.to_owned()).collect();
+        if self.current_dir.is_some() {
+            for file in files.iter_mut().filter(|file| file.is_relative()) {
+                *file = env::current_dir()?.join(&file);
+            }
+        }
+
+        if let Some(command) = self.command() {
+            return spawn(&self.resolve(&command)?, &files);
+        }
+
+        let mut failures = Vec::new();
+        let mut skipped = Vec::new();
+        for (source, command) in self.candidate_commands() {
+            let candidate = match self.resolve(&command) {
+                Ok(candidate) => candidate,
+                Err(_) => continue,
+            };
+            if let Some(reason) = self.skip_reason(&candidate.editor) {
+                skipped.push(format!("'{}' ({})", candidate.editor.display(), reason));
+                continue;
+            }
+            match spawn(&candidate, &files) {
+                Ok(spawned) => {
+                    if self.remember_editor && source.is_guess() {
+                        remember::remember(&command);
+                    }
+                    return Ok(spawned);
+                }
+                Err(e) => failures.push((candidate.editor, e)),
+            }
+        }
+
+        if failures.is_empty() && !skipped.is_empty() {
+            skipped.dedup();
+            return Err(Error::new(
+                ErrorKind::NotFound,
+                format!("no usable editor found; skipped {}", skipped.join(", ")),
+            ));
+        } else if failures.is_empty() {
+            return Err(self.not_found());
+        }
+        let kind = if failures
+            .iter()
+            .all(|(_, e)| e.kind() == ErrorKind::NotFound)
+        {
+            ErrorKind::NotFound
+        } else {
+            ErrorKind::Other
+        };
+        let tried: Vec<String> = failures
+            .iter()
+            .map(|(editor, e)| format!("'{}' ({})", editor.to_string_lossy(), e))
+            .collect();
+        Err(Error::new(
+            kind,
+            format!("couldn't start any editor; tried {}", tried.join(", ")),
+        ))
+    }
+
+    /// Check whether files should be opened through the desktop portal.
+    fn uses_portal(&self) -> bool {
+        #[cfg(all(feature = "portal", target_os = "linux"))]
+        if self.portal {
+            return portal::in_sandbox();
+        }
+        false
+    }
+
+    /// Find the editor `command` refers to.
+    ///
+    /// When interpreting commands with the shell, commands that use shell syntax are taken as-is,
+    /// since only the shell knows what they refer to.
+    fn resolve(&self, command: &OsStr) -> Result<Candidate> {
+        let found = get_full_editor_cmd(command);
+        if !self.shell {
+            let (editor, args) = found?;
+            return Ok(Candidate {
+                editor,
+                args,
+                shell_command: None,
+            });
+        }
+
+        let editor = match found {
+            Ok((editor, _)) => editor,
+            Err(_) if has_shell_syntax(command) => parse_editor_command_os(command)
+                .map(|(editor, _)| editor)
+                .unwrap_or_else(|_| PathBuf::from(command)),
+            Err(e) => return Err(e),
+        };
+        Ok(Candidate {
+            editor,
+            args: Vec::new(),
+            shell_command: Some(command.to_owned()),
+        })
+    }
+
+    /// Build the command to run `candidate` on `files`, along with a description of it for error
+    /// messages and the exit codes to accept besides success (see [`session::check_status`]).
+    fn editor_command(
+        &self,
+        candidate: &Candidate,
+        files: &[PathBuf],
+    ) -> (Command, String, Option<Vec<i32>>) {
+        let Candidate {
+            editor,
+            args,
+            shell_command,
+        } = candidate;
+
+        // Windows programs in WSL can't open Linux paths
+        let files: Vec<PathBuf> = if wsl::is_windows_program(editor) {
+            files
+                .iter()
+                .map(|file| wsl::windows_path(file).unwrap_or_else(|| file.clone()))
+                .collect()
+        } else {
+            files.to_vec()
+        };
+
+        let mut command = match shell_command {
+            Some(shell_command) => shell_command_for(shell_command, &files),
+            None => {
+                let mut command = Command::new(editor);
+                // only the first file is opened at the line
+                command
+                    .args(args)
+                    .args(registry::file_args(editor, &files[0], self.line))
+                    .args(&files[1..]);
+                command
+            }
+        };
+        if self.multiplexer_popup
+            && !self.pty
+            && gui::is_gui(editor) != Some(true)
+            && multiplexer::detected()
+        {
+            let dir = self.current_dir.clone().or_else(|| env::current_dir().ok());
+            command = multiplexer::wrap(command, dir.as_deref().unwrap_or(Path::new(".")));
+        } else if self.needs_terminal_emulator(editor) {
+            command = terminal::wrap(command);
+        }
+        command
+            .stdin(self.stdin)
+            .stdout(self.stdout)
+            .stderr(self.stderr);
+
+        if let Some(dir) = &self.current_dir {
+            command.current_dir(dir);
+        }
+
+        if self.env_clear {
+            command.env_clear();
+        }
+        if self.root_policy == RootPolicy::RunGuiAsUser && gui::is_gui(editor) == Some(true) {
+            if let Some(user) = self.sudo_user() {
+                sudo::run_as(&mut command, &user);
+            }
+        }
+        for (key, val) in &self.envs {
+            match val {
+                Some(val) => command.env(key, val),
+                None => command.env_remove(key),
+            };
+        }
+
+        let files: Vec<_> = files.iter().map(|file| file.to_string_lossy()).collect();
+        let file = files.join(" ");
+        let description = if let Some(shell_command) = shell_command {
+            format!("{} {}", shell_command.to_string_lossy(), file)
+        } else if args.is_empty() {
+            format!("{} {}", editor.to_string_lossy(), file)
+        } else {
+            let args: Vec<_> = args.iter().map(|arg| arg.to_string_lossy()).collect();
+            format!("{} {} {}", editor.to_string_lossy(), args.join(" "), file)
+        };
+
+        let accepted_codes = self
+            .exit_code_policy
+            .accepted_codes(editor, &self.accepted_exit_codes);
+
+        (command, description, accepted_codes)
+    }
+
+    fn spawn_editor(&self, candidate: &Candidate, files: &[PathBuf]) -> Result<EditorSession> {
+        let (mut command, description, accepted_codes) = self.editor_command(candidate, files);
+
+        let signals = if self.forward_signals {
+            Some(SignalGuard::install()?)
+        } else {
+            None
+        };
+
+        let pty = if self.pty {
+            let pty = Pty::open()?;
+            pty.attach(&mut command)?;
+            Some(pty)
+        } else {
+            None
+        };
+
+        EditorSession::spawn(
+            &mut command,
+            description,
+            accepted_codes,
+            signals,
+            pty,
+            self.cancel.clone(),
+        )
+    }
+}
+
+/// An editor to try running, as found by [`Editor::resolve`].
+struct Candidate {
+    /// The editor program (if the command uses shell syntax, just its first word).
+    editor: PathBuf,
+    args: Vec<OsString>,
+    /// The whole command, if it is to be interpreted by the shell.
+    shell_command: Option<OsString>,
+}
+
+fn has_shell_syntax(command: &OsStr) -> bool {
+    command
+        .to_string_lossy()
+        .contains(|c| "$`~(){}[];|&<>*?%^".contains(c))
+}
+
+// like git, pass the files as positional parameters so they don't need quoting
+#[cfg(not(target_os = "windows"))]
+fn shell_command_for(command: &OsStr, files: &[PathBuf]) -> Command {
+    let mut script = command.to_owned();
+    script.push(r#" "$@""#);
+    let mut shell = Command::new("sh");
+    shell.arg("-c").arg(script).arg(command).args(files);
+    shell
+}
+
+// quote `word` for sh, for programs that take commands to run as a single string
+#[cfg(not(target_os = "windows"))]
+fn shell_quote(word: &OsStr) -> String {
+    format!("'{}'", word.to_string_lossy().replace('\'', r"'\''"))
+}
+
+#[cfg(target_os = "windows")]
+fn shell_command_for(command: &OsStr, files: &[PathBuf]) -> Command {
+    use std::os::windows::process::CommandExt;
+
+    // with /S, cmd strips the outer quotes and runs everything between them as-is
+    let mut line = OsString::from(r#"/S /C ""#);
+    line.push(command);
+    for file in files {
+        line.push(r#" ""#);
+        line.push(file);
+        line.push(r#"""#);
+    }
+    line.push(r#"""#);
+    let mut shell = Command::new("cmd.exe");
+    shell.raw_arg(line);
+    shell
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(s: &str) -> Result<Vec<OsString>> {
+        split_words(OsStr::new(s))
+    }
+
+    #[test]
+    fn split_words_on_whitespace() {
+        assert_eq!(words(" code \t-w\n file ").unwrap(), ["code", "-w", "file"]);
+        assert!(words("").unwrap().is_empty());
+        assert!(words(" \t\r\n").unwrap().is_empty());
+    }
+
+    #[test]
+    fn split_words_with_quotes() {
+        assert_eq!(
+            words(r#"'/My Apps/ed' "a b"c '' """#).unwrap(),
+            ["/My Apps/ed", "a bc", "", ""]
+        );
+        assert_eq!(words(r#"'"' "'""#).unwrap(), ["\"", "'"]);
+        assert_eq!(
+            words("'unterminated").unwrap_err().kind(),
+            ErrorKind::InvalidInput
+        );
+        assert_eq!(
+            words("\"unterminated").unwrap_err().kind(),
+            ErrorKind::InvalidInput
+        );
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn split_words_with_escapes() {
+        assert_eq!(words(r"a\ b c\\d").unwrap(), ["a b", r"c\d"]);
+        // only some characters can be escaped inside double quotes
+        assert_eq!(words(r#""\"\\\$\`\n""#).unwrap(), [r#""\$`\n"#]);
+        // and nothing can be inside single quotes
+        assert_eq!(words(r"'a\ b'").unwrap(), [r"a\ b"]);
+        // escaped line breaks are removed
+        assert_eq!(words("a\\\nb \"c\\\nd\"").unwrap(), ["ab", "cd"]);
+        assert_eq!(
+            words("trailing\\").unwrap_err().kind(),
+            ErrorKind::InvalidInput
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn split_words_without_escapes() {
+        assert_eq!(
+            words(r"C:\Program\ Files\ed.exe").unwrap(),
+            [r"C:\Program\", r"Files\ed.exe"]
+        );
+    }
+
+    #[test]
+    fn parse_editor_command_splits_program() {
+        let (program, args) = parse_editor_command("'my editor' --wait").unwrap();
+        assert_eq!(program, Path::new("my editor"));
+        assert_eq!(args, ["--wait"]);
+        assert!(parse_editor_command("  ").is_err());
+    }
+
+    #[test]
+    fn split_editors_var_in_order() {
+        let sep = EDITORS_VAR_SEPARATOR;
+        let value = format!(
+            " nvim {0}vim -u NONE{0}{0} {0}nano{0}nvim{0}vim -u NONE",
+            sep
+        );
+        assert_eq!(split_editors_var(&value), ["nvim", "vim -u NONE", "nano"]);
+        assert!(split_editors_var("").is_empty());
+        assert!(split_editors_var(&format!(" {0} {0}", sep)).is_empty());
+    }
+
+    #[test]
+    fn place_editors_var() {
+        let env = || vec!["VISUAL", "EDITOR"];
+        let editors = || vec!["nvim", "nano"];
+        assert_eq!(
+            EditorsVar::BeforeEnvVars.place(env(), editors()),
+            ["nvim", "nano", "VISUAL", "EDITOR"]
+        );
+        assert_eq!(
+            EditorsVar::AfterEnvVars.place(env(), editors()),
+            ["VISUAL", "EDITOR", "nvim", "nano"]
+        );
+        assert_eq!(
+            EditorsVar::Ignore.place(env(), editors()),
+            ["VISUAL", "EDITOR"]
+        );
+    }
+
+    #[test]
+    fn order_tool_env_vars() {
+        let default = || ENV_VARS.to_vec();
+        assert_eq!(
+            env_var_order(default(), false, false, false),
+            ["VISUAL", "EDITOR"]
+        );
+        assert_eq!(
+            env_var_order(default(), false, true, false),
+            ["SUDO_EDITOR", "VISUAL", "EDITOR"]
+        );
+        assert_eq!(
+            env_var_order(default(), false, false, true),
+            ["VISUAL", "EDITOR", "GIT_EDITOR"]
+        );
+        assert_eq!(
+            env_var_order(default(), true, true, true),
+            ["SUDO_EDITOR", "EDITOR", "VISUAL", "GIT_EDITOR"]
+        );
+    }
+
+    #[test]
+    fn order_visual_after_editor() {
+        assert_eq!(
+            env_var_order(vec!["VISUAL", "EDITOR"], true, false, false),
+            ["EDITOR", "VISUAL"]
+        );
+        // only moves VISUAL back, and only if EDITOR is read at all
+        assert_eq!(
+            env_var_order(
+                vec!["VISUAL", "MY_EDITOR", "EDITOR", "OTHER"],
+                true,
+                false,
+                false
+            ),
+            ["MY_EDITOR", "EDITOR", "VISUAL", "OTHER"]
+        );
+        assert_eq!(
+            env_var_order(vec!["EDITOR", "VISUAL"], true, false, false),
+            ["EDITOR", "VISUAL"]
+        );
+        assert_eq!(
+            env_var_order(vec!["VISUAL"], true, false, false),
+            ["VISUAL"]
+        );
+    }
+
+    fn assert_unique(list: &[&str]) {
+        for (i, command) in list.iter().enumerate() {
+            assert!(!list[..i].contains(command), "{} is listed twice", command);
+        }
+    }
+
+    #[test]
+    fn platform_lists_are_unique() {
+        for list in [
+            HARDCODED_CLI_NAMES,
+            HARDCODED_GUI_NAMES,
+            GENERIC_OPENERS,
+            HARDCODED_PATHS,
+        ] {
+            assert_unique(list);
+        }
+        assert_unique(&[HARDCODED_CLI_NAMES, HARDCODED_GUI_NAMES].concat());
+    }
+
+    #[test]
+    fn platform_lists_match_registry() {
+        let known = |command: &str| {
+            let (program, args) = parse_editor_command(command).unwrap();
+            (registry::lookup(&program), program, args)
+        };
+        for &command in HARDCODED_CLI_NAMES {
+            if let (Some(editor), _, _) = known(command) {
+                assert!(!editor.gui, "{} is a GUI editor", command);
+            }
+        }
+        for &command in HARDCODED_GUI_NAMES {
+            if let (Some(editor), _, _) = known(command) {
+                assert!(editor.gui, "{} is a terminal editor", command);
+            }
+        }
+        // every editor waits for the file to be closed (once it's given any wait arguments it
+        // needs), unlike generic openers
+        for &command in HARDCODED_CLI_NAMES.iter().chain(HARDCODED_GUI_NAMES) {
+            let (_, program, args) = known(&registry::with_wait_args(command));
+            let args: Vec<OsString> = args.into_iter().map(Into::into).collect();
+            assert!(
+                registry::blocks(&program, &args),
+                "{} doesn't wait",
+                command
+            );
+        }
+        for &command in GENERIC_OPENERS {
+            assert!(
+                known(command).0.is_some(),
+                "{} isn't a known opener",
+                command
+            );
+        }
+        for &command in HARDCODED_PATHS {
+            assert!(
+                known(command).1.is_absolute(),
+                "{} isn't a full path",
+                command
+            );
+        }
     }
 }
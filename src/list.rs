@@ -0,0 +1,183 @@
+//! Editing lists of items like `git rebase -i` does with commits.
+
+use crate::{cleanup, Editor};
+use std::io::{Error, ErrorKind, Result};
+
+/// An item of a list edited with [`edit_list`], as the user left it.
+///
+/// [`edit_list`]: fn.edit_list.html
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ListEntry {
+    pub(crate) index: usize,
+    pub(crate) action: Option<String>,
+    pub(crate) text: String,
+}
+
+impl ListEntry {
+    /// Get the index of the item in the original list.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Get the action word at the start of the item's line, if it has one.
+    pub fn action(&self) -> Option<&str> {
+        self.action.as_deref()
+    }
+
+    /// Get the text after the item's number, which the user may have changed.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+/// Render `items` one per line, numbered from 1 and starting with `action` (if any), followed by
+/// instructions in comments starting with `prefix`.
+fn render<S: AsRef<str>>(items: &[S], action: Option<&str>, prefix: &str) -> String {
+    let mut text = String::new();
+    for (i, item) in items.iter().enumerate() {
+        if let Some(action) = action {
+            text.push_str(action);
+            text.push(' ');
+        }
+        text.push_str(&format!("{} {}\n", i + 1, item.as_ref()));
+    }
+    text.push('\n');
+    for line in [
+        "Lines can be reordered to change the order of the items, or removed to drop them.",
+        "The word before the number of an item is its action, and the text after it can be",
+        "edited. Lines like this one are ignored, and removing every item aborts.",
+    ] {
+        text.push_str(&format!("{} {}\n", prefix, line));
+    }
+    text
+}
+
+/// Parse the edited list of `len` items back into entries, or describe everything that's wrong
+/// with it, one problem per line.
+fn parse(text: &str, len: usize, prefix: &str) -> std::result::Result<Vec<ListEntry>, String> {
+    let mut entries = Vec::new();
+    let mut errors = Vec::new();
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with(prefix) {
+            continue;
+        }
+        let (first, rest) = split_word(line);
+        let (action, number, text) = match first.parse::<usize>() {
+            Ok(number) => (None, Ok(number), rest),
+            Err(_) => {
+                let (number, text) = split_word(rest);
+                (Some(first), number.parse::<usize>(), text)
+            }
+        };
+        match number {
+            Ok(number) if (1..=len).contains(&number) => entries.push(ListEntry {
+                index: number - 1,
+                action: action.map(str::to_owned),
+                text: text.to_owned(),
+            }),
+            Ok(number) => errors.push(format!("'{}': there's no item {}", line, number)),
+            Err(_) => errors.push(format!("'{}': expected an item number", line)),
+        }
+    }
+    if errors.is_empty() {
+        Ok(entries)
+    } else {
+        Err(errors.join("\n"))
+    }
+}
+
+/// Split the first word off `line`, and trim the whitespace after it.
+fn split_word(line: &str) -> (&str, &str) {
+    match line.split_once(char::is_whitespace) {
+        Some((word, rest)) => (word, rest.trim_start()),
+        None => (line, ""),
+    }
+}
+
+impl Editor {
+    /// Open a list of items in the editor, and return them in the order the user left them in,
+    /// with their actions, or `None` if the user aborted. See [`edit_list`].
+    ///
+    /// [`edit_list`]: fn.edit_list.html
+    pub fn edit_list<S: AsRef<str>>(
+        &self,
+        items: &[S],
+        action: Option<&str>,
+    ) -> Result<Option<Vec<ListEntry>>> {
+        // every item has to fit on its line, or it couldn't be told apart from the next one
+        if let Some(i) = items
+            .iter()
+            .position(|item| item.as_ref().contains(&['\n', '\r'][..]))
+        {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("item {} of the list has a line break", i + 1),
+            ));
+        }
+        let prefix = self.comment_prefix.as_deref();
+        let prefix = prefix.unwrap_or(cleanup::DEFAULT_COMMENT_PREFIX);
+        let text = render(items, action, prefix);
+        let entries = self.edit_until_valid(text.as_bytes(), false, |text| {
+            parse(text, items.len(), prefix)
+        })?;
+        Ok(entries.filter(|entries| !entries.is_empty()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(index: usize, action: Option<&str>, text: &str) -> ListEntry {
+        ListEntry {
+            index,
+            action: action.map(str::to_owned),
+            text: text.to_owned(),
+        }
+    }
+
+    #[test]
+    fn render_items() {
+        let text = render(&["a", "b c"], Some("pick"), "#");
+        assert!(text.starts_with("pick 1 a\npick 2 b c\n\n# "));
+        assert!(render(&["a"], None, "//").starts_with("1 a\n\n// "));
+    }
+
+    #[test]
+    fn parse_with_actions() {
+        let text = "pick 2 b\n# comment\n\n  drop   1  a, annotated  \n";
+        assert_eq!(
+            parse(text, 2, "#").unwrap(),
+            [
+                entry(1, Some("pick"), "b"),
+                entry(0, Some("drop"), "a, annotated")
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_without_actions() {
+        assert_eq!(
+            parse("3 c\n1\n1 again\n", 3, "#").unwrap(),
+            [
+                entry(2, None, "c"),
+                entry(0, None, ""),
+                entry(0, None, "again")
+            ]
+        );
+        assert!(parse("# nothing\n", 3, "#").unwrap().is_empty());
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            parse("pick 0 a\n4 d\n", 3, "#").unwrap_err(),
+            "'pick 0 a': there's no item 0\n'4 d': there's no item 4"
+        );
+        assert_eq!(
+            parse("squash\npick b\n", 3, "#").unwrap_err(),
+            "'squash': expected an item number\n'pick b': expected an item number"
+        );
+    }
+}
@@ -0,0 +1,259 @@
+//! Locking files while they're being edited, so two edit sessions don't overwrite each other's
+//! changes.
+
+use crate::session::POLL_INTERVAL;
+use std::{
+    ffi::OsString,
+    fs::{self, File, OpenOptions},
+    io::{Error, ErrorKind, Result},
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// What to do when a file is already being edited by another process, as told by a lock file
+/// next to it. See [`Editor::lock`].
+///
+/// [`Editor::lock`]: struct.Editor.html#method.lock
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LockPolicy {
+    /// Don't lock files, so nothing stops two processes from editing the same file at once (with
+    /// the last one to save overwriting the changes of the other). This is the default.
+    #[default]
+    None,
+    /// Fail with an [`ErrorKind::WouldBlock`] error.
+    ///
+    /// [`ErrorKind::WouldBlock`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.WouldBlock
+    Fail,
+    /// Wait for the other process to finish editing the file for up to the given time, then fail
+    /// with an [`ErrorKind::TimedOut`] error.
+    ///
+    /// [`ErrorKind::TimedOut`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.TimedOut
+    Wait(Duration),
+}
+
+/// A lock on a file being edited, which is released (and its lock file deleted) when dropped.
+pub(crate) struct Lock {
+    file: File,
+    #[cfg_attr(not(unix), allow(dead_code))]
+    path: PathBuf,
+}
+
+impl Drop for Lock {
+    fn drop(&mut self) {
+        // delete the lock file before unlocking it, so processes waiting on it notice it's stale.
+        // Elsewhere, there's no (stable) way for them to notice, so it's left in place.
+        #[cfg(unix)]
+        let _ = fs::remove_file(&self.path);
+        unlock(&self.file);
+    }
+}
+
+/// Lock `file` according to `policy`.
+pub(crate) fn acquire(file: &Path, policy: LockPolicy) -> Result<Option<Lock>> {
+    if policy == LockPolicy::None {
+        return Ok(None);
+    }
+    let start = Instant::now();
+    loop {
+        match (try_acquire(file)?, policy) {
+            (Some(lock), _) => return Ok(Some(lock)),
+            (None, LockPolicy::Wait(timeout)) if start.elapsed() < timeout => {
+                thread::sleep(POLL_INTERVAL.min(timeout - start.elapsed()));
+            }
+            (None, _) => return Err(busy(file, policy)),
+        }
+    }
+}
+
+/// Lock `file`, unless another process already has.
+pub(crate) fn try_acquire(file: &Path) -> Result<Option<Lock>> {
+    let path = lock_path(file)?;
+    loop {
+        let lock = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)?;
+        if !try_lock(&lock)? {
+            return Ok(None);
+        }
+        // if the process that had the lock deleted the lock file in the meantime, lock the one
+        // that replaced it instead
+        if is_same_file(&lock, &path) {
+            return Ok(Some(Lock { file: lock, path }));
+        }
+    }
+}
+
+/// Get the error for when `file` couldn't be locked with `policy`.
+pub(crate) fn busy(file: &Path, policy: LockPolicy) -> Error {
+    let kind = match policy {
+        LockPolicy::Wait(_) => ErrorKind::TimedOut,
+        _ => ErrorKind::WouldBlock,
+    };
+    Error::new(
+        kind,
+        format!("'{}' is already being edited", file.display()),
+    )
+}
+
+/// Get the path of the lock file for `file`, like `.config.toml.lock` for `config.toml`.
+fn lock_path(file: &Path) -> Result<PathBuf> {
+    // make sure every path to the same file is locked with the same lock file, even if it
+    // doesn't exist yet (in which case only the directory it would be in can be resolved)
+    let file = match fs::canonicalize(file) {
+        Ok(file) => file,
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            let dir = match file.parent() {
+                Some(dir) if !dir.as_os_str().is_empty() => dir,
+                _ => Path::new("."),
+            };
+            fs::canonicalize(dir)?.join(file.file_name().unwrap_or_default())
+        }
+        Err(e) => return Err(e),
+    };
+    let mut name = OsString::from(".");
+    name.push(file.file_name().unwrap_or_default());
+    name.push(".lock");
+    Ok(file.with_file_name(name))
+}
+
+/// Lock `file` exclusively, returning `false` if another process already has.
+#[cfg(unix)]
+fn try_lock(file: &File) -> Result<bool> {
+    use std::os::unix::io::AsRawFd;
+
+    if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } == 0 {
+        return Ok(true);
+    }
+    match Error::last_os_error() {
+        e if e.kind() == ErrorKind::WouldBlock => Ok(false),
+        e => Err(e),
+    }
+}
+
+#[cfg(unix)]
+fn unlock(file: &File) {
+    use std::os::unix::io::AsRawFd;
+
+    unsafe {
+        libc::flock(file.as_raw_fd(), libc::LOCK_UN);
+    }
+}
+
+#[cfg(windows)]
+mod windows {
+    use std::{ffi::c_void, ptr};
+
+    pub const LOCKFILE_FAIL_IMMEDIATELY: u32 = 0x1;
+    pub const LOCKFILE_EXCLUSIVE_LOCK: u32 = 0x2;
+    pub const ERROR_LOCK_VIOLATION: i32 = 33;
+
+    #[repr(C)]
+    pub struct Overlapped {
+        internal: usize,
+        internal_high: usize,
+        offset: u32,
+        offset_high: u32,
+        event: *mut c_void,
+    }
+
+    impl Overlapped {
+        /// Get an `OVERLAPPED` for locking a file from its start.
+        pub fn new() -> Self {
+            Overlapped {
+                internal: 0,
+                internal_high: 0,
+                offset: 0,
+                offset_high: 0,
+                event: ptr::null_mut(),
+            }
+        }
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        pub fn LockFileEx(
+            file: *mut c_void,
+            flags: u32,
+            reserved: u32,
+            bytes_low: u32,
+            bytes_high: u32,
+            overlapped: *mut Overlapped,
+        ) -> i32;
+        pub fn UnlockFileEx(
+            file: *mut c_void,
+            reserved: u32,
+            bytes_low: u32,
+            bytes_high: u32,
+            overlapped: *mut Overlapped,
+        ) -> i32;
+    }
+}
+
+/// Lock `file` exclusively, returning `false` if another process already has.
+#[cfg(windows)]
+fn try_lock(file: &File) -> Result<bool> {
+    use std::os::windows::io::AsRawHandle;
+    use windows::*;
+
+    // lock the whole file, however long it gets
+    let flags = LOCKFILE_EXCLUSIVE_LOCK | LOCKFILE_FAIL_IMMEDIATELY;
+    let mut overlapped = Overlapped::new();
+    let handle = file.as_raw_handle().cast();
+    if unsafe { LockFileEx(handle, flags, 0, u32::MAX, u32::MAX, &mut overlapped) } != 0 {
+        return Ok(true);
+    }
+    match Error::last_os_error() {
+        e if e.raw_os_error() == Some(ERROR_LOCK_VIOLATION) => Ok(false),
+        e => Err(e),
+    }
+}
+
+#[cfg(windows)]
+fn unlock(file: &File) {
+    use std::os::windows::io::AsRawHandle;
+    use windows::*;
+
+    let mut overlapped = Overlapped::new();
+    let handle = file.as_raw_handle().cast();
+    unsafe {
+        UnlockFileEx(handle, 0, u32::MAX, u32::MAX, &mut overlapped);
+    }
+}
+
+#[cfg(unix)]
+fn is_same_file(lock: &File, path: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    match (lock.metadata(), fs::metadata(path)) {
+        (Ok(a), Ok(b)) => a.dev() == b.dev() && a.ino() == b.ino(),
+        _ => false,
+    }
+}
+
+// lock files are never deleted elsewhere
+#[cfg(not(unix))]
+fn is_same_file(_lock: &File, _path: &Path) -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn lock_path_of_missing_file_is_canonical() {
+        let dir = fs::canonicalize(env::temp_dir()).unwrap();
+        let direct = dir.join("edit-lock-test-missing.txt");
+        let roundabout = dir.join(".").join("edit-lock-test-missing.txt");
+        assert_eq!(
+            lock_path(&direct).unwrap(),
+            dir.join(".edit-lock-test-missing.txt.lock")
+        );
+        assert_eq!(lock_path(&roundabout).unwrap(), lock_path(&direct).unwrap());
+    }
+}
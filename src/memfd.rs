@@ -0,0 +1,68 @@
+//! Editing text in anonymous in-memory files on Linux, so it's never written to disk.
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use std::{fs, io::Write, os::unix::io::FromRawFd, path::PathBuf, process};
+use std::{fs::File, io::Result, path::Path};
+
+/// An anonymous file that only exists in memory, created with `memfd_create`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub(crate) struct MemFile {
+    // kept open so the file isn't freed while it's being edited
+    file: File,
+    path: PathBuf,
+}
+
+// there are no in-memory files elsewhere
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub(crate) enum MemFile {}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl MemFile {
+    /// Get the open file.
+    pub(crate) fn file(&self) -> &File {
+        &self.file
+    }
+
+    /// Get a path editors can open the file with.
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+impl MemFile {
+    pub(crate) fn file(&self) -> &File {
+        match *self {}
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        match *self {}
+    }
+}
+
+/// Create an in-memory file containing `content`, if the platform supports it.
+///
+/// The file is opened by editors through `/proc/<pid>/fd/<fd>` of this process rather than
+/// `/proc/self/...`, so editors that hand the path off to another process (like a server) can open
+/// it too.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub(crate) fn create(content: &[u8]) -> Result<Option<MemFile>> {
+    let fd = unsafe { libc::memfd_create(b"edit\0".as_ptr().cast(), libc::MFD_CLOEXEC) };
+    if fd < 0 {
+        // e.g. ENOSYS on kernels before 3.17, or EPERM when blocked by a seccomp filter
+        return Ok(None);
+    }
+    let mut file = unsafe { File::from_raw_fd(fd) };
+    file.write_all(content)?;
+    let path = PathBuf::from(format!("/proc/{}/fd/{}", process::id(), fd));
+    if fs::metadata(&path).is_err() {
+        // /proc isn't mounted
+        return Ok(None);
+    }
+    Ok(Some(MemFile { file, path }))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub(crate) fn create(_content: &[u8]) -> Result<Option<MemFile>> {
+    Ok(None)
+}
@@ -0,0 +1,70 @@
+//! Running editors in a floating pane of the terminal multiplexer (tmux or zellij) this is running
+//! in, so they don't fight with this program over the terminal.
+
+#[cfg(not(target_os = "windows"))]
+use crate::{get_full_editor_cmd, shell_quote};
+#[cfg(not(target_os = "windows"))]
+use std::ffi::OsStr;
+use std::{env, path::Path, process::Command};
+
+// `zellij run` doesn't wait for the command to exit, so the command is run in a shell that writes
+// its exit status to a FIFO when it exits (even if the pane is closed), which is waited on
+#[cfg(not(target_os = "windows"))]
+static ZELLIJ_SCRIPT: &str = r#"zellij=$1 dir=$2 pane=$3; shift 3
+fifo=$(mktemp -u) && mkfifo "$fifo" || exit
+if ! "$zellij" run --floating --close-on-exit --cwd "$dir" -- sh -c "$pane" "$fifo" "$@"; then
+    rm -f "$fifo"
+    exit 1
+fi
+read -r status < "$fifo"
+rm -f "$fifo"
+exit "$status""#;
+
+#[cfg(not(target_os = "windows"))]
+static ZELLIJ_PANE_SCRIPT: &str = r#"trap 'echo $? > "$0"' EXIT; trap 'exit 129' HUP; "$@""#;
+
+/// Check whether this is running inside tmux or zellij.
+pub(crate) fn detected() -> bool {
+    ["TMUX", "ZELLIJ"]
+        .iter()
+        .any(|var| env::var_os(var).is_some_and(|v| !v.is_empty()))
+}
+
+/// Make `command` run in a popup (with tmux) or floating pane (with zellij) in `dir`, and wait for
+/// it to exit. If this isn't running in either, or it can't be found, `command` is returned
+/// unchanged.
+#[cfg(not(target_os = "windows"))]
+pub(crate) fn wrap(command: Command, dir: &Path) -> Command {
+    let words = Some(command.get_program())
+        .into_iter()
+        .chain(command.get_args());
+    if env::var_os("TMUX").is_some_and(|v| !v.is_empty()) {
+        if let Ok((tmux, _)) = get_full_editor_cmd(OsStr::new("tmux")) {
+            // the popup runs a shell command, and is closed when it exits
+            let line: Vec<String> = words.map(shell_quote).collect();
+            let mut wrapped = Command::new(tmux);
+            wrapped
+                .args(["display-popup", "-E", "-w", "80%", "-h", "80%", "-d"])
+                .arg(dir)
+                .arg(line.join(" "));
+            return wrapped;
+        }
+    } else if env::var_os("ZELLIJ").is_some_and(|v| !v.is_empty()) {
+        if let Ok((zellij, _)) = get_full_editor_cmd(OsStr::new("zellij")) {
+            let mut wrapped = Command::new("sh");
+            wrapped
+                .args(["-c", ZELLIJ_SCRIPT, "sh"])
+                .arg(zellij)
+                .arg(dir)
+                .arg(ZELLIJ_PANE_SCRIPT)
+                .args(words);
+            return wrapped;
+        }
+    }
+    command
+}
+
+#[cfg(target_os = "windows")]
+pub(crate) fn wrap(command: Command, _dir: &Path) -> Command {
+    command
+}
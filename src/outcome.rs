@@ -0,0 +1,62 @@
+//! Details about how an edit session went, for logging and the like.
+
+use std::{process::ExitStatus, time::Duration};
+
+/// The result of editing text with [`Editor::edit_with_outcome`]: the edited text, along with
+/// details about the edit session.
+///
+/// [`Editor::edit_with_outcome`]: struct.Editor.html#method.edit_with_outcome
+#[derive(Clone, Debug)]
+pub struct EditOutcome {
+    pub(crate) content: String,
+    pub(crate) changed: bool,
+    pub(crate) run: Run,
+}
+
+impl EditOutcome {
+    /// Get the edited text.
+    pub fn content(&self) -> &str {
+        &self.content
+    }
+
+    /// Get the edited text, consuming the outcome.
+    pub fn into_content(self) -> String {
+        self.content
+    }
+
+    /// Check whether the user changed the text, i.e. whether the file they edited was saved with
+    /// different contents than the template (before any cleanup, like removing comments).
+    pub fn changed(&self) -> bool {
+        self.changed
+    }
+
+    /// Get the command the editor was run with, including the file, or `None` if no editor was run
+    /// (e.g. since [`Editor::noninteractive_content`] was set, or the file was opened through the
+    /// desktop portal).
+    ///
+    /// [`Editor::noninteractive_content`]: struct.Editor.html#method.noninteractive_content
+    pub fn command(&self) -> Option<&str> {
+        self.run.command.as_deref()
+    }
+
+    /// Get the exit status of the editor, or `None` if no editor was run or it was still running
+    /// when editing was done (with [`WaitFor::Save`]).
+    ///
+    /// [`WaitFor::Save`]: enum.WaitFor.html#variant.Save
+    pub fn status(&self) -> Option<ExitStatus> {
+        self.run.status
+    }
+
+    /// Get how long the editor was open for.
+    pub fn duration(&self) -> Duration {
+        self.run.duration
+    }
+}
+
+/// How waiting for an editor went.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Run {
+    pub(crate) command: Option<String>,
+    pub(crate) status: Option<ExitStatus>,
+    pub(crate) duration: Duration,
+}
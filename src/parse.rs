@@ -0,0 +1,170 @@
+//! Parsing edited text into values, and the error for when that fails.
+
+use crate::{cleanup, Editor, TempOptions};
+use std::{
+    error, fmt,
+    io::{Error, ErrorKind, Result},
+    str::FromStr,
+};
+
+/// The error for when edited text couldn't be parsed, which has the text so it can be opened in
+/// the editor again instead of making the user start over.
+///
+/// It's returned as an [`io::Error`] of kind [`ErrorKind::InvalidData`], and can be retrieved
+/// with [`io::Error::get_ref`] (or [`io::Error::into_inner`] to take the text):
+///
+/// ```rust,ignore
+/// use edit::EditParseError;
+///
+/// match edit::edit_with_parser(&template, |text| text.trim().parse::<u16>()) {
+///     Ok(port) => println!("using port {}", port),
+///     Err(e) => match e.get_ref().and_then(|e| e.downcast_ref::<EditParseError>()) {
+///         Some(parse_error) => eprintln!("{}; you wrote:\n{}", parse_error, parse_error.text()),
+///         None => return Err(e),
+///     },
+/// }
+/// ```
+///
+/// [`io::Error`]: https://doc.rust-lang.org/std/io/struct.Error.html
+/// [`ErrorKind::InvalidData`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidData
+/// [`io::Error::get_ref`]: https://doc.rust-lang.org/std/io/struct.Error.html#method.get_ref
+/// [`io::Error::into_inner`]: https://doc.rust-lang.org/std/io/struct.Error.html#method.into_inner
+#[derive(Debug)]
+pub struct EditParseError {
+    text: String,
+    source: Box<dyn error::Error + Send + Sync>,
+}
+
+impl EditParseError {
+    /// Get the error for when the edited `text` couldn't be parsed because of `source`.
+    pub(crate) fn error<E>(text: String, source: E) -> Error
+    where
+        E: Into<Box<dyn error::Error + Send + Sync>>,
+    {
+        let source = source.into();
+        Error::new(ErrorKind::InvalidData, EditParseError { text, source })
+    }
+
+    /// Get the edited text that couldn't be parsed.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Get the edited text that couldn't be parsed, consuming the error.
+    pub fn into_text(self) -> String {
+        self.text
+    }
+}
+
+impl fmt::Display for EditParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "couldn't parse the edited text: {}", self.source)
+    }
+}
+
+impl error::Error for EditParseError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&*self.source)
+    }
+}
+
+impl Editor {
+    /// Open the contents of a string or buffer in the editor, and parse the edited text with
+    /// `parse`. See [`edit_with_parser`].
+    ///
+    /// [`edit_with_parser`]: fn.edit_with_parser.html
+    pub fn edit_with_parser<S, T, E, F>(&self, text: S, parse: F) -> Result<T>
+    where
+        S: AsRef<[u8]>,
+        E: Into<Box<dyn error::Error + Send + Sync>>,
+        F: FnOnce(&str) -> std::result::Result<T, E>,
+    {
+        self.edit_parsed_with(text, &TempOptions::new(), parse)
+    }
+
+    /// Like [`Editor::edit_with_parser`], but create the temporary file with `options`.
+    pub(crate) fn edit_parsed_with<S, T, E, F>(
+        &self,
+        text: S,
+        options: &TempOptions,
+        parse: F,
+    ) -> Result<T>
+    where
+        S: AsRef<[u8]>,
+        E: Into<Box<dyn error::Error + Send + Sync>>,
+        F: FnOnce(&str) -> std::result::Result<T, E>,
+    {
+        let edited = self.edit_with_builder(text, options)?;
+        match parse(&edited) {
+            Ok(value) => Ok(value),
+            Err(e) => Err(EditParseError::error(edited, e)),
+        }
+    }
+
+    /// Open the contents of a string or buffer in the editor until the edited text passes
+    /// `validate`, or the user aborts. See [`edit_validated`].
+    ///
+    /// [`edit_validated`]: fn.edit_validated.html
+    pub fn edit_validated<S, T, E, F>(&self, text: S, validate: F) -> Result<Option<T>>
+    where
+        S: AsRef<[u8]>,
+        E: fmt::Display,
+        F: FnMut(&str) -> std::result::Result<T, E>,
+    {
+        self.edit_until_valid(text.as_ref(), true, validate)
+    }
+
+    /// Like [`Editor::edit_validated`], but only treat saving the text unchanged as aborting if
+    /// `unchanged_aborts` is set, or it's been opened again because of an error.
+    pub(crate) fn edit_until_valid<T, E, F>(
+        &self,
+        text: &[u8],
+        unchanged_aborts: bool,
+        mut validate: F,
+    ) -> Result<Option<T>>
+    where
+        E: fmt::Display,
+        F: FnMut(&str) -> std::result::Result<T, E>,
+    {
+        let prefix = self.comment_prefix.as_deref();
+        let prefix = prefix.unwrap_or(cleanup::DEFAULT_COMMENT_PREFIX);
+        let mut text = self.template(text)?.into_owned();
+        let mut error = Vec::new();
+        loop {
+            let template = [&error[..], &text].concat();
+            let edited = self.edit_temp(&template, &TempOptions::new(), Ok)?;
+            if edited == template && (unchanged_aborts || !error.is_empty()) {
+                return Ok(None);
+            }
+            // the error is out of date now, so take it out again if it's still there
+            text = match edited.strip_prefix(&error[..]) {
+                Some(rest) => rest.to_vec(),
+                None => edited,
+            };
+            let finished = self.finish_text(text.clone())?;
+            if finished.trim().is_empty() {
+                return Ok(None);
+            }
+            match validate(&finished) {
+                Ok(value) => return Ok(Some(value)),
+                Err(e) => {
+                    let comment = cleanup::error_comment(&e.to_string(), prefix);
+                    error = self.template(comment.as_bytes())?.into_owned();
+                }
+            }
+        }
+    }
+
+    /// Open a template in the editor until the edited text parses as a `T`, or the user aborts.
+    /// See [`edit_parse`].
+    ///
+    /// [`edit_parse`]: fn.edit_parse.html
+    pub fn edit_parse<T, S>(&self, template: S) -> Result<Option<T>>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+        S: AsRef<[u8]>,
+    {
+        self.edit_validated(template, |text| text.trim().parse())
+    }
+}
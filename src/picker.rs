@@ -0,0 +1,95 @@
+//! Letting users choose an editor from a menu on the terminal.
+
+use crate::{has_terminal, Editor, EditorInfo};
+use std::{
+    io::{self, BufRead, Error, ErrorKind, Result, Write},
+    path::Path,
+};
+
+impl Editor {
+    /// Let the user choose one of the editors this `Editor` could open. See [`choose_editor`].
+    ///
+    /// [`choose_editor`]: fn.choose_editor.html
+    pub fn choose_editor(&self) -> Result<String> {
+        let candidates: Vec<_> = self
+            .editor_candidates()
+            .into_iter()
+            .map(|(editor, args)| {
+                let command = join_command(&editor, &args);
+                (
+                    EditorInfo::new(editor, args.into_iter().map(Into::into).collect()),
+                    command,
+                )
+            })
+            .collect();
+        if candidates.is_empty() {
+            return Err(self.not_found());
+        }
+        if candidates.len() == 1 || !has_terminal() {
+            return Ok(candidates.into_iter().next().unwrap().1);
+        }
+
+        let mut stderr = io::stderr().lock();
+        writeln!(stderr, "Choose an editor:")?;
+        for (i, (info, command)) in candidates.iter().enumerate() {
+            writeln!(stderr, "  {}) {} ({})", i + 1, info.name(), command)?;
+        }
+        let stdin = io::stdin();
+        let mut line = String::new();
+        loop {
+            write!(stderr, "Editor [1]: ")?;
+            stderr.flush()?;
+            line.clear();
+            if stdin.lock().read_line(&mut line)? == 0 {
+                return Err(Error::new(
+                    ErrorKind::UnexpectedEof,
+                    "no editor was chosen before the end of input",
+                ));
+            }
+            let choice = match line.trim() {
+                "" => Some(1),
+                choice => choice.parse().ok(),
+            };
+            match choice {
+                Some(i @ 1..) if i <= candidates.len() => {
+                    return Ok(candidates.into_iter().nth(i - 1).unwrap().1);
+                }
+                _ => writeln!(stderr, "Enter a number from 1 to {}.", candidates.len())?,
+            }
+        }
+    }
+}
+
+/// Join a program and its arguments into a command that [`parse_editor_command`] splits back into
+/// them.
+///
+/// [`parse_editor_command`]: fn.parse_editor_command.html
+fn join_command(program: &Path, args: &[String]) -> String {
+    let program = program.to_string_lossy();
+    Some(program.as_ref())
+        .into_iter()
+        .chain(args.iter().map(String::as_str))
+        .map(quote)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn quote(word: &str) -> String {
+    let special = |c: char| c.is_whitespace() || matches!(c, '\'' | '"' | '\\');
+    if !word.is_empty() && !word.contains(special) {
+        return word.to_owned();
+    }
+    if !word.contains('\'') {
+        return format!("'{}'", word);
+    }
+    // inside double quotes, backslashes only escape on Unix-likes
+    let mut quoted = String::from("\"");
+    for c in word.chars() {
+        if !cfg!(windows) && matches!(c, '"' | '\\' | '$' | '`') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
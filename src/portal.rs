@@ -0,0 +1,139 @@
+//! Opening files with the host's default application from inside a Flatpak or Snap sandbox,
+//! using the [OpenURI portal].
+//!
+//! Host programs can't be run from inside a sandbox, and the files in it (such as temporary
+//! files) aren't visible to the host, so the file is passed to the portal as a file descriptor.
+//! The application the host opens it with can't be waited on, so the edit session ends when the
+//! file is saved instead, just like with [`WaitFor::Save`].
+//!
+//! [OpenURI portal]: https://flatpak.github.io/xdg-desktop-portal/docs/doc-org.freedesktop.portal.OpenURI.html
+//! [`WaitFor::Save`]: ../enum.WaitFor.html#variant.Save
+
+use crate::{
+    cancel::{CancelHandle, Cancelled},
+    session::{wait_until_settled, FileState, POLL_INTERVAL},
+};
+use std::{
+    collections::HashMap,
+    env,
+    fs::OpenOptions,
+    io::{Error, ErrorKind, Result},
+    os::fd::AsFd,
+    path::Path,
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+use zbus::{
+    blocking::{proxy::SignalIterator, Connection, Proxy},
+    zvariant::{Fd, OwnedObjectPath, OwnedValue, Value},
+};
+
+const DESTINATION: &str = "org.freedesktop.portal.Desktop";
+const PATH: &str = "/org/freedesktop/portal/desktop";
+
+/// Check whether this is running inside a Flatpak or Snap sandbox.
+pub(crate) fn in_sandbox() -> bool {
+    Path::new("/.flatpak-info").exists() || env::var_os("SNAP").is_some()
+}
+
+/// Open `file` with the host's default application and wait for it to be saved.
+pub(crate) fn edit_file(
+    file: &Path,
+    before: Option<FileState>,
+    timeout: Option<Duration>,
+    cancel: Option<&CancelHandle>,
+) -> Result<()> {
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+    open_file(file)?;
+    loop {
+        if cancel.is_some_and(|c| c.is_cancelled()) {
+            return Err(Cancelled.into());
+        }
+
+        let state = FileState::of(file);
+        if state.is_some() && state != before {
+            wait_until_settled(file, state);
+            return Ok(());
+        }
+
+        match (timeout, deadline) {
+            (Some(timeout), Some(deadline)) if Instant::now() >= deadline => {
+                return Err(Error::new(
+                    ErrorKind::TimedOut,
+                    format!("file was not saved within {:?}", timeout),
+                ));
+            }
+            _ => thread::sleep(POLL_INTERVAL),
+        }
+    }
+}
+
+/// Ask the portal to open `file`, and wait for it to respond.
+fn open_file(file: &Path) -> Result<()> {
+    static REQUESTS: AtomicUsize = AtomicUsize::new(0);
+
+    let connection = Connection::session().map_err(Error::other)?;
+
+    // subscribe to the response before making the request so it can't be missed; the request
+    // object's path is derived from our unique name and a token of our choosing
+    let token = format!(
+        "edit_{}_{}",
+        process::id(),
+        REQUESTS.fetch_add(1, Ordering::Relaxed)
+    );
+    let sender = connection
+        .unique_name()
+        .ok_or_else(|| Error::other("not connected to the session bus"))?
+        .trim_start_matches(':')
+        .replace('.', "_");
+    let request_path = format!("{}/request/{}/{}", PATH, sender, token);
+    let mut responses = responses(&connection, &request_path)?;
+
+    let open_uri = Proxy::new(
+        &connection,
+        DESTINATION,
+        PATH,
+        "org.freedesktop.portal.OpenURI",
+    )
+    .map_err(Error::other)?;
+    let fd = OpenOptions::new().read(true).write(true).open(file)?;
+    let mut options = HashMap::new();
+    options.insert("handle_token", Value::from(token.as_str()));
+    options.insert("writable", Value::from(true));
+    let handle: OwnedObjectPath = open_uri
+        .call("OpenFile", &("", Fd::from(fd.as_fd()), options))
+        .map_err(Error::other)?;
+
+    // old versions of the portal ignore the token
+    if handle.as_str() != request_path {
+        responses = self::responses(&connection, handle.as_str())?;
+    }
+
+    let response = responses
+        .next()
+        .ok_or_else(|| Error::other("lost connection to the portal"))?;
+    let (response, _): (u32, HashMap<String, OwnedValue>) =
+        response.body().deserialize().map_err(Error::other)?;
+    match response {
+        0 => Ok(()),
+        1 => Err(Error::new(
+            ErrorKind::Interrupted,
+            "opening the file was cancelled",
+        )),
+        _ => Err(Error::other("the portal couldn't open the file")),
+    }
+}
+
+/// Subscribe to the responses to the request object at `path`.
+fn responses(connection: &Connection, path: &str) -> Result<SignalIterator<'static>> {
+    Proxy::new(
+        connection,
+        DESTINATION,
+        path,
+        "org.freedesktop.portal.Request",
+    )
+    .and_then(|request| request.receive_signal("Response"))
+    .map_err(Error::other)
+}
@@ -0,0 +1,184 @@
+//! Editing files the current user can't write to, like `sudoedit` does.
+
+#[cfg(unix)]
+use crate::{get_full_editor_path, has_terminal};
+use crate::{recover, Editor, TempOptions};
+#[cfg(unix)]
+use std::{ffi::CString, os::unix::ffi::OsStrExt};
+use std::{
+    ffi::OsStr,
+    fs,
+    io::{Error, ErrorKind, Result, Write},
+    iter,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+impl Editor {
+    /// Open a file the current user may not be able to write to in the editor, using a
+    /// privilege escalation tool to read and write it. See [`edit_file_privileged`].
+    ///
+    /// [`edit_file_privileged`]: fn.edit_file_privileged.html
+    pub fn edit_file_privileged<P: AsRef<Path>>(&self, file: P) -> Result<()> {
+        let file = file.as_ref();
+        if self.leaves_own_files() {
+            return Ok(());
+        }
+        if writable(file) {
+            return self.edit_file(file);
+        }
+        let tool = find_tool().ok_or_else(|| {
+            Error::new(
+                ErrorKind::PermissionDenied,
+                format!(
+                    "can't write to '{}', and none of sudo, doas, or pkexec could be found to \
+                     edit it with",
+                    file.display()
+                ),
+            )
+        })?;
+        if tool.file_name() == Some(OsStr::new("sudo")) {
+            if let Some(command) = self.sudo_editor()? {
+                return sudoedit(&tool, &command, file);
+            }
+        }
+
+        let original = match fs::symlink_metadata(file) {
+            Err(e) if e.kind() == ErrorKind::NotFound => Vec::new(),
+            _ => run(&tool, &["cat".as_ref(), "--".as_ref(), file.as_os_str()])?,
+        };
+        let mut options = TempOptions::new();
+        let mut prefix = file.file_stem().unwrap_or_default().to_owned();
+        prefix.push("-");
+        options.prefix(prefix);
+        if let Some(extension) = file.extension() {
+            let mut suffix = OsStr::new(".").to_owned();
+            suffix.push(extension);
+            options.suffix(suffix);
+        }
+        let mut copy = self.temp_file(&options, None)?;
+        copy.write_all(&original)?;
+        let copy = copy.into_temp_path();
+
+        let written = self.open_and_wait(&copy).and_then(|()| {
+            if fs::read(&copy)? != original {
+                let args = [
+                    "cp".as_ref(),
+                    "--".as_ref(),
+                    copy.as_os_str(),
+                    file.as_os_str(),
+                ];
+                run(&tool, &args)?;
+            }
+            Ok(())
+        });
+        match written {
+            Ok(()) => self.remove_temp(copy),
+            Err(e) if self.keep_on_error && !self.secure => Err(recover::keep(copy, &original, e)),
+            Err(e) => {
+                let _ = self.remove_temp(copy);
+                Err(e)
+            }
+        }
+    }
+
+    /// Get the command to set `SUDO_EDITOR` to so `sudo -e` opens this editor, if it can be
+    /// expressed that way.
+    fn sudo_editor(&self) -> Result<Option<String>> {
+        let (editor, args) = self.get_editor_args()?;
+        let words: Option<Vec<&str>> = iter::once(editor.as_os_str())
+            .chain(args.iter().map(|arg| arg.as_os_str()))
+            .map(OsStr::to_str)
+            .collect();
+        // sudo splits SUDO_EDITOR on whitespace, with no way to quote it
+        Ok(words
+            .filter(|words| {
+                words
+                    .iter()
+                    .all(|word| !word.is_empty() && !word.contains(char::is_whitespace))
+            })
+            .map(|words| words.join(" ")))
+    }
+}
+
+/// Check whether the current user can write to `file` (or create it, if it doesn't exist).
+#[cfg(unix)]
+fn writable(file: &Path) -> bool {
+    let path = match fs::symlink_metadata(file) {
+        Err(e) if e.kind() == ErrorKind::NotFound => match file.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        },
+        _ => file,
+    };
+    match CString::new(path.as_os_str().as_bytes()) {
+        Ok(path) => unsafe { libc::access(path.as_ptr(), libc::W_OK) == 0 },
+        Err(_) => false,
+    }
+}
+
+#[cfg(not(unix))]
+fn writable(file: &Path) -> bool {
+    fs::metadata(file).map_or(true, |metadata| !metadata.permissions().readonly())
+}
+
+/// Find a tool to run commands as root with.
+#[cfg(unix)]
+fn find_tool() -> Option<PathBuf> {
+    // without a terminal to ask for a password on, pkexec (which asks through a polkit agent) is
+    // the most likely to work
+    let tools = if has_terminal() {
+        ["sudo", "doas", "pkexec"]
+    } else {
+        ["pkexec", "sudo", "doas"]
+    };
+    tools
+        .iter()
+        .find_map(|tool| get_full_editor_path(tool).ok())
+}
+
+#[cfg(not(unix))]
+fn find_tool() -> Option<PathBuf> {
+    None
+}
+
+/// Edit `file` with `sudo -e`, which runs `editor` as the current user on a copy of the file.
+fn sudoedit(sudo: &Path, editor: &str, file: &Path) -> Result<()> {
+    let status = Command::new(sudo)
+        .arg("-e")
+        .arg("--")
+        .arg(file)
+        .env("SUDO_EDITOR", editor)
+        .status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(Error::other(format!(
+            "'sudo -e' exited with error: {}",
+            status
+        )))
+    }
+}
+
+/// Run `args` as root with `tool`, returning what they write to stdout.
+fn run(tool: &Path, args: &[&OsStr]) -> Result<Vec<u8>> {
+    let output = Command::new(tool)
+        .args(args)
+        .stdin(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .output()?;
+    if output.status.success() {
+        Ok(output.stdout)
+    } else {
+        let name = tool.file_name().unwrap_or_default().to_string_lossy();
+        Err(Error::new(
+            ErrorKind::PermissionDenied,
+            format!(
+                "'{} {}' exited with error: {}",
+                name,
+                args[0].to_string_lossy(),
+                output.status
+            ),
+        ))
+    }
+}
@@ -0,0 +1,275 @@
+//! Running the editor in a pseudo-terminal, for when this process doesn't have a terminal the
+//! editor could use.
+
+#[cfg(unix)]
+use std::{
+    ffi::CStr,
+    fs::{File, OpenOptions},
+    io::{self, Error, IsTerminal, Read, Write},
+    mem,
+    os::unix::{
+        fs::OpenOptionsExt,
+        io::{AsRawFd, FromRawFd},
+        process::CommandExt,
+    },
+    sync::Mutex,
+    thread::{self, JoinHandle},
+};
+use std::{io::Result, process::Command};
+
+/// The parent's side of a pseudo-terminal the editor is running in.
+///
+/// Output from the editor is copied to this process's stdout in a background thread. If stdin is a
+/// terminal, it is put into raw mode (so keystrokes are passed through as-is) and copied to the
+/// editor in another thread until [`finish`] is called. Input that isn't from a terminal (like a
+/// pipe) is left for the caller.
+///
+/// [`finish`]: #method.finish
+#[cfg(unix)]
+pub(crate) struct Pty {
+    master: File,
+    slave: Option<File>,
+    output: Option<JoinHandle<()>>,
+    input: Option<Input>,
+    termios: Option<libc::termios>,
+}
+
+/// The thread copying stdin to the editor.
+#[cfg(unix)]
+struct Input {
+    thread: JoinHandle<()>,
+    /// The write end of a pipe the thread polls along with stdin, which is closed to stop it.
+    stop: File,
+}
+
+// ptsname() isn't thread-safe, and ptsname_r() isn't available everywhere
+#[cfg(unix)]
+static PTSNAME_LOCK: Mutex<()> = Mutex::new(());
+
+#[cfg(unix)]
+fn check(ret: libc::c_int) -> Result<libc::c_int> {
+    if ret < 0 {
+        Err(Error::last_os_error())
+    } else {
+        Ok(ret)
+    }
+}
+
+#[cfg(unix)]
+impl Pty {
+    pub fn open() -> Result<Self> {
+        let master = unsafe {
+            let fd = check(libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY))?;
+            File::from_raw_fd(fd)
+        };
+        let fd = master.as_raw_fd();
+
+        let slave_path = unsafe {
+            check(libc::grantpt(fd))?;
+            check(libc::unlockpt(fd))?;
+
+            let _lock = PTSNAME_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+            let name = libc::ptsname(fd);
+            if name.is_null() {
+                return Err(Error::last_os_error());
+            }
+            CStr::from_ptr(name).to_string_lossy().into_owned()
+        };
+        let slave = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .custom_flags(libc::O_NOCTTY)
+            .open(slave_path)?;
+
+        // give the editor the same window size as our terminal, if we have one
+        unsafe {
+            let mut size: libc::winsize = mem::zeroed();
+            if libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) == 0 {
+                libc::ioctl(slave.as_raw_fd(), libc::TIOCSWINSZ, &size);
+            }
+        }
+
+        Ok(Self {
+            master,
+            slave: Some(slave),
+            output: None,
+            input: None,
+            termios: None,
+        })
+    }
+
+    /// Set up `command` to run with the pseudo-terminal as its controlling terminal and stdio.
+    pub fn attach(&self, command: &mut Command) -> Result<()> {
+        let slave = self.slave.as_ref().expect("pty already started");
+        command
+            .stdin(slave.try_clone()?)
+            .stdout(slave.try_clone()?)
+            .stderr(slave.try_clone()?);
+        unsafe {
+            command.pre_exec(|| {
+                check(libc::setsid())?;
+                check(libc::ioctl(libc::STDIN_FILENO, libc::TIOCSCTTY as _, 0))?;
+                Ok(())
+            });
+        }
+        Ok(())
+    }
+
+    /// Start copying input and output once the editor has been spawned.
+    pub fn start(&mut self) -> Result<()> {
+        // close our copy of the slave so reading the master fails once the editor exits
+        drop(self.slave.take());
+
+        unsafe {
+            let mut termios = mem::zeroed();
+            if libc::isatty(libc::STDIN_FILENO) == 1
+                && libc::tcgetattr(libc::STDIN_FILENO, &mut termios) == 0
+            {
+                let mut raw = termios;
+                libc::cfmakeraw(&mut raw);
+                libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &raw);
+                self.termios = Some(termios);
+            }
+        }
+
+        if io::stdin().is_terminal() {
+            self.input = Some(self.copy_input()?);
+        }
+
+        let mut output = self.master.try_clone()?;
+        self.output = Some(thread::spawn(move || {
+            let stdout = io::stdout();
+            let mut stdout = stdout.lock();
+            let mut buf = [0; 4096];
+            // reading fails with EIO once every process using the slave has exited
+            while let Ok(n) = output.read(&mut buf) {
+                if n == 0 || stdout.write_all(&buf[..n]).is_err() {
+                    break;
+                }
+                let _ = stdout.flush();
+            }
+        }));
+
+        Ok(())
+    }
+
+    /// Start copying stdin to the editor, without locking it, until the returned [`Input`] is
+    /// stopped.
+    fn copy_input(&self) -> Result<Input> {
+        let (stopped, stop) = unsafe {
+            let mut fds = [0; 2];
+            check(libc::pipe(fds.as_mut_ptr()))?;
+            let pipe = (File::from_raw_fd(fds[0]), File::from_raw_fd(fds[1]));
+            for fd in &fds {
+                check(libc::fcntl(*fd, libc::F_SETFD, libc::FD_CLOEXEC))?;
+            }
+            pipe
+        };
+
+        let mut input = self.master.try_clone()?;
+        let thread = thread::spawn(move || {
+            let mut fds = [
+                libc::pollfd {
+                    fd: libc::STDIN_FILENO,
+                    events: libc::POLLIN,
+                    revents: 0,
+                },
+                libc::pollfd {
+                    fd: stopped.as_raw_fd(),
+                    events: libc::POLLIN,
+                    revents: 0,
+                },
+            ];
+            let mut buf = [0; 1024];
+            loop {
+                let ret = unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as _, -1) };
+                if ret < 0 {
+                    if Error::last_os_error().kind() == io::ErrorKind::Interrupted {
+                        continue;
+                    }
+                    break;
+                }
+                // the stop pipe only becomes readable (or hangs up) once it's closed
+                if fds[1].revents != 0 {
+                    break;
+                }
+                if fds[0].revents == 0 {
+                    continue;
+                }
+                // read stdin directly rather than through io::stdin(), which would have to be
+                // locked (and could buffer input meant for the caller)
+                let n =
+                    unsafe { libc::read(libc::STDIN_FILENO, buf.as_mut_ptr().cast(), buf.len()) };
+                if n <= 0 || input.write_all(&buf[..n as usize]).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Input { thread, stop })
+    }
+
+    /// Stop copying stdin to the editor, if it's being copied.
+    fn stop_input(&mut self) {
+        if let Some(input) = self.input.take() {
+            drop(input.stop);
+            let _ = input.thread.join();
+        }
+    }
+
+    /// Wait for the editor's output to be copied, stop copying input, and restore the terminal.
+    /// Call this after the editor has exited.
+    pub fn finish(&mut self) {
+        if let Some(output) = self.output.take() {
+            let _ = output.join();
+        }
+        self.stop_input();
+        if let Some(termios) = self.termios.take() {
+            unsafe {
+                libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &termios);
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for Pty {
+    fn drop(&mut self) {
+        self.stop_input();
+        if let Some(termios) = self.termios.take() {
+            unsafe {
+                libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &termios);
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+impl std::fmt::Debug for Pty {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Pty").field("master", &self.master).finish()
+    }
+}
+
+#[cfg(not(unix))]
+#[derive(Debug)]
+pub(crate) struct Pty;
+
+#[cfg(not(unix))]
+impl Pty {
+    pub fn open() -> Result<Self> {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "pseudo-terminals are only supported on Unix",
+        ))
+    }
+
+    pub fn attach(&self, _command: &mut Command) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn start(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn finish(&mut self) {}
+}
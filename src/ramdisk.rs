@@ -0,0 +1,42 @@
+//! Finding directories on RAM-backed filesystems to create temporary files in.
+
+use std::path::PathBuf;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use std::{env, ffi::CString, mem, os::unix::ffi::OsStrExt, path::Path};
+
+/// Get a writable directory on a RAM-backed filesystem (i.e. tmpfs), if there is one.
+///
+/// `XDG_RUNTIME_DIR` is preferred since only the current user can access it, unless the files
+/// will be opened by `other_user` too, in which case only `/dev/shm` is considered.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub(crate) fn dir(other_user: bool) -> Option<PathBuf> {
+    let runtime_dir = env::var_os("XDG_RUNTIME_DIR")
+        .filter(|_| !other_user)
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from);
+    runtime_dir
+        .into_iter()
+        .chain(Some(PathBuf::from("/dev/shm")))
+        .find(|dir| is_writable_tmpfs(dir))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub(crate) fn dir(_other_user: bool) -> Option<PathBuf> {
+    None
+}
+
+// the type of `f_type` differs between architectures and C libraries
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[allow(clippy::unnecessary_cast)]
+fn is_writable_tmpfs(dir: &Path) -> bool {
+    let path = match CString::new(dir.as_os_str().as_bytes()) {
+        Ok(path) => path,
+        Err(_) => return false,
+    };
+    let mut stat: libc::statfs = unsafe { mem::zeroed() };
+    if unsafe { libc::statfs(path.as_ptr(), &mut stat) } != 0 {
+        return false;
+    }
+    stat.f_type as i64 == libc::TMPFS_MAGIC as i64
+        && unsafe { libc::access(path.as_ptr(), libc::W_OK | libc::X_OK) } == 0
+}
@@ -0,0 +1,254 @@
+//! Editing a range of lines of a file, without loading the rest of it into the editor.
+
+use crate::{atomic, atomic::Conflict, attributes, lock, Editor, TempOptions};
+use std::{
+    ffi::OsString,
+    fs::{self, File},
+    io::{self, BufRead, BufReader, Error, ErrorKind, Read, Result, Seek, SeekFrom, Write},
+    ops::{Bound, RangeBounds},
+    path::Path,
+    time::SystemTime,
+};
+
+impl Editor {
+    /// Open a range of lines of an existing file in the editor, and put the edited lines back in
+    /// their place. See [`edit_range`].
+    ///
+    /// [`edit_range`]: fn.edit_range.html
+    pub fn edit_range<P: AsRef<Path>, R: RangeBounds<usize>>(
+        &self,
+        file: P,
+        lines: R,
+    ) -> Result<()> {
+        let file = file.as_ref();
+        let (start, end) = line_bounds(&lines)?;
+        if self.leaves_own_files() {
+            return Ok(());
+        }
+        let _lock = lock::acquire(file, self.lock_policy)?;
+        let target = fs::canonicalize(file)?;
+        let source = File::open(&target)?;
+        let before = Snapshot::of(&source.metadata()?);
+
+        // find where the lines start and end without holding the rest of the file in memory
+        let mut reader = BufReader::new(&source);
+        let mut skipped = Vec::new();
+        // the line ending of the last line before the range that had one
+        let mut newline: &[u8] = b"\n";
+        let mut head = 0;
+        let mut line = 1;
+        while line < start {
+            skipped.clear();
+            match reader.read_until(b'\n', &mut skipped)? {
+                0 => {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        format!(
+                        "the range of lines to edit starts at line {}, but the file only has {}",
+                        start,
+                        line - 1
+                    ),
+                    ))
+                }
+                n => head += n as u64,
+            }
+            if skipped.ends_with(b"\r\n") {
+                newline = b"\r\n";
+            } else if skipped.ends_with(b"\n") {
+                newline = b"\n";
+            }
+            line += 1;
+        }
+        let mut original = Vec::new();
+        while end.is_none_or(|end| line < end) && reader.read_until(b'\n', &mut original)? > 0 {
+            line += 1;
+        }
+        let tail = head + original.len() as u64;
+
+        let mut options = TempOptions::new();
+        if let Some(extension) = target.extension() {
+            let mut suffix = OsString::from(".");
+            suffix.push(extension);
+            options.suffix(suffix);
+        }
+        let mut edited = self.edit_temp(&original, &options, Ok)?;
+        if edited == original {
+            return Ok(());
+        }
+        // start the range on a line of its own after a last line with no line ending
+        if !edited.is_empty() && !skipped.is_empty() && !skipped.ends_with(b"\n") {
+            let mut after = newline.to_vec();
+            after.append(&mut edited);
+            edited = after;
+        }
+        // keep the lines after the range on lines of their own
+        if tail < before.len && !edited.is_empty() && !edited.ends_with(b"\n") {
+            let newline: &[u8] = if original.ends_with(b"\r\n") {
+                b"\r\n"
+            } else {
+                b"\n"
+            };
+            edited.extend_from_slice(newline);
+        }
+
+        // the offsets found above are only right if the file wasn't changed in the meantime
+        match fs::metadata(&target) {
+            Ok(after) if Snapshot::of(&after) == before => {}
+            Ok(_) => {
+                return Err(Conflict::error(
+                    target.clone(),
+                    Some(fs::read(&target)?),
+                    edited,
+                ))
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                return Err(Conflict::error(target, None, edited));
+            }
+            Err(e) => return Err(e),
+        }
+
+        let mut spliced = atomic::temp_next_to(&target, false)?;
+        let mut source = &source;
+        source.seek(SeekFrom::Start(0))?;
+        io::copy(&mut source.take(head), spliced.as_file_mut())?;
+        spliced.write_all(&edited)?;
+        source.seek(SeekFrom::Start(tail))?;
+        io::copy(&mut source, spliced.as_file_mut())?;
+        attributes::copy(source, spliced.as_file())?;
+        spliced.as_file().sync_all()?;
+        spliced.persist(&target).map_err(|e| e.error)?;
+        Ok(())
+    }
+}
+
+/// Turn `lines` into the first line number and the line number after the last, if any.
+fn line_bounds<R: RangeBounds<usize>>(lines: &R) -> Result<(usize, Option<usize>)> {
+    let start = match lines.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => start.saturating_add(1),
+        Bound::Unbounded => 1,
+    };
+    let end = match lines.end_bound() {
+        Bound::Included(&end) => Some(end.saturating_add(1)),
+        Bound::Excluded(&end) => Some(end),
+        Bound::Unbounded => None,
+    };
+    if start == 0 {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "line numbers start at 1",
+        ));
+    }
+    if end.is_some_and(|end| end < start) {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "the range of lines to edit ends before it starts",
+        ));
+    }
+    Ok((start, end))
+}
+
+/// What's used to tell whether a file was changed.
+#[derive(PartialEq, Eq)]
+struct Snapshot {
+    len: u64,
+    modified: Option<SystemTime>,
+}
+
+impl Snapshot {
+    fn of(metadata: &fs::Metadata) -> Self {
+        Self {
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bounds<R: RangeBounds<usize>>(lines: R) -> (usize, Option<usize>) {
+        line_bounds(&lines).unwrap()
+    }
+
+    #[test]
+    fn bounds_of_ranges() {
+        assert_eq!(bounds(3..7), (3, Some(7)));
+        assert_eq!(bounds(3..=7), (3, Some(8)));
+        assert_eq!(bounds(..), (1, None));
+        assert_eq!(bounds(5..), (5, None));
+        assert_eq!(bounds(..=4), (1, Some(5)));
+        assert_eq!(bounds(..4), (1, Some(4)));
+        assert_eq!(bounds(2..2), (2, Some(2)));
+        assert_eq!(bounds((Bound::Excluded(1), Bound::Unbounded)), (2, None));
+    }
+
+    #[test]
+    fn invalid_bounds() {
+        assert_eq!(
+            line_bounds(&(0..3)).unwrap_err().kind(),
+            ErrorKind::InvalidInput
+        );
+        assert_eq!(
+            line_bounds(&(0..)).unwrap_err().kind(),
+            ErrorKind::InvalidInput
+        );
+        #[allow(clippy::reversed_empty_ranges)]
+        let backwards = 5..3;
+        assert_eq!(
+            line_bounds(&backwards).unwrap_err().kind(),
+            ErrorKind::InvalidInput
+        );
+    }
+
+    /// Edit `lines` of a file containing `original`, replacing them with `edited`, and get what
+    /// the file contains afterwards.
+    fn splice<R: RangeBounds<usize>>(original: &str, lines: R, edited: &str) -> Result<String> {
+        let dir = tempfile::tempdir()?;
+        let file = dir.path().join("lines.txt");
+        fs::write(&file, original)?;
+        Editor::new()
+            .noninteractive_content(edited)
+            .edit_range(&file, lines)?;
+        fs::read_to_string(&file)
+    }
+
+    #[test]
+    fn splice_lines() {
+        assert_eq!(
+            splice("a\nb\nc\n", 2..=2, "x\ny\n").unwrap(),
+            "a\nx\ny\nc\n"
+        );
+        assert_eq!(splice("a\nb\nc\n", 2..3, "").unwrap(), "a\nc\n");
+        assert_eq!(splice("a\nb\nc\n", ..=2, "x").unwrap(), "x\nc\n");
+        assert_eq!(splice("a\nb\nc\n", 3.., "x").unwrap(), "a\nb\nx");
+    }
+
+    #[test]
+    fn splice_crlf() {
+        assert_eq!(
+            splice("a\r\nb\r\nc\r\n", 2..=2, "x").unwrap(),
+            "a\r\nx\r\nc\r\n"
+        );
+        assert_eq!(splice("a\r\nb", 3..=3, "c\r\n").unwrap(), "a\r\nb\r\nc\r\n");
+    }
+
+    #[test]
+    fn splice_without_final_newline() {
+        assert_eq!(splice("a\nb", 2..=2, "x\n").unwrap(), "a\nx\n");
+        assert_eq!(splice("a\nb", 1..=1, "x").unwrap(), "x\nb");
+        assert_eq!(splice("a\nb", 3..=3, "c\n").unwrap(), "a\nb\nc\n");
+        assert_eq!(splice("a\nb\n", 3.., "c\n").unwrap(), "a\nb\nc\n");
+        assert_eq!(splice("", 1.., "a\n").unwrap(), "a\n");
+    }
+
+    #[test]
+    fn splice_past_end() {
+        let e = splice("a\nb", 4..=4, "c\n").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidInput);
+        let e = splice("a\nb\n", 5.., "c\n").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidInput);
+        assert_eq!(splice("a\nb", 2..10, "x\n").unwrap(), "a\nx\n");
+    }
+}
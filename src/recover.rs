@@ -0,0 +1,140 @@
+//! Keeping temporary files around when editing fails, so the user's changes aren't lost.
+
+#[cfg(feature = "encrypted-temp")]
+use crate::encrypted::Key;
+use crate::Cancelled;
+use std::{
+    error, fmt, fs,
+    io::{Error, Result},
+    path::{Path, PathBuf},
+    result,
+};
+use tempfile::TempPath;
+
+/// The error returned when editing fails after the user may have made changes, and the temporary
+/// file with those changes was kept. See [`Editor::keep_on_error`].
+///
+/// This is wrapped in an [`io::Error`] of the same kind as the error that made editing fail, and
+/// can be retrieved with [`io::Error::get_ref`]:
+///
+/// ```rust,ignore
+/// use edit::KeptFile;
+///
+/// if let Err(e) = edit::Editor::new().keep_on_error(true).edit("text") {
+///     if let Some(kept) = e.get_ref().and_then(|e| e.downcast_ref::<KeptFile>()) {
+///         eprintln!("your draft was saved to {}", kept.path().display());
+///     }
+/// }
+/// ```
+///
+/// [`Editor::keep_on_error`]: struct.Editor.html#method.keep_on_error
+/// [`io::Error`]: https://doc.rust-lang.org/std/io/struct.Error.html
+/// [`io::Error::get_ref`]: https://doc.rust-lang.org/std/io/struct.Error.html#method.get_ref
+#[derive(Debug)]
+pub struct KeptFile {
+    path: PathBuf,
+    source: Error,
+    // the key the file is encrypted with, if it is (see Editor::encrypt_temp)
+    #[cfg(feature = "encrypted-temp")]
+    key: Option<Key>,
+}
+
+impl KeptFile {
+    /// Get the path of the temporary file, which is no longer deleted automatically.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Get the error that made editing fail.
+    pub fn error(&self) -> &Error {
+        &self.source
+    }
+
+    /// Read the text that was kept, decrypting it if it was kept encrypted (see
+    /// [`Editor::encrypt_temp`]).
+    ///
+    /// [`Editor::encrypt_temp`]: struct.Editor.html#method.encrypt_temp
+    pub fn contents(&self) -> Result<Vec<u8>> {
+        let contents = fs::read(&self.path)?;
+        #[cfg(feature = "encrypted-temp")]
+        if let Some(key) = &self.key {
+            return key.open(&contents);
+        }
+        Ok(contents)
+    }
+
+    fn is_encrypted(&self) -> bool {
+        #[cfg(feature = "encrypted-temp")]
+        return self.key.is_some();
+        #[cfg(not(feature = "encrypted-temp"))]
+        false
+    }
+}
+
+impl fmt::Display for KeptFile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} (the edited text was kept{} in {})",
+            self.source,
+            if self.is_encrypted() {
+                " encrypted"
+            } else {
+                ""
+            },
+            self.path.display()
+        )
+    }
+}
+
+impl error::Error for KeptFile {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+/// Keep the temporary file at `path` after editing failed with `error`, unless its contents are
+/// still `original` (so there's nothing to lose) or the edit session was cancelled on purpose.
+pub(crate) fn keep(path: TempPath, original: &[u8], error: Error) -> Error {
+    let changed = fs::read(&path).is_ok_and(|contents| contents != original);
+    keep_if_changed(path, changed, error)
+}
+
+/// Keep the temporary file at `path` after editing failed with `error`, if it was `changed` and
+/// the edit session wasn't cancelled on purpose.
+pub(crate) fn keep_if_changed(path: TempPath, changed: bool, error: Error) -> Error {
+    match try_keep(path, changed, error) {
+        Ok(kept) => Error::new(kept.source.kind(), kept),
+        Err(error) => error,
+    }
+}
+
+/// Keep the temporary file at `path`, which is encrypted with `key`, like [`keep_if_changed`].
+#[cfg(feature = "encrypted-temp")]
+pub(crate) fn keep_encrypted(path: TempPath, changed: bool, key: Key, error: Error) -> Error {
+    match try_keep(path, changed, error) {
+        Ok(mut kept) => {
+            kept.key = Some(key);
+            Error::new(kept.source.kind(), kept)
+        }
+        Err(error) => error,
+    }
+}
+
+/// Keep the temporary file at `path` like [`keep_if_changed`], or give `error` back if it isn't
+/// kept.
+fn try_keep(path: TempPath, changed: bool, error: Error) -> result::Result<KeptFile, Error> {
+    let cancelled = error.get_ref().is_some_and(|e| e.is::<Cancelled>());
+    if cancelled || !changed {
+        return Err(error);
+    }
+    match path.keep() {
+        Ok(path) => Ok(KeptFile {
+            path,
+            source: error,
+            #[cfg(feature = "encrypted-temp")]
+            key: None,
+        }),
+        Err(_) => Err(error),
+    }
+}
@@ -0,0 +1,396 @@
+//! What this crate knows about individual editors (and generic file openers): whether they are GUI
+//! programs, how to make them wait for the file to be closed, how to open a file at a line, and
+//! whether they can open directories.
+
+use crate::parse_editor_command;
+use std::{ffi::OsString, path::Path};
+
+/// An editor or file opener this crate knows about.
+pub(crate) struct KnownEditor {
+    /// The name to show users, e.g. "Visual Studio Code".
+    pub name: &'static str,
+    /// The names of its programs, without any extension like `.exe`.
+    pub programs: &'static [&'static str],
+    /// Whether it opens a window of its own, as opposed to running in a terminal.
+    pub gui: bool,
+    /// The arguments that make it wait until the file is closed, if it doesn't by default. The
+    /// last one is what makes it wait; any before it keep the wait from being cut short (e.g.
+    /// `-n`, which makes Visual Studio Code open a new window rather than reuse one).
+    pub wait_args: &'static [&'static str],
+    /// Whether it returns before the file is closed when there are no `wait_args` to stop it
+    /// (like generic file openers, which hand the file off to another program).
+    pub detaches: bool,
+    /// The arguments that open a file at a line, with `{file}` and `{line}` as placeholders, or
+    /// `None` if it can't do that.
+    pub line_args: Option<&'static [&'static str]>,
+    /// Non-zero exit codes that don't mean editing failed.
+    pub harmless_exit_codes: &'static [i32],
+    /// The arguments that make it print its version and exit, or `None` if it can't do that.
+    pub version_args: Option<&'static [&'static str]>,
+    /// Whether it can open a directory, e.g. as a project or with a file browser.
+    pub opens_dirs: bool,
+}
+
+impl KnownEditor {
+    const fn terminal(name: &'static str, programs: &'static [&'static str]) -> Self {
+        KnownEditor {
+            name,
+            programs,
+            gui: false,
+            wait_args: &[],
+            detaches: false,
+            line_args: None,
+            harmless_exit_codes: &[],
+            version_args: None,
+            opens_dirs: false,
+        }
+    }
+
+    const fn gui(name: &'static str, programs: &'static [&'static str]) -> Self {
+        KnownEditor {
+            gui: true,
+            ..Self::terminal(name, programs)
+        }
+    }
+
+    const fn opener(program: &'static [&'static str; 1]) -> Self {
+        KnownEditor {
+            detaches: true,
+            ..Self::gui(program[0], program)
+        }
+    }
+
+    const fn wait_args(self, wait_args: &'static [&'static str]) -> Self {
+        KnownEditor {
+            wait_args,
+            detaches: true,
+            ..self
+        }
+    }
+
+    const fn line_args(self, line_args: &'static [&'static str]) -> Self {
+        KnownEditor {
+            line_args: Some(line_args),
+            ..self
+        }
+    }
+
+    const fn version_args(self, version_args: &'static [&'static str]) -> Self {
+        KnownEditor {
+            version_args: Some(version_args),
+            ..self
+        }
+    }
+
+    const fn opens_dirs(self) -> Self {
+        KnownEditor {
+            opens_dirs: true,
+            ..self
+        }
+    }
+
+    const fn harmless_exit_codes(self, harmless_exit_codes: &'static [i32]) -> Self {
+        KnownEditor {
+            harmless_exit_codes,
+            ..self
+        }
+    }
+}
+
+const PLUS_LINE: &[&str] = &["+{line}", "{file}"];
+const FILE_COLON_LINE: &[&str] = &["{file}:{line}"];
+const LINE_FLAG: &[&str] = &["-l", "{line}", "{file}"];
+const VERSION: &[&str] = &["--version"];
+
+#[rustfmt::skip]
+static EDITORS: &[KnownEditor] = &[
+    // vim exits with 1 if any error message was shown during the session (e.g. from a broken
+    // vimrc, or from hitting Ctrl-C at a prompt), even if the file was written successfully.
+    // note this means :cq can't be used to signal failure; use ExitCodePolicy::Strict for that
+    KnownEditor::terminal("Vim", &["vi", "vim"])
+        .line_args(PLUS_LINE)
+        .version_args(VERSION)
+        .opens_dirs()
+        .harmless_exit_codes(&[1]),
+    KnownEditor::terminal("Neovim", &["nvim"])
+        .line_args(PLUS_LINE)
+        .version_args(VERSION)
+        .opens_dirs(),
+    KnownEditor::terminal("GNU nano", &["nano"]).line_args(PLUS_LINE).version_args(VERSION),
+    KnownEditor::terminal("Pico", &["pico"]).line_args(PLUS_LINE),
+    // emacs can open a window of its own, but falls back to the terminal when there's no display
+    KnownEditor::terminal("Emacs", &["emacs"])
+        .line_args(PLUS_LINE)
+        .version_args(VERSION)
+        .opens_dirs(),
+    KnownEditor::terminal("mg", &["mg"]).line_args(PLUS_LINE),
+    KnownEditor::terminal("JOE", &["joe"]).line_args(PLUS_LINE),
+    KnownEditor::terminal("Kakoune", &["kak"]).line_args(PLUS_LINE).version_args(&["-version"]),
+    KnownEditor::terminal("micro", &["micro"]).line_args(PLUS_LINE).version_args(&["-version"]),
+    KnownEditor::terminal("Helix", &["helix", "hx"])
+        .line_args(FILE_COLON_LINE)
+        .version_args(VERSION)
+        .opens_dirs(),
+    KnownEditor::terminal("ed", &["ed"]).version_args(VERSION),
+    KnownEditor::terminal("ee", &["ee"]),
+    KnownEditor::terminal("JED", &["jed"]).version_args(VERSION),
+    KnownEditor::terminal("sensible-editor", &["sensible-editor"]),
+
+    KnownEditor::gui("Visual Studio Code", &["code"])
+        .wait_args(&["-n", "-w"])
+        .line_args(&["-g", "{file}:{line}"])
+        .version_args(VERSION)
+        .opens_dirs(),
+    KnownEditor::gui("VSCodium", &["codium"])
+        .wait_args(&["-n", "-w"])
+        .line_args(&["-g", "{file}:{line}"])
+        .version_args(VERSION)
+        .opens_dirs(),
+    KnownEditor::gui("Atom", &["atom"])
+        .wait_args(&["-w"])
+        .line_args(FILE_COLON_LINE)
+        .version_args(VERSION)
+        .opens_dirs(),
+    KnownEditor::gui("Sublime Text", &["subl", "sublime_text"])
+        .wait_args(&["-w"])
+        .line_args(FILE_COLON_LINE)
+        .version_args(VERSION)
+        .opens_dirs(),
+    KnownEditor::gui("Zed", &["zed"])
+        .wait_args(&["--wait"])
+        .line_args(FILE_COLON_LINE)
+        .version_args(VERSION)
+        .opens_dirs(),
+    KnownEditor::gui("gedit", &["gedit"])
+        .wait_args(&["--wait"])
+        .line_args(PLUS_LINE)
+        .version_args(VERSION),
+    KnownEditor::gui("GNOME Text Editor", &["gnome-text-editor"])
+        .wait_args(&["--standalone"])
+        .version_args(VERSION),
+    KnownEditor::gui("gVim", &["gvim"])
+        .wait_args(&["-f"])
+        .line_args(PLUS_LINE)
+        .version_args(VERSION)
+        .opens_dirs()
+        .harmless_exit_codes(&[1]),
+    KnownEditor::gui("MacVim", &["mvim"])
+        .wait_args(&["-f"])
+        .line_args(PLUS_LINE)
+        .version_args(VERSION)
+        .opens_dirs()
+        .harmless_exit_codes(&[1]),
+    KnownEditor::gui("Kate", &["kate"])
+        .wait_args(&["-b"])
+        .line_args(LINE_FLAG)
+        .version_args(VERSION),
+    KnownEditor::gui("TextMate", &["mate"])
+        .wait_args(&["-w"])
+        .line_args(LINE_FLAG)
+        .version_args(VERSION)
+        .opens_dirs(),
+    KnownEditor::gui("Notepad++", &["notepad++"]).line_args(&["-n{line}", "{file}"]),
+    KnownEditor::gui("KWrite", &["kwrite"]).version_args(VERSION),
+    KnownEditor::gui("Mousepad", &["mousepad"]).version_args(VERSION),
+    KnownEditor::gui("Notepad", &["notepad"]),
+    KnownEditor::gui("Pluma", &["pluma"]).version_args(VERSION),
+    KnownEditor::gui("Xed", &["xed"]).version_args(VERSION),
+    KnownEditor::gui("Koder", &["Koder"]),
+    KnownEditor::gui("Pe", &["Pe"]),
+    KnownEditor::gui("StyledEdit", &["StyledEdit"]),
+    KnownEditor::gui("TextEdit", &["TextEdit"]),
+
+    // generic openers
+    // open -W exits with 1 on some versions of MacOS if the app was already running
+    KnownEditor::opener(&["open"]).wait_args(&["-W"]).harmless_exit_codes(&[1]),
+    KnownEditor::opener(&["cmd"]),
+    KnownEditor::opener(&["exo-open"]),
+    KnownEditor::opener(&["gio"]),
+    KnownEditor::opener(&["gnome-open"]),
+    KnownEditor::opener(&["kde-open"]),
+    KnownEditor::opener(&["kde-open5"]),
+    KnownEditor::opener(&["termux-open"]),
+    KnownEditor::opener(&["wslview"]),
+    KnownEditor::opener(&["xdg-open"]),
+    // run sandboxed apps, which are all GUI editors in this crate's lists
+    KnownEditor::gui("Flatpak", &["flatpak"]),
+    KnownEditor::gui("Snap", &["snap"]),
+];
+
+/// Look up what's known about `editor`.
+pub(crate) fn lookup(editor: &Path) -> Option<&'static KnownEditor> {
+    let name = editor.file_stem()?.to_str()?;
+    EDITORS
+        .iter()
+        .find(|known| known.programs.iter().any(|p| p.eq_ignore_ascii_case(name)))
+}
+
+// long forms of the arguments in `wait_args`
+const LONG_WAIT_ARGS: &[&str] = &["--wait", "--block", "--nofork", "--standalone"];
+
+/// Check whether `editor` is known to be able to open directories.
+pub(crate) fn opens_dirs(editor: &Path) -> bool {
+    lookup(editor).is_some_and(|known| known.opens_dirs)
+}
+
+/// Add the arguments that make the editor of `command` wait for the file to be closed, if it is
+/// known to need any that aren't there already (and it doesn't have a long form of them).
+pub(crate) fn with_wait_args(command: &str) -> String {
+    let (program, args) = match parse_editor_command(command) {
+        Ok(parsed) => parsed,
+        Err(_) => return command.to_owned(),
+    };
+    if args.iter().any(|a| LONG_WAIT_ARGS.contains(&a.as_str())) {
+        return command.to_owned();
+    }
+    let missing = lookup(&program)
+        .map(|known| known.wait_args)
+        .unwrap_or_default()
+        .iter()
+        .filter(|arg| !args.iter().any(|a| a == *arg));
+    let mut command = command.to_owned();
+    for arg in missing {
+        command.push(' ');
+        command.push_str(arg);
+    }
+    command
+}
+
+/// Check whether `editor` waits for the file to be closed when run with `args`. Editors this crate
+/// doesn't know about are assumed to.
+pub(crate) fn blocks(editor: &Path, args: &[OsString]) -> bool {
+    let known = match lookup(editor) {
+        Some(known) => known,
+        None => return true,
+    };
+    !known.detaches
+        || args.iter().any(|arg| {
+            known.wait_args.last().is_some_and(|wait| arg == wait)
+                || LONG_WAIT_ARGS.iter().any(|long| arg == long)
+        })
+}
+
+/// Get the arguments that open `file` in `editor`, at `line` if given and the editor can do that.
+pub(crate) fn file_args(editor: &Path, file: &Path, line: Option<usize>) -> Vec<OsString> {
+    let (line, template) = match (line, lookup(editor).and_then(|known| known.line_args)) {
+        (Some(line), Some(template)) => (line.to_string(), template),
+        _ => return vec![file.into()],
+    };
+    template
+        .iter()
+        .map(|arg| {
+            let arg = arg.replace("{line}", &line);
+            match arg.split_once("{file}") {
+                Some((before, after)) => {
+                    let mut arg = OsString::from(before);
+                    arg.push(file);
+                    arg.push(after);
+                    arg
+                }
+                None => arg.into(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn unique_entries() {
+        let mut names = HashSet::new();
+        let mut programs = HashSet::new();
+        for known in EDITORS {
+            assert!(names.insert(known.name), "{} is listed twice", known.name);
+            for program in known.programs {
+                let program = program.to_ascii_lowercase();
+                assert!(
+                    programs.insert(program.clone()),
+                    "{} is listed twice",
+                    program
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn consistent_entries() {
+        for known in EDITORS {
+            assert!(!known.programs.is_empty(), "{}", known.name);
+            // only GUI programs detach from the terminal
+            assert!(known.gui || !known.detaches, "{}", known.name);
+            for args in [known.line_args, Some(known.wait_args)].iter().flatten() {
+                assert!(args.iter().all(|arg| !arg.is_empty()), "{}", known.name);
+            }
+            if let Some(line_args) = known.line_args {
+                assert!(
+                    line_args.iter().any(|a| a.contains("{file}")),
+                    "{}",
+                    known.name
+                );
+                assert!(
+                    line_args.iter().any(|a| a.contains("{line}")),
+                    "{}",
+                    known.name
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn lookup_by_program() {
+        let name = |editor: &str| lookup(Path::new(editor)).map(|known| known.name);
+        assert_eq!(name("vim"), Some("Vim"));
+        assert_eq!(name("vi"), Some("Vim"));
+        assert_eq!(name("/usr/local/bin/nvim"), Some("Neovim"));
+        assert_eq!(name("hx"), Some("Helix"));
+        assert_eq!(name("Code.exe"), Some("Visual Studio Code"));
+        assert_eq!(name("code.cmd"), Some("Visual Studio Code"));
+        assert_eq!(name("sublime_text"), Some("Sublime Text"));
+        assert_eq!(name("stylededit"), Some("StyledEdit"));
+        assert_eq!(name("xdg-open"), Some("xdg-open"));
+        assert_eq!(name("vimdiff"), None);
+        assert_eq!(name("my-editor"), None);
+        assert_eq!(name(""), None);
+    }
+
+    #[test]
+    fn add_wait_args() {
+        assert_eq!(with_wait_args("code"), "code -n -w");
+        assert_eq!(with_wait_args("code -n"), "code -n -w");
+        // -n is still needed to keep an existing window from ending the wait
+        assert_eq!(with_wait_args("code -w"), "code -w -n");
+        assert_eq!(with_wait_args("code --wait"), "code --wait");
+        assert_eq!(with_wait_args("vim"), "vim");
+        assert_eq!(with_wait_args("my-editor"), "my-editor");
+        assert_eq!(with_wait_args("'unterminated"), "'unterminated");
+    }
+
+    #[test]
+    fn blocking() {
+        let blocks = |editor: &str, args: &[&str]| {
+            let args: Vec<OsString> = args.iter().map(Into::into).collect();
+            blocks(Path::new(editor), &args)
+        };
+        assert!(blocks("vim", &[]));
+        assert!(blocks("my-editor", &[]));
+        assert!(!blocks("code", &[]));
+        assert!(blocks("code", &["-w"]));
+        assert!(blocks("code", &["--wait"]));
+        assert!(!blocks("xdg-open", &[]));
+        assert!(blocks("open", &["-W", "-t"]));
+    }
+
+    #[test]
+    fn line_file_args() {
+        let args = |editor: &str, line| file_args(Path::new(editor), Path::new("a b.txt"), line);
+        assert_eq!(args("vim", Some(3)), ["+3", "a b.txt"]);
+        assert_eq!(args("vim", None), ["a b.txt"]);
+        assert_eq!(args("kate", Some(3)), ["-l", "3", "a b.txt"]);
+        assert_eq!(args("notepad++", Some(3)), ["-n3", "a b.txt"]);
+        assert_eq!(args("notepad", Some(3)), ["a b.txt"]);
+        assert_eq!(args("my-editor", Some(3)), ["a b.txt"]);
+    }
+}
@@ -0,0 +1,90 @@
+//! Remembering which of the editors this crate guessed was last used, so the same one is used
+//! next time.
+
+#[cfg(not(target_os = "windows"))]
+use std::path::Path;
+use std::{
+    env,
+    ffi::OsStr,
+    fs,
+    io::{ErrorKind, Result},
+    path::PathBuf,
+};
+
+/// Get the path of the file the last used editor is remembered in.
+fn state_path() -> Option<PathBuf> {
+    state_dir().map(|dir| dir.join("edit").join("last-editor"))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn state_dir() -> Option<PathBuf> {
+    env::var_os("XDG_STATE_HOME")
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".local/state")))
+}
+
+#[cfg(target_os = "macos")]
+fn state_dir() -> Option<PathBuf> {
+    env::var_os("HOME").map(|home| Path::new(&home).join("Library/Application Support"))
+}
+
+#[cfg(target_os = "windows")]
+fn state_dir() -> Option<PathBuf> {
+    env::var_os("LOCALAPPDATA")
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Get the editor remembered by an [`Editor`] with [`Editor::remember_editor`] enabled, if any.
+///
+/// The editor is returned as a command, like the value of `EDITOR`. It is stored in
+/// `$XDG_STATE_HOME/edit/last-editor` (or `~/.local/state/edit/last-editor`) on Linux and other
+/// Unix-likes, `~/Library/Application Support/edit/last-editor` on MacOS, and
+/// `%LOCALAPPDATA%\edit\last-editor` on Windows.
+///
+/// [`Editor`]: struct.Editor.html
+/// [`Editor::remember_editor`]: struct.Editor.html#method.remember_editor
+pub fn remembered_editor() -> Option<String> {
+    let command = fs::read_to_string(state_path()?).ok()?;
+    let command = command.trim();
+    if command.is_empty() {
+        None
+    } else {
+        Some(command.to_owned())
+    }
+}
+
+/// Forget the editor remembered by an [`Editor`] with [`Editor::remember_editor`] enabled, so
+/// editors are guessed from scratch again.
+///
+/// It isn't an error if no editor was remembered.
+///
+/// [`Editor`]: struct.Editor.html
+/// [`Editor::remember_editor`]: struct.Editor.html#method.remember_editor
+pub fn forget_editor() -> Result<()> {
+    let path = match state_path() {
+        Some(path) => path,
+        None => return Ok(()),
+    };
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// Remember `command` as the last used editor. Failing to is harmless (the editor will just be
+/// guessed again next time), so errors are ignored.
+pub(crate) fn remember(command: &OsStr) {
+    let (path, command) = match (state_path(), command.to_str()) {
+        (Some(path), Some(command)) => (path, command),
+        _ => return,
+    };
+    if remembered_editor().as_deref() == Some(command) {
+        return;
+    }
+    if let Some(dir) = path.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+    let _ = fs::write(path, format!("{}\n", command));
+}
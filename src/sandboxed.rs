@@ -0,0 +1,73 @@
+//! Finding editors that are installed as Flatpak or Snap apps, which often have no program of
+//! their own in `PATH`.
+
+use std::ffi::OsString;
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+use std::{
+    env,
+    path::{Path, PathBuf},
+};
+
+/// Flatpak app IDs of editors, and the arguments that make them wait for the file to be closed.
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+#[rustfmt::skip]
+static FLATPAK_APPS: &[(&str, &str)] = &[
+    ("com.visualstudio.code", "-n -w"),
+    ("com.vscodium.codium", "-n -w"),
+    ("com.sublimetext.three", "-w"),
+    ("dev.zed.Zed", "--wait"),
+    ("org.gnome.gedit", "--wait"),
+    ("org.gnome.TextEditor", "--standalone"),
+    ("org.kde.kate", "-b"),
+    ("org.vim.Vim", "-f"),
+];
+
+/// Snap names and commands of editors, and the arguments that make them wait for the file to be
+/// closed. Only snaps with classic confinement are listed, since strictly confined snaps can't
+/// see temporary files.
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+#[rustfmt::skip]
+static SNAP_APPS: &[(&str, &str, &str)] = &[
+    ("code", "code", "-n -w"),
+    ("codium", "codium", "-n -w"),
+    ("sublime-text", "sublime-text.subl", "-w"),
+];
+
+/// Get commands that run the installed Flatpak and Snap editors.
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+pub(crate) fn installed_editors() -> Vec<OsString> {
+    let flatpak_dirs = flatpak_dirs();
+    let flatpaks = FLATPAK_APPS
+        .iter()
+        .filter(|(id, _)| {
+            flatpak_dirs
+                .iter()
+                .any(|dir| dir.join(id).join("current").exists())
+        })
+        // everything between a pair of @@ is a file to make available inside the sandbox, and
+        // the file to edit is appended after this
+        .map(|(id, args)| format!("flatpak run --file-forwarding {} {} @@", id, args));
+    let snaps = SNAP_APPS
+        .iter()
+        .filter(|(name, _, _)| Path::new("/snap").join(name).join("current").exists())
+        .map(|(_, command, args)| format!("snap run {} {}", command, args));
+    flatpaks.chain(snaps).map(OsString::from).collect()
+}
+
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+pub(crate) fn installed_editors() -> Vec<OsString> {
+    Vec::new()
+}
+
+/// The directories of the user and system Flatpak installations' apps.
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn flatpak_dirs() -> Vec<PathBuf> {
+    let user = env::var_os("XDG_DATA_HOME")
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".local/share")))
+        .map(|dir| dir.join("flatpak/app"));
+    user.into_iter()
+        .chain(Some(PathBuf::from("/var/lib/flatpak/app")))
+        .collect()
+}
@@ -0,0 +1,130 @@
+//! Making sure temporary files holding sensitive text can't be read by other users, or recovered
+//! after they're deleted.
+
+#[cfg(unix)]
+use std::{
+    fs::{self, DirBuilder, Permissions},
+    io::{Error, ErrorKind},
+    os::unix::fs::{DirBuilderExt, MetadataExt, PermissionsExt},
+};
+use std::{
+    fs::{File, OpenOptions},
+    io::{Result, Write},
+    path::{Path, PathBuf},
+    ptr,
+    sync::atomic::{self, Ordering},
+};
+
+/// Get a directory in `dir` that only the current user can access, creating it if needed.
+///
+/// `dir` itself is used if it's already private (like `XDG_RUNTIME_DIR` or the per-user temporary
+/// directories on MacOS). Otherwise, it must not be writable by other users unless it has the
+/// sticky bit set (like `/tmp`), since they could replace the private directory otherwise.
+#[cfg(unix)]
+pub(crate) fn private_dir(dir: &Path) -> Result<PathBuf> {
+    let uid = unsafe { libc::geteuid() };
+    let metadata = fs::metadata(dir)?;
+    if !metadata.is_dir() {
+        return Err(insecure(dir, "isn't a directory"));
+    }
+    if metadata.uid() != uid && metadata.uid() != 0 {
+        return Err(insecure(dir, "is owned by another user"));
+    }
+    if metadata.uid() == uid && metadata.mode() & 0o077 == 0 {
+        return Ok(dir.to_owned());
+    }
+    // 0o1000 is the sticky bit
+    if metadata.mode() & 0o022 != 0 && metadata.mode() & 0o1000 == 0 {
+        return Err(insecure(
+            dir,
+            "can be written to by other users and doesn't have the sticky bit set",
+        ));
+    }
+
+    let private = dir.join(format!("edit-{}", uid));
+    match DirBuilder::new().mode(0o700).create(&private) {
+        Err(e) if e.kind() != ErrorKind::AlreadyExists => return Err(e),
+        _ => {}
+    }
+    // check the directory even if it was just created, in case someone else got there first
+    let metadata = fs::symlink_metadata(&private)?;
+    if !metadata.is_dir() {
+        return Err(insecure(&private, "isn't a directory"));
+    }
+    if metadata.uid() != uid {
+        return Err(insecure(&private, "is owned by another user"));
+    }
+    if metadata.mode() & 0o077 != 0 {
+        return Err(insecure(&private, "can be accessed by other users"));
+    }
+    Ok(private)
+}
+
+// temporary directories are already per-user elsewhere (e.g. in %LOCALAPPDATA% on Windows)
+#[cfg(not(unix))]
+pub(crate) fn private_dir(dir: &Path) -> Result<PathBuf> {
+    Ok(dir.to_owned())
+}
+
+/// Make sure only the current user can read or write `file`.
+#[cfg(unix)]
+pub(crate) fn check_file(file: &File) -> Result<()> {
+    file.set_permissions(Permissions::from_mode(0o600))?;
+    let metadata = file.metadata()?;
+    if metadata.uid() != unsafe { libc::geteuid() } || metadata.mode() & 0o077 != 0 {
+        return Err(Error::new(
+            ErrorKind::PermissionDenied,
+            "the temporary file can be accessed by other users",
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub(crate) fn check_file(_file: &File) -> Result<()> {
+    Ok(())
+}
+
+/// Overwrite the contents of the file at `path` with zeros, so they can't be recovered from the
+/// disk after it's deleted (at least on filesystems that overwrite data in place).
+pub(crate) fn shred(path: &Path) -> Result<()> {
+    let mut file = OpenOptions::new().write(true).open(path)?;
+    let mut len = file.metadata()?.len();
+    while len > 0 {
+        let n = len.min(ZEROS.len() as u64) as usize;
+        file.write_all(&ZEROS[..n])?;
+        len -= n as u64;
+    }
+    file.sync_all()
+}
+
+pub(crate) static ZEROS: [u8; 8192] = [0; 8192];
+
+/// Overwrite `buf` with zeros in a way the compiler can't optimize away.
+pub(crate) fn wipe(buf: &mut [u8]) {
+    for byte in buf.iter_mut() {
+        unsafe { ptr::write_volatile(byte, 0) };
+    }
+    atomic::compiler_fence(Ordering::SeqCst);
+}
+
+/// Overwrite `buf` with zeros like [`wipe`] does.
+#[cfg(feature = "encrypted-temp")]
+pub(crate) fn wipe_words(buf: &mut [u32]) {
+    for word in buf.iter_mut() {
+        unsafe { ptr::write_volatile(word, 0) };
+    }
+    atomic::compiler_fence(Ordering::SeqCst);
+}
+
+#[cfg(unix)]
+fn insecure(dir: &Path, problem: &str) -> Error {
+    Error::new(
+        ErrorKind::PermissionDenied,
+        format!(
+            "can't create a private temporary file, since '{}' {}",
+            dir.display(),
+            problem
+        ),
+    )
+}
@@ -0,0 +1,31 @@
+//! Opening files in an editor that is already running, when this is running in that editor's
+//! built-in terminal.
+
+use std::env;
+
+/// Get commands that open a file in the editor whose terminal this is running in (if any) and wait
+/// for it to be closed.
+pub(crate) fn commands() -> Vec<String> {
+    let mut commands = Vec::new();
+
+    // Neovim sets NVIM to the address of its server in :terminal. nvim itself can't wait for a
+    // remote file to be closed (its --remote-wait isn't implemented), but neovim-remote can
+    if let Some(address) = env::var("NVIM")
+        .ok()
+        .filter(|a| !a.is_empty() && !a.contains('\''))
+    {
+        commands.push(format!("nvr --servername '{}' --remote-wait", address));
+    }
+
+    // set by shell-mode, eshell, term, and vterm; -a '' starts a server if there isn't one
+    if env::var_os("INSIDE_EMACS").is_some() {
+        commands.push("emacsclient -a ''".into());
+    }
+
+    // -r opens the file in the window the terminal is in, rather than a new one
+    if env::var("TERM_PROGRAM").is_ok_and(|program| program == "vscode") {
+        commands.push("code -r -w".into());
+    }
+
+    commands
+}
@@ -0,0 +1,704 @@
+use crate::{
+    cancel::{CancelHandle, Cancelled},
+    pty::Pty,
+    registry,
+    signals::SignalGuard,
+};
+use std::{
+    error, fmt, fs,
+    io::{self, Error, ErrorKind, Read, Result},
+    path::Path,
+    process::{Child, Command, ExitStatus, Stdio},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant, SystemTime},
+};
+
+// how often wait_timeout checks whether the editor has exited (and the file has been saved)
+pub(crate) const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// What to wait for before an edit session is considered finished.
+///
+/// See [`Editor::wait_for`].
+///
+/// [`Editor::wait_for`]: struct.Editor.html#method.wait_for
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WaitFor {
+    /// Wait for the editor process to exit. This is the default.
+    #[default]
+    Exit,
+    /// Wait for the file to be saved, without waiting for the editor process to exit.
+    ///
+    /// This is useful for GUI editors that open files in an existing window and exit
+    /// immediately (e.g. `code` without `--wait`), which would otherwise make the edit session
+    /// end before the user has done anything. The file is considered saved once its
+    /// modification time or size changes; the first save ends the edit session, and the editor
+    /// is left running.
+    ///
+    /// If the editor exits with an error before the file is saved, the edit session fails.
+    /// Combine this with [`Editor::timeout`] to avoid waiting forever for a save that never
+    /// happens.
+    ///
+    /// [`Editor::timeout`]: struct.Editor.html#method.timeout
+    Save,
+}
+
+/// Which exit codes of the editor are treated as errors.
+///
+/// See [`Editor::exit_code_policy`].
+///
+/// [`Editor::exit_code_policy`]: struct.Editor.html#method.exit_code_policy
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ExitCodePolicy {
+    /// Any non-zero exit code is an error, except for those known to be harmless for the editor
+    /// being used (e.g. `vim` exits with 1 if any error message was shown during the session,
+    /// even if the file was saved). This is the default.
+    #[default]
+    Known,
+    /// Any non-zero exit code is an error.
+    Strict,
+    /// Exit codes are ignored. The edit session still fails if the editor is killed by a signal.
+    Ignore,
+}
+
+impl ExitCodePolicy {
+    /// Get the non-zero exit codes of `editor` that aren't errors, besides `accepted`, or `None`
+    /// if no exit code is.
+    pub(crate) fn accepted_codes(self, editor: &Path, accepted: &[i32]) -> Option<Vec<i32>> {
+        match self {
+            ExitCodePolicy::Known => {
+                let harmless = registry::lookup(editor).map_or(&[][..], |k| k.harmless_exit_codes);
+                Some([harmless, accepted].concat())
+            }
+            ExitCodePolicy::Strict => Some(accepted.to_vec()),
+            ExitCodePolicy::Ignore => None,
+        }
+    }
+}
+
+/// How one of the standard streams (stdin, stdout, or stderr) of the editor is set up.
+///
+/// See [`Editor::stdin`], [`Editor::stdout`], and [`Editor::stderr`].
+///
+/// [`Editor::stdin`]: struct.Editor.html#method.stdin
+/// [`Editor::stdout`]: struct.Editor.html#method.stdout
+/// [`Editor::stderr`]: struct.Editor.html#method.stderr
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum StdioMode {
+    /// The editor shares the stream with this process. This is the default.
+    #[default]
+    Inherit,
+    /// The stream is connected to the null device (e.g. `/dev/null`).
+    Null,
+    /// The stream is connected to a pipe.
+    ///
+    /// For stderr, the editor's output is captured and included in the error if the editor
+    /// fails (see [`ExitError`]). For stdin and stdout, the pipe is available through
+    /// [`EditorSession::child_mut`]; when waiting for the editor to exit (e.g. with [`edit`]),
+    /// stdin is closed immediately and anything written to stdout is discarded.
+    ///
+    /// [`ExitError`]: struct.ExitError.html
+    /// [`EditorSession::child_mut`]: struct.EditorSession.html#method.child_mut
+    /// [`edit`]: fn.edit.html
+    Piped,
+}
+
+impl From<StdioMode> for Stdio {
+    fn from(mode: StdioMode) -> Self {
+        match mode {
+            StdioMode::Inherit => Stdio::inherit(),
+            StdioMode::Null => Stdio::null(),
+            StdioMode::Piped => Stdio::piped(),
+        }
+    }
+}
+
+/// A running editor process, as returned by [`edit_file_session`].
+///
+/// Unlike [`edit_file`], which blocks until the editor exits, an `EditorSession` lets the caller
+/// poll the editor, wait for it with a timeout, or kill it.
+///
+/// Dropping an `EditorSession` does not kill or wait for the editor (just like dropping a
+/// [`Child`]).
+///
+/// [`edit_file_session`]: fn.edit_file_session.html
+/// [`edit_file`]: fn.edit_file.html
+/// [`Child`]: https://doc.rust-lang.org/std/process/struct.Child.html
+#[derive(Debug)]
+pub struct EditorSession {
+    child: Child,
+    command: String,
+    stderr: Option<JoinHandle<Vec<u8>>>,
+    accepted_codes: Option<Vec<i32>>,
+    signals: Option<SignalGuard>,
+    interrupted: Option<i32>,
+    pty: Option<Pty>,
+    cancel: Option<CancelHandle>,
+    status: Option<ExitStatus>,
+}
+
+impl EditorSession {
+    /// Spawn `command`, which is described as `description` in error messages.
+    ///
+    /// Besides success, the editor exiting with any of `accepted_codes` is not considered an
+    /// error; if `accepted_codes` is `None`, no exit code is.
+    ///
+    /// If `command` has its stderr piped, it is read in a background thread and included in the
+    /// error if the editor fails.
+    ///
+    /// If `signals` is given, signals it catches are forwarded to the editor, and it is dropped
+    /// (restoring the terminal) as soon as the editor exits.
+    ///
+    /// If `pty` is given, `command` must already be attached to it.
+    ///
+    /// If `cancel` is given, the editor is killed as soon as it is cancelled and the session is
+    /// checked.
+    pub(crate) fn spawn(
+        command: &mut Command,
+        description: String,
+        accepted_codes: Option<Vec<i32>>,
+        signals: Option<SignalGuard>,
+        mut pty: Option<Pty>,
+        cancel: Option<CancelHandle>,
+    ) -> Result<Self> {
+        let mut child = command.spawn()?;
+        if let Some(pty) = &mut pty {
+            pty.start()?;
+        }
+        let stderr = child.stderr.take().map(|mut pipe| {
+            thread::spawn(move || {
+                let mut buf = Vec::new();
+                let _ = pipe.read_to_end(&mut buf);
+                buf
+            })
+        });
+
+        Ok(Self {
+            child,
+            command: description,
+            stderr,
+            accepted_codes,
+            signals,
+            interrupted: None,
+            pty,
+            cancel,
+            status: None,
+        })
+    }
+
+    /// Close a piped stdin and discard anything written to a piped stdout, so that an editor
+    /// that's being waited on doesn't block on either pipe.
+    pub(crate) fn close_pipes(&mut self) {
+        drop(self.child.stdin.take());
+        if let Some(mut stdout) = self.child.stdout.take() {
+            thread::spawn(move || io::copy(&mut stdout, &mut io::sink()));
+        }
+    }
+
+    fn forward_signals(&mut self) {
+        if let Some(signals) = &self.signals {
+            if let Some(signal) = signals.take_signal() {
+                signals.forward(self.child.id(), signal);
+                self.interrupted = Some(signal);
+            }
+        }
+    }
+
+    fn restore_terminal(&mut self) {
+        if let Some(pty) = &mut self.pty {
+            pty.finish();
+        }
+        self.signals = None;
+    }
+
+    fn check(&mut self, status: ExitStatus) -> Result<()> {
+        // restore the terminal before anything else
+        self.restore_terminal();
+        self.status = Some(status);
+        if let Some(signal) = self.interrupted {
+            return Err(Error::new(
+                ErrorKind::Interrupted,
+                format!(
+                    "editor '{}' was interrupted by signal {}",
+                    self.command, signal
+                ),
+            ));
+        }
+
+        let stderr = self.stderr.take().and_then(|t| t.join().ok());
+        check_status(
+            &self.command,
+            status,
+            self.accepted_codes.as_deref(),
+            stderr,
+        )
+    }
+
+    /// Get the description of the editor's command used in error messages.
+    pub(crate) fn command(&self) -> &str {
+        &self.command
+    }
+
+    /// Get the exit status of the editor, if it has exited (and been waited for).
+    pub(crate) fn status(&self) -> Option<ExitStatus> {
+        self.status
+    }
+
+    /// Get the OS-assigned process identifier of the editor.
+    pub fn id(&self) -> u32 {
+        self.child.id()
+    }
+
+    /// Get a reference to the underlying [`Child`].
+    ///
+    /// [`Child`]: https://doc.rust-lang.org/std/process/struct.Child.html
+    pub fn child(&self) -> &Child {
+        &self.child
+    }
+
+    /// Get a mutable reference to the underlying [`Child`].
+    ///
+    /// [`Child`]: https://doc.rust-lang.org/std/process/struct.Child.html
+    pub fn child_mut(&mut self) -> &mut Child {
+        &mut self.child
+    }
+
+    /// Consume the session, returning the underlying [`Child`].
+    ///
+    /// [`Child`]: https://doc.rust-lang.org/std/process/struct.Child.html
+    pub fn into_child(self) -> Child {
+        self.child
+    }
+
+    /// Check whether the editor has exited, without blocking.
+    ///
+    /// Returns `Ok(None)` if the editor is still running, `Ok(Some(()))` if it exited
+    /// successfully, and an error if it exited with an error (as in [`edit_file`]).
+    ///
+    /// If the session was started with a [`CancelHandle`] that has since been cancelled, the
+    /// editor is killed and a [`Cancelled`] error is returned.
+    ///
+    /// [`edit_file`]: fn.edit_file.html
+    /// [`CancelHandle`]: struct.CancelHandle.html
+    /// [`Cancelled`]: struct.Cancelled.html
+    pub fn try_wait(&mut self) -> Result<Option<()>> {
+        if self.cancel.as_ref().is_some_and(|c| c.is_cancelled()) {
+            return self.cancelled();
+        }
+        self.forward_signals();
+        match self.child.try_wait()? {
+            Some(status) => self.check(status).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Wait for the editor to exit.
+    ///
+    /// Returns an error if the editor exited with an error, as in [`edit_file`].
+    ///
+    /// [`edit_file`]: fn.edit_file.html
+    pub fn wait(&mut self) -> Result<()> {
+        if self.signals.is_some() || self.cancel.is_some() {
+            // poll so that signals can be forwarded and cancellation noticed while waiting
+            loop {
+                if let Some(result) = self.try_wait()? {
+                    return Ok(result);
+                }
+                thread::sleep(POLL_INTERVAL);
+            }
+        }
+
+        let status = self.child.wait()?;
+        self.check(status)
+    }
+
+    /// Wait for the editor to exit, giving up after `timeout` has passed.
+    ///
+    /// Returns `Ok(None)` if the editor is still running after `timeout`; otherwise, this is the
+    /// same as [`wait`]. The editor is not killed when the timeout is reached.
+    ///
+    /// [`wait`]: #method.wait
+    pub fn wait_timeout(&mut self, timeout: Duration) -> Result<Option<()>> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(result) = self.try_wait()? {
+                return Ok(Some(result));
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                return Ok(None);
+            }
+            thread::sleep(POLL_INTERVAL.min(deadline - now));
+        }
+    }
+
+    /// Kill the editor.
+    ///
+    /// This does not wait for the editor to exit; use [`wait`] afterwards to reap it.
+    ///
+    /// [`wait`]: #method.wait
+    pub fn kill(&mut self) -> Result<()> {
+        self.child.kill()
+    }
+
+    /// Kill the editor and any processes it started.
+    ///
+    /// Some "editors" are really wrapper scripts that start the actual editor as a child process,
+    /// which [`kill`] would leave running. On Linux and Windows, this kills the whole process tree
+    /// instead; on other platforms, it is the same as `kill`.
+    ///
+    /// This does not wait for the editor to exit; use [`wait`] afterwards to reap it.
+    ///
+    /// [`kill`]: #method.kill
+    /// [`wait`]: #method.wait
+    pub fn kill_tree(&mut self) -> Result<()> {
+        let child = &mut self.child;
+        kill_tree(child.id(), || child.kill())
+    }
+
+    /// Wait for the editor to exit, killing it (and any processes it started) if it is still
+    /// running after `timeout`.
+    ///
+    /// If the editor was killed, returns [`ErrorKind::TimedOut`].
+    ///
+    /// [`ErrorKind::TimedOut`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.TimedOut
+    pub(crate) fn wait_or_kill(&mut self, timeout: Duration) -> Result<()> {
+        if self.wait_timeout(timeout)?.is_some() {
+            return Ok(());
+        }
+
+        self.timed_out(timeout)
+    }
+
+    fn cancelled<T>(&mut self) -> Result<T> {
+        self.kill_tree()?;
+        self.child.wait()?;
+        self.restore_terminal();
+        Err(Cancelled.into())
+    }
+
+    fn timed_out(&mut self, timeout: Duration) -> Result<()> {
+        self.kill_tree()?;
+        self.child.wait()?;
+        Err(Error::new(
+            ErrorKind::TimedOut,
+            format!(
+                "editor '{}' was killed after running for {:?}",
+                self.command, timeout
+            ),
+        ))
+    }
+
+    /// Wait for `file` to be saved, i.e. for its modification time or size to differ from
+    /// `before` (its state when the editor was spawned).
+    ///
+    /// The editor exiting successfully is *not* taken as a sign that editing is done, since many
+    /// GUI editors hand the file off to an existing window and exit right away. Once the file
+    /// has been saved, the editor is left running in the background.
+    ///
+    /// Returns the exit status of the editor, if it exited before the file was saved.
+    pub(crate) fn wait_for_save(
+        mut self,
+        file: &Path,
+        before: Option<FileState>,
+        timeout: Option<Duration>,
+    ) -> Result<Option<ExitStatus>> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        let mut exited = false;
+        loop {
+            if !exited {
+                exited = self.try_wait()?.is_some();
+            }
+
+            let state = FileState::of(file);
+            if state.is_some() && state != before {
+                wait_until_settled(file, state);
+                let status = self.status;
+                if !exited {
+                    // reap the editor whenever it does exit so it doesn't linger as a zombie
+                    thread::spawn(move || self.child.wait());
+                }
+                return Ok(status);
+            }
+
+            match (timeout, deadline) {
+                (Some(timeout), Some(deadline)) if Instant::now() >= deadline => {
+                    return if exited {
+                        Err(Error::new(
+                            ErrorKind::TimedOut,
+                            format!("file was not saved within {:?}", timeout),
+                        ))
+                    } else {
+                        self.timed_out(timeout).map(|()| None)
+                    };
+                }
+                _ => thread::sleep(POLL_INTERVAL),
+            }
+        }
+    }
+}
+
+/// Wait for `file` to stop changing, in case the editor is still writing it.
+pub(crate) fn wait_until_settled(file: &Path, mut state: Option<FileState>) {
+    loop {
+        thread::sleep(POLL_INTERVAL);
+        let next = FileState::of(file);
+        if next == state {
+            break;
+        }
+        state = next;
+    }
+}
+
+/// The modification time and size of a file, used to tell when it has been saved.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct FileState {
+    modified: SystemTime,
+    len: u64,
+}
+
+impl FileState {
+    pub fn of(path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+        Some(Self {
+            modified: metadata.modified().ok()?,
+            len: metadata.len(),
+        })
+    }
+}
+
+/// Turn the exit status of the editor described by `command` into a result.
+///
+/// Besides success, exiting with any of `accepted_codes` is not an error; if `accepted_codes` is
+/// `None`, no exit code is.
+pub(crate) fn check_status(
+    command: &str,
+    status: ExitStatus,
+    accepted_codes: Option<&[i32]>,
+    stderr: Option<Vec<u8>>,
+) -> Result<()> {
+    let accepted = match (accepted_codes, status.code()) {
+        (_, None) => false,
+        (None, Some(_)) => true,
+        (Some(codes), Some(code)) => codes.contains(&code),
+    };
+
+    if status.success() || accepted {
+        Ok(())
+    } else {
+        Err(Error::other(ExitError {
+            command: command.to_owned(),
+            status,
+            stderr,
+        }))
+    }
+}
+
+/// The error returned when an editor exits unsuccessfully.
+///
+/// This is wrapped in an [`io::Error`] of kind [`ErrorKind::Other`], and can be retrieved with
+/// [`io::Error::get_ref`]:
+///
+/// ```rust,ignore
+/// use edit::ExitError;
+///
+/// if let Err(e) = edit::Editor::new().capture_stderr(true).edit("text") {
+///     if let Some(exit) = e.get_ref().and_then(|e| e.downcast_ref::<ExitError>()) {
+///         eprintln!("editor printed: {}", String::from_utf8_lossy(exit.stderr().unwrap_or(&[])));
+///     }
+/// }
+/// ```
+///
+/// [`io::Error`]: https://doc.rust-lang.org/std/io/struct.Error.html
+/// [`ErrorKind::Other`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.Other
+/// [`io::Error::get_ref`]: https://doc.rust-lang.org/std/io/struct.Error.html#method.get_ref
+#[derive(Debug)]
+pub struct ExitError {
+    command: String,
+    status: ExitStatus,
+    stderr: Option<Vec<u8>>,
+}
+
+impl ExitError {
+    /// Get the command line that was run, for display purposes.
+    pub fn command(&self) -> &str {
+        &self.command
+    }
+
+    /// Get the exit status of the editor.
+    pub fn status(&self) -> ExitStatus {
+        self.status
+    }
+
+    /// Get everything the editor printed to stderr, if it was captured (see
+    /// [`Editor::capture_stderr`]).
+    ///
+    /// [`Editor::capture_stderr`]: struct.Editor.html#method.capture_stderr
+    pub fn stderr(&self) -> Option<&[u8]> {
+        self.stderr.as_deref()
+    }
+}
+
+impl fmt::Display for ExitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "editor '{}' exited with error: {}",
+            self.command, self.status
+        )?;
+        match &self.stderr {
+            Some(stderr) if !stderr.is_empty() => {
+                write!(f, "\n{}", String::from_utf8_lossy(stderr).trim_end())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl error::Error for ExitError {}
+
+/// Kill the process `pid` and all of its descendants, using `kill` to kill `pid` itself (so that
+/// it isn't confused with another process that reused its pid).
+#[cfg(target_os = "linux")]
+pub(crate) fn kill_tree<F: FnOnce() -> Result<()>>(pid: u32, kill: F) -> Result<()> {
+    use std::collections::HashMap;
+
+    // map every process to its children using the ppid field of /proc/<pid>/stat
+    let mut children: HashMap<i32, Vec<i32>> = HashMap::new();
+    for entry in fs::read_dir("/proc")?.filter_map(|e| e.ok()) {
+        let pid = match entry.file_name().to_str().and_then(|s| s.parse().ok()) {
+            Some(pid) => pid,
+            None => continue,
+        };
+        // the command name may contain spaces or parentheses, so skip past the last ')'
+        let ppid = fs::read_to_string(entry.path().join("stat"))
+            .ok()
+            .and_then(|stat| {
+                let rest = &stat[stat.rfind(')')? + 1..];
+                rest.split_whitespace().nth(1)?.parse().ok()
+            });
+        if let Some(ppid) = ppid {
+            children.entry(ppid).or_default().push(pid);
+        }
+    }
+
+    let mut tree = vec![pid as i32];
+    let mut i = 0;
+    while i < tree.len() {
+        if let Some(pids) = children.get(&tree[i]) {
+            tree.extend(pids);
+        }
+        i += 1;
+    }
+
+    kill()?;
+    for &pid in &tree[1..] {
+        unsafe {
+            libc::kill(pid, libc::SIGKILL);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+pub(crate) fn kill_tree<F: FnOnce() -> Result<()>>(pid: u32, kill: F) -> Result<()> {
+    let status = Command::new("taskkill.exe")
+        .args(["/F", "/T", "/PID"])
+        .arg(pid.to_string())
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+    match status {
+        Ok(status) if status.success() => Ok(()),
+        _ => kill(),
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+pub(crate) fn kill_tree<F: FnOnce() -> Result<()>>(_pid: u32, kill: F) -> Result<()> {
+    kill()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    fn exited(code: i32) -> ExitStatus {
+        std::os::unix::process::ExitStatusExt::from_raw(code << 8)
+    }
+
+    #[cfg(windows)]
+    fn exited(code: i32) -> ExitStatus {
+        std::os::windows::process::ExitStatusExt::from_raw(code as u32)
+    }
+
+    fn accepted(policy: ExitCodePolicy, editor: &str, accepted: &[i32]) -> Option<Vec<i32>> {
+        policy.accepted_codes(Path::new(editor), accepted)
+    }
+
+    #[test]
+    fn default_policy_is_known() {
+        assert_eq!(ExitCodePolicy::default(), ExitCodePolicy::Known);
+    }
+
+    #[test]
+    fn accepted_codes_by_policy() {
+        for (policy, editor, extra, expected) in [
+            (ExitCodePolicy::Known, "vim", &[][..], Some(&[1][..])),
+            (ExitCodePolicy::Known, "/usr/bin/VI", &[], Some(&[1])),
+            (ExitCodePolicy::Known, "gvim", &[2], Some(&[1, 2])),
+            (ExitCodePolicy::Known, "nvim", &[], Some(&[])),
+            (ExitCodePolicy::Known, "nano", &[2], Some(&[2])),
+            (ExitCodePolicy::Known, "unknown-editor", &[], Some(&[])),
+            (ExitCodePolicy::Strict, "vim", &[], Some(&[])),
+            (ExitCodePolicy::Strict, "vim", &[2], Some(&[2])),
+            (ExitCodePolicy::Ignore, "vim", &[2], None),
+            (ExitCodePolicy::Ignore, "nano", &[], None),
+        ] {
+            assert_eq!(
+                accepted(policy, editor, extra).as_deref(),
+                expected,
+                "{:?} {}",
+                policy,
+                editor
+            );
+        }
+    }
+
+    #[test]
+    fn check_exit_codes() {
+        for (code, accepted, ok) in [
+            (0, Some(&[][..]), true),
+            (0, None, true),
+            (1, Some(&[]), false),
+            (1, Some(&[1]), true),
+            (2, Some(&[1]), false),
+            (2, Some(&[1, 2]), true),
+            (3, None, true),
+        ] {
+            let result = check_status("ed", exited(code), accepted, None);
+            assert_eq!(result.is_ok(), ok, "{} {:?}", code, accepted);
+        }
+    }
+
+    #[test]
+    fn exit_error_details() {
+        let e = check_status("vim file", exited(1), Some(&[]), Some(b"oops".to_vec())).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::Other);
+        let exit = e.get_ref().unwrap().downcast_ref::<ExitError>().unwrap();
+        assert_eq!(exit.command(), "vim file");
+        assert_eq!(exit.status().code(), Some(1));
+        assert_eq!(exit.stderr(), Some(&b"oops"[..]));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn killed_by_signal() {
+        // a signal is an error even when exit codes are ignored
+        let status = std::os::unix::process::ExitStatusExt::from_raw(libc::SIGKILL);
+        assert!(check_status("vim", status, None, None).is_err());
+        assert!(check_status("vim", status, Some(&[1]), None).is_err());
+    }
+}
@@ -0,0 +1,121 @@
+//! Forwarding of SIGINT and SIGTERM to the editor, plus restoring the terminal afterwards.
+//!
+//! Only one set of handlers can be installed at a time, so edit sessions that forward signals
+//! shouldn't run concurrently.
+
+#[cfg(unix)]
+use std::{
+    io::{Error, Result},
+    mem,
+    sync::atomic::{AtomicI32, Ordering},
+};
+
+#[cfg(unix)]
+static RECEIVED: AtomicI32 = AtomicI32::new(0);
+
+#[cfg(unix)]
+const SIGNALS: [libc::c_int; 2] = [libc::SIGINT, libc::SIGTERM];
+
+#[cfg(unix)]
+extern "C" fn handler(signal: libc::c_int) {
+    RECEIVED.store(signal, Ordering::SeqCst);
+}
+
+/// Signal handlers installed for the duration of an edit session. Dropping this restores the
+/// previous handlers and the terminal modes saved when it was installed.
+#[cfg(unix)]
+pub(crate) struct SignalGuard {
+    old_actions: Vec<(libc::c_int, libc::sigaction)>,
+    termios: Option<libc::termios>,
+}
+
+#[cfg(unix)]
+impl SignalGuard {
+    pub fn install() -> Result<Self> {
+        let termios = unsafe {
+            let mut termios = mem::zeroed();
+            if libc::isatty(libc::STDIN_FILENO) == 1
+                && libc::tcgetattr(libc::STDIN_FILENO, &mut termios) == 0
+            {
+                Some(termios)
+            } else {
+                None
+            }
+        };
+
+        RECEIVED.store(0, Ordering::SeqCst);
+        let mut guard = Self {
+            old_actions: Vec::with_capacity(SIGNALS.len()),
+            termios,
+        };
+        for &signal in &SIGNALS {
+            unsafe {
+                let mut action: libc::sigaction = mem::zeroed();
+                action.sa_sigaction = handler as *const () as libc::sighandler_t;
+                action.sa_flags = libc::SA_RESTART;
+                libc::sigemptyset(&mut action.sa_mask);
+
+                let mut old_action = mem::zeroed();
+                if libc::sigaction(signal, &action, &mut old_action) != 0 {
+                    // dropping the guard restores any handlers that were already replaced
+                    return Err(Error::last_os_error());
+                }
+                guard.old_actions.push((signal, old_action));
+            }
+        }
+        Ok(guard)
+    }
+
+    /// Take the most recently received signal, if one was received since the last call.
+    pub fn take_signal(&self) -> Option<i32> {
+        match RECEIVED.swap(0, Ordering::SeqCst) {
+            0 => None,
+            signal => Some(signal),
+        }
+    }
+
+    pub fn forward(&self, pid: u32, signal: i32) {
+        unsafe {
+            libc::kill(pid as libc::pid_t, signal);
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for SignalGuard {
+    fn drop(&mut self) {
+        unsafe {
+            if let Some(termios) = &self.termios {
+                libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, termios);
+            }
+            for (signal, old_action) in &self.old_actions {
+                libc::sigaction(*signal, old_action, std::ptr::null_mut());
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+impl std::fmt::Debug for SignalGuard {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("SignalGuard").finish()
+    }
+}
+
+// on other platforms, the console already delivers Ctrl-C to every attached process
+#[cfg(not(unix))]
+#[derive(Debug)]
+pub(crate) struct SignalGuard;
+
+#[cfg(not(unix))]
+impl SignalGuard {
+    pub fn install() -> std::io::Result<Self> {
+        Ok(SignalGuard)
+    }
+
+    pub fn take_signal(&self) -> Option<i32> {
+        None
+    }
+
+    pub fn forward(&self, _pid: u32, _signal: i32) {}
+}
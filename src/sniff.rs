@@ -0,0 +1,111 @@
+//! Guessing the format of text from its contents, so temporary files can be given an extension
+//! that makes editors highlight it.
+
+/// Guess the file extension (with the dot) for `content`, if it looks like JSON, YAML, TOML, or
+/// Markdown.
+///
+/// Only cheap heuristics are used, so this can be wrong, but is usually right for the kind of
+/// text programs ask users to edit.
+pub(crate) fn suffix(content: &[u8]) -> Option<&'static str> {
+    let text = std::str::from_utf8(content).ok()?;
+    let text = text.trim_start_matches('\u{feff}').trim_start();
+
+    if text.starts_with('{') || (text.starts_with('[') && !is_table_header(first_line(text))) {
+        return Some(".json");
+    }
+    if first_line(text) == "---" {
+        return Some(".yaml");
+    }
+
+    // comments look the same in YAML and TOML (and like headings in Markdown), so the first line
+    // that isn't one decides between the two
+    let mut lines = text.lines().map(str::trim_end).filter(|l| !l.is_empty());
+    match lines.clone().find(|l| !l.starts_with('#')) {
+        Some(line) if is_table_header(line) || is_toml_pair(line) => return Some(".toml"),
+        Some(line) if is_yaml_pair(line) => return Some(".yaml"),
+        _ => {}
+    }
+
+    if lines.any(is_markdown) {
+        return Some(".md");
+    }
+    None
+}
+
+fn first_line(text: &str) -> &str {
+    text.lines().next().unwrap_or_default().trim_end()
+}
+
+fn is_key(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
+
+// `[table]` or `[[array.of.tables]]`
+fn is_table_header(line: &str) -> bool {
+    let name = line
+        .strip_prefix("[[")
+        .and_then(|l| l.strip_suffix("]]"))
+        .or_else(|| line.strip_prefix('[').and_then(|l| l.strip_suffix(']')));
+    name.is_some_and(|name| is_key(name.trim()))
+}
+
+// `key = value`
+fn is_toml_pair(line: &str) -> bool {
+    line.split_once('=')
+        .is_some_and(|(key, value)| is_key(key.trim()) && !value.trim().is_empty())
+}
+
+// `key: value` or `key:`
+fn is_yaml_pair(line: &str) -> bool {
+    line.split_once(':')
+        .is_some_and(|(key, value)| is_key(key) && (value.is_empty() || value.starts_with(' ')))
+}
+
+// a heading, code fence, or list item
+fn is_markdown(line: &str) -> bool {
+    let hashes = line.len() - line.trim_start_matches('#').len();
+    (1..=6).contains(&hashes) && line[hashes..].starts_with(' ')
+        || line.starts_with("```")
+        || line.starts_with("- ")
+        || line.starts_with("* ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn guess_suffixes() {
+        for (content, expected) in [
+            (&b"{\"name\": \"edit\"}\n"[..], Some(".json")),
+            (b"\xEF\xBB\xBF  {}", Some(".json")),
+            (b"[1, 2, 3]\n", Some(".json")),
+            (b"[\n  {\"a\": 1}\n]\n", Some(".json")),
+            (b"[package]\nname = \"edit\"\n", Some(".toml")),
+            (b"[[bin]]\nname = \"edit\"\n", Some(".toml")),
+            (b"---\nname: edit\n", Some(".yaml")),
+            (b"# settings\n\nname = \"edit\"\n", Some(".toml")),
+            (b"# settings\n\nname: edit\n", Some(".yaml")),
+            (b"list:\n  - a\n", Some(".yaml")),
+            (b"# Title\n\nSome text.\n", Some(".md")),
+            (b"Some text.\n\n```rust\nfn main() {}\n```\n", Some(".md")),
+            (b"Shopping:\n\n- eggs\n", Some(".yaml")),
+            (b"To buy\n\n- eggs\n", Some(".md")),
+            (b"Just some text.\n", None),
+            (b"#hashtag\n", None),
+            (b"", None),
+            (b"{\xFF}", None),
+            (b"name = \xFF\n", None),
+        ] {
+            assert_eq!(
+                suffix(content),
+                expected,
+                "{:?}",
+                String::from_utf8_lossy(content)
+            );
+        }
+    }
+}
@@ -0,0 +1,70 @@
+//! Editing values as serialized documents, like `kubectl edit` does with resources.
+
+use crate::{Editor, TempOptions};
+#[cfg(feature = "structured")]
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    error,
+    io::{Error, ErrorKind, Result},
+};
+
+impl Editor {
+    /// Open a value in the editor as a TOML document, and deserialize the edited document. See
+    /// [`edit_toml`].
+    ///
+    /// [`edit_toml`]: fn.edit_toml.html
+    #[cfg(feature = "structured")]
+    pub fn edit_toml<T: Serialize + DeserializeOwned>(&self, value: &T) -> Result<T> {
+        self.edit_serialized(toml::to_string_pretty(value), ".toml", toml::from_str)
+    }
+
+    /// Open a value in the editor as a JSON document, and deserialize the edited document. See
+    /// [`edit_json`].
+    ///
+    /// [`edit_json`]: fn.edit_json.html
+    #[cfg(feature = "json")]
+    pub fn edit_json<T>(&self, value: &T) -> Result<T>
+    where
+        T: serde::Serialize + serde::de::DeserializeOwned,
+    {
+        // end the document with a line ending, like editors do when saving it
+        let text = serde_json::to_string_pretty(value).map(|text| text + "\n");
+        self.edit_serialized(text, ".json", |text| serde_json::from_str(text))
+    }
+
+    /// Open a value in the editor as a YAML document, and deserialize the edited document. See
+    /// [`edit_yaml`].
+    ///
+    /// [`edit_yaml`]: fn.edit_yaml.html
+    #[cfg(feature = "yaml")]
+    pub fn edit_yaml<T>(&self, value: &T) -> Result<T>
+    where
+        T: serde::Serialize + serde::de::DeserializeOwned,
+    {
+        self.edit_serialized(serde_yaml::to_string(value), ".yaml", |text| {
+            serde_yaml::from_str(text)
+        })
+    }
+
+    /// Open the serialized `text` of a value in a temporary file ending in `suffix`, and parse
+    /// the edited text back into a value with `parse`.
+    fn edit_serialized<T, S, P, E>(
+        &self,
+        text: std::result::Result<String, S>,
+        suffix: &str,
+        parse: P,
+    ) -> Result<T>
+    where
+        S: Into<Box<dyn error::Error + Send + Sync>>,
+        P: FnOnce(&str) -> std::result::Result<T, E>,
+        E: Into<Box<dyn error::Error + Send + Sync>>,
+    {
+        let text = text.map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
+        let mut options = TempOptions::new();
+        // still let a suffix set on the editor win, like it would for any other text
+        if self.suffix.is_none() {
+            options.suffix(suffix);
+        }
+        self.edit_parsed_with(text, &options, parse)
+    }
+}
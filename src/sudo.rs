@@ -0,0 +1,97 @@
+//! Running editors as the user who ran `sudo`, rather than as root.
+
+#[cfg(unix)]
+use std::{
+    env,
+    ffi::{CStr, OsStr, OsString},
+    fs::File,
+    io::Result,
+    mem,
+    os::unix::{ffi::OsStrExt, fs::fchown, process::CommandExt},
+    path::{Path, PathBuf},
+    process::Command,
+    ptr,
+};
+#[cfg(not(unix))]
+use std::{fs::File, io::Result, process::Command};
+
+/// The user who ran `sudo`.
+#[cfg(unix)]
+pub(crate) struct SudoUser {
+    name: OsString,
+    uid: u32,
+    gid: u32,
+}
+
+// there's no sudo (and so no such user) elsewhere
+#[cfg(not(unix))]
+pub(crate) enum SudoUser {}
+
+/// Get the user who ran `sudo`, if this is running as root under `sudo`.
+#[cfg(unix)]
+pub(crate) fn invoking_user() -> Option<SudoUser> {
+    if unsafe { libc::geteuid() } != 0 {
+        return None;
+    }
+    let id = |var| env::var(var).ok()?.parse().ok();
+    Some(SudoUser {
+        name: env::var_os("SUDO_USER")?,
+        uid: id("SUDO_UID")?,
+        gid: id("SUDO_GID")?,
+    })
+}
+
+#[cfg(not(unix))]
+pub(crate) fn invoking_user() -> Option<SudoUser> {
+    None
+}
+
+/// Make `command` run as `user`, with the environment variables that say who the user is (and
+/// where their session is) set to match.
+#[cfg(unix)]
+pub(crate) fn run_as(command: &mut Command, user: &SudoUser) {
+    command
+        .uid(user.uid)
+        .gid(user.gid)
+        .env("USER", &user.name)
+        .env("LOGNAME", &user.name);
+    if let Some(home) = home_dir(user.uid) {
+        command.env("HOME", home);
+    }
+    // sudo doesn't keep XDG_RUNTIME_DIR, which Wayland and D-Bus need
+    let runtime_dir = Path::new("/run/user").join(user.uid.to_string());
+    if runtime_dir.is_dir() {
+        command.env("XDG_RUNTIME_DIR", runtime_dir);
+    }
+}
+
+#[cfg(not(unix))]
+pub(crate) fn run_as(_command: &mut Command, user: &SudoUser) {
+    match *user {}
+}
+
+/// Give `file` to `user`, so editors run as them can write to it.
+#[cfg(unix)]
+pub(crate) fn give(file: &File, user: &SudoUser) -> Result<()> {
+    fchown(file, Some(user.uid), Some(user.gid))
+}
+
+#[cfg(not(unix))]
+pub(crate) fn give(_file: &File, user: &SudoUser) -> Result<()> {
+    match *user {}
+}
+
+/// Look up the home directory of the user with the ID `uid`.
+#[cfg(unix)]
+fn home_dir(uid: u32) -> Option<PathBuf> {
+    let mut buf = vec![0; 16 * 1024];
+    let mut passwd: libc::passwd = unsafe { mem::zeroed() };
+    let mut result = ptr::null_mut();
+    let ret =
+        unsafe { libc::getpwuid_r(uid, &mut passwd, buf.as_mut_ptr(), buf.len(), &mut result) };
+    if ret != 0 || result.is_null() || passwd.pw_dir.is_null() {
+        return None;
+    }
+    let dir = unsafe { CStr::from_ptr(passwd.pw_dir) };
+    Some(Path::new(OsStr::from_bytes(dir.to_bytes())).to_owned())
+}
@@ -0,0 +1,86 @@
+//! Options for the temporary files strings and buffers are edited in.
+
+use std::{
+    ffi::{OsStr, OsString},
+    fs::Permissions,
+    path::{Path, PathBuf},
+};
+use tempfile::Builder;
+
+/// Options for creating the temporary file a string or buffer is edited in, for use with e.g.
+/// [`edit_with_builder`].
+///
+/// Any option that isn't set is taken from the [`Editor`] doing the editing (e.g. from
+/// [`Editor::suffix`]), or left at its default.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let mut options = edit::TempOptions::new();
+/// options.prefix("COMMIT_EDITMSG-").suffix(".txt").dir(".git");
+/// let message = edit::edit_with_builder("", &options)?;
+/// ```
+///
+/// [`edit_with_builder`]: fn.edit_with_builder.html
+/// [`Editor`]: struct.Editor.html
+/// [`Editor::suffix`]: struct.Editor.html#method.suffix
+#[derive(Clone, Debug, Default)]
+pub struct TempOptions {
+    pub(crate) prefix: Option<OsString>,
+    pub(crate) suffix: Option<OsString>,
+    pub(crate) dir: Option<PathBuf>,
+    pub(crate) permissions: Option<Permissions>,
+}
+
+impl TempOptions {
+    /// Create a new `TempOptions` with nothing set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set a prefix for the name of the temporary file. The default is `.tmp`.
+    pub fn prefix<S: AsRef<OsStr>>(&mut self, prefix: S) -> &mut Self {
+        self.prefix = Some(prefix.as_ref().to_owned());
+        self
+    }
+
+    /// Set a suffix for the name of the temporary file, like `".md"`. The default is no suffix.
+    pub fn suffix<S: AsRef<OsStr>>(&mut self, suffix: S) -> &mut Self {
+        self.suffix = Some(suffix.as_ref().to_owned());
+        self
+    }
+
+    /// Set the directory to create the temporary file in. See [`Editor::temp_dir`].
+    ///
+    /// [`Editor::temp_dir`]: struct.Editor.html#method.temp_dir
+    pub fn dir<P: AsRef<Path>>(&mut self, dir: P) -> &mut Self {
+        self.dir = Some(dir.as_ref().to_owned());
+        self
+    }
+
+    /// Set the permissions to create the temporary file with. The default is to only let the
+    /// current user read and write it.
+    ///
+    /// This is ignored in [secure mode], which always uses the default.
+    ///
+    /// [secure mode]: struct.Editor.html#method.secure
+    pub fn permissions(&mut self, permissions: Permissions) -> &mut Self {
+        self.permissions = Some(permissions);
+        self
+    }
+
+    /// Get a [`Builder`] that creates temporary files with these options.
+    pub(crate) fn builder(&self, secure: bool) -> Builder<'_, '_> {
+        let mut builder = Builder::new();
+        if let Some(prefix) = &self.prefix {
+            builder.prefix(prefix);
+        }
+        if let Some(suffix) = &self.suffix {
+            builder.suffix(suffix);
+        }
+        if let Some(permissions) = self.permissions.clone().filter(|_| !secure) {
+            builder.permissions(permissions);
+        }
+        builder
+    }
+}
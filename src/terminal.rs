@@ -0,0 +1,129 @@
+//! Running terminal editors in a new terminal emulator window, for when there's no terminal to run
+//! them in (e.g. in a GUI application).
+
+#[cfg(not(target_os = "windows"))]
+use crate::get_full_editor_cmd;
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+use crate::gui;
+#[cfg(target_os = "macos")]
+use crate::shell_quote;
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+use std::env;
+use std::process::Command;
+#[cfg(not(target_os = "windows"))]
+use std::{
+    ffi::{OsStr, OsString},
+    path::PathBuf,
+};
+
+/// Terminal emulators to try, and the arguments that make them run the command after them and
+/// wait for it to exit.
+#[cfg(not(any(
+    target_os = "windows",
+    target_os = "macos",
+    target_os = "android",
+    target_os = "haiku"
+)))]
+#[rustfmt::skip]
+static EMULATORS: &[&str] = &[
+    // Debian's alternative for the default terminal emulator
+    "x-terminal-emulator -e",
+    "gnome-terminal --wait --", "konsole --nofork -e", "xfce4-terminal --disable-server -x",
+    "alacritty -e", "kitty", "foot", "wezterm start --always-new-process --", "xterm -e",
+];
+
+#[cfg(target_os = "macos")]
+#[rustfmt::skip]
+static EMULATORS: &[&str] = &[
+    "alacritty -e", "kitty", "wezterm start --always-new-process --",
+];
+
+// Termux and Haiku apps always have a terminal
+#[cfg(any(target_os = "android", target_os = "haiku"))]
+static EMULATORS: &[&str] = &[];
+
+// Terminal.app can't be told to run a command with arguments, so the command is quoted for the
+// shell and typed into a new window, which is polled until the command exits
+#[cfg(target_os = "macos")]
+static TERMINAL_APP_SCRIPT: &[&str] = &[
+    "on run argv",
+    "tell application \"Terminal\"",
+    "set t to do script (item 1 of argv)",
+    "repeat while busy of t",
+    "delay 0.2",
+    "end repeat",
+    "end tell",
+    "end run",
+];
+
+/// Check whether a terminal emulator can be started.
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+pub(crate) fn available() -> bool {
+    gui::display_available() && emulator().is_some()
+}
+
+// Terminal.app and consoles are always there
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+pub(crate) fn available() -> bool {
+    true
+}
+
+/// Make `command` run in a new terminal emulator window. If there's no terminal emulator to run it
+/// in, it is returned unchanged.
+#[cfg(not(target_os = "windows"))]
+pub(crate) fn wrap(command: Command) -> Command {
+    let (program, mut args) = match emulator() {
+        Some(emulator) => emulator,
+        #[cfg(target_os = "macos")]
+        None => {
+            let mut args: Vec<OsString> = TERMINAL_APP_SCRIPT
+                .iter()
+                .flat_map(|line| ["-e", line])
+                .map(OsString::from)
+                .collect();
+            let words = Some(command.get_program())
+                .into_iter()
+                .chain(command.get_args());
+            let line: Vec<String> = words.map(shell_quote).collect();
+            args.push(format!("{}; exit", line.join(" ")).into());
+            let mut wrapped = Command::new("osascript");
+            wrapped.args(args);
+            return wrapped;
+        }
+        #[cfg(not(target_os = "macos"))]
+        None => return command,
+    };
+    args.push(command.get_program().to_owned());
+    args.extend(command.get_args().map(OsStr::to_owned));
+    let mut wrapped = Command::new(program);
+    wrapped.args(args);
+    wrapped
+}
+
+// console programs started with a new console get a window of their own, which is shown in the
+// default terminal (e.g. Windows Terminal, if it's set as the default)
+#[cfg(target_os = "windows")]
+pub(crate) fn wrap(mut command: Command) -> Command {
+    use std::os::windows::process::CommandExt;
+
+    const CREATE_NEW_CONSOLE: u32 = 0x0000_0010;
+    command.creation_flags(CREATE_NEW_CONSOLE);
+    command
+}
+
+/// Find the terminal emulator to use: the one named by the `TERMINAL` environment variable, if
+/// it's set, or else the first one in `EMULATORS` that can be found.
+#[cfg(not(target_os = "windows"))]
+fn emulator() -> Option<(PathBuf, Vec<OsString>)> {
+    #[cfg(not(target_os = "macos"))]
+    if let Some((program, mut args)) = env::var_os("TERMINAL")
+        .filter(|v| !v.is_empty())
+        .and_then(|terminal| get_full_editor_cmd(&terminal).ok())
+    {
+        args.push("-e".into());
+        return Some((program, args));
+    }
+    EMULATORS
+        .iter()
+        .find_map(|emulator| get_full_editor_cmd(OsStr::new(emulator)).ok())
+}
@@ -0,0 +1,32 @@
+//! Detecting CI runners and containers, where there's usually nobody around to use an editor.
+
+use std::{env, path::Path};
+
+// set by CI services that don't set CI
+static CI_VARS: &[&str] = &[
+    "JENKINS_URL",
+    "TF_BUILD",
+    "TEAMCITY_VERSION",
+    "CODEBUILD_BUILD_ID",
+];
+
+// created by docker and podman, respectively
+static CONTAINER_FILES: &[&str] = &["/.dockerenv", "/run/.containerenv"];
+
+/// Check whether this seems to be running unattended, and if so, describe where (e.g. "in CI").
+///
+/// Containers only count if `has_terminal` is false, since an interactive container (e.g.
+/// `docker run -it`) can be used like any other system.
+pub(crate) fn detect(has_terminal: bool) -> Option<&'static str> {
+    let ci = env::var("CI").is_ok_and(|v| !matches!(v.as_str(), "" | "0" | "false"))
+        || CI_VARS.iter().any(|var| env::var_os(var).is_some());
+    if ci {
+        return Some("in CI");
+    }
+    let container = CONTAINER_FILES.iter().any(|file| Path::new(file).exists())
+        || env::var_os("KUBERNETES_SERVICE_HOST").is_some();
+    if container && !has_terminal {
+        return Some("in a container without a terminal");
+    }
+    None
+}
@@ -0,0 +1,97 @@
+//! Finding out which version of an editor is installed.
+
+use crate::registry;
+use std::{
+    fmt,
+    io::Result,
+    path::Path,
+    process::{Command, Stdio},
+};
+
+/// The version of an editor, as returned by [`editor_version`].
+///
+/// Editors print their versions in all sorts of formats, so only the first number that looks like
+/// a version (e.g. `9.1` in `VIM - Vi IMproved 9.1 (2024 Jan 02, ...)`) is parsed. Missing
+/// components are 0.
+///
+/// [`editor_version`]: fn.editor_version.html
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EditorVersion {
+    major: u64,
+    minor: u64,
+    patch: u64,
+    text: String,
+}
+
+impl EditorVersion {
+    /// Get the major version, e.g. 1 for Visual Studio Code 1.85.2.
+    pub fn major(&self) -> u64 {
+        self.major
+    }
+
+    /// Get the minor version, e.g. 85 for Visual Studio Code 1.85.2.
+    pub fn minor(&self) -> u64 {
+        self.minor
+    }
+
+    /// Get the patch version, e.g. 2 for Visual Studio Code 1.85.2.
+    pub fn patch(&self) -> u64 {
+        self.patch
+    }
+
+    /// Get the line of the editor's output the version was found in.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Check whether this version is at least `major.minor.patch`.
+    pub fn at_least(&self, major: u64, minor: u64, patch: u64) -> bool {
+        (self.major, self.minor, self.patch) >= (major, minor, patch)
+    }
+
+    fn parse(output: &str) -> Option<Self> {
+        output.lines().find_map(|line| {
+            let word = line
+                .split(|c: char| c.is_whitespace() || c == ',' || c == '(')
+                .map(|word| word.strip_prefix('v').unwrap_or(word))
+                .find(|word| {
+                    word.starts_with(|c: char| c.is_ascii_digit()) && word.contains('.')
+                })?;
+            let mut numbers = word.split('.').map(|part| {
+                let end = part
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(part.len());
+                part[..end].parse().ok()
+            });
+            Some(EditorVersion {
+                major: numbers.next()??,
+                minor: numbers.next()??,
+                patch: numbers.next().flatten().unwrap_or(0),
+                text: line.trim().to_owned(),
+            })
+        })
+    }
+}
+
+impl fmt::Display for EditorVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Run `editor` to find out its version. Returns `Ok(None)` if it isn't known how to ask
+/// `editor` for its version, or if its answer doesn't contain one.
+pub(crate) fn probe(editor: &Path) -> Result<Option<EditorVersion>> {
+    let args = match registry::lookup(editor).and_then(|known| known.version_args) {
+        Some(args) => args,
+        None => return Ok(None),
+    };
+    // some editors print their version to stderr
+    let output = Command::new(editor)
+        .args(args)
+        .stdin(Stdio::null())
+        .output()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    Ok(EditorVersion::parse(&stdout).or_else(|| EditorVersion::parse(&stderr)))
+}
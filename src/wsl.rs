@@ -0,0 +1,61 @@
+//! Running Windows editors from inside the Windows Subsystem for Linux.
+//!
+//! Windows programs can be run from WSL (e.g. `notepad.exe`), but they only understand Windows
+//! paths, so the path of the file to edit has to be translated with `wslpath` first.
+
+use std::path::{Path, PathBuf};
+#[cfg(target_os = "linux")]
+use std::{env, ffi::OsString, fs, os::unix::ffi::OsStringExt, process::Command, sync::OnceLock};
+
+/// Windows editors to try inside WSL after the usual Linux ones.
+#[rustfmt::skip]
+pub(crate) static WSL_NAMES: &[&str] = &[
+    "code.exe -n -w",
+    // notepad++ only blocks if it's a separate instance
+    "notepad++.exe -multiInst -nosession",
+    "notepad.exe",
+];
+
+/// Check whether this is running inside WSL.
+#[cfg(target_os = "linux")]
+pub(crate) fn is_wsl() -> bool {
+    static IS_WSL: OnceLock<bool> = OnceLock::new();
+    *IS_WSL.get_or_init(|| {
+        env::var_os("WSL_DISTRO_NAME").is_some()
+            || fs::read_to_string("/proc/version")
+                .is_ok_and(|version| version.to_lowercase().contains("microsoft"))
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn is_wsl() -> bool {
+    false
+}
+
+/// Check whether `editor` is a Windows program (as opposed to a Linux one) in WSL.
+pub(crate) fn is_windows_program(editor: &Path) -> bool {
+    is_wsl()
+        && editor
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("exe"))
+}
+
+/// Translate a Linux path into one that Windows programs can open (e.g.
+/// `\\wsl.localhost\Ubuntu\tmp\file.txt`), or `None` if `wslpath` fails.
+#[cfg(target_os = "linux")]
+pub(crate) fn windows_path(path: &Path) -> Option<PathBuf> {
+    let output = Command::new("wslpath").arg("-w").arg(path).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let mut path = output.stdout;
+    while path.last().is_some_and(u8::is_ascii_whitespace) {
+        path.pop();
+    }
+    Some(OsString::from_vec(path).into())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn windows_path(_path: &Path) -> Option<PathBuf> {
+    None
+}